@@ -0,0 +1,18 @@
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use imp_encode::{bytes_to_points, bytes_to_points_n};
+
+fn points(c: &mut Criterion) {
+    let bytes: Vec<u8> = (0..1024 * 1024).map(|_| rand::random::<u8>()).collect();
+    let mut group = c.benchmark_group("bytes_to_points_1mb");
+    group.throughput(Throughput::Bytes(bytes.len() as u64));
+    group.bench_function("grouped", |b| b.iter(|| bytes_to_points(black_box(&bytes))));
+    group.bench_function("scalar", |b| {
+        b.iter(|| bytes_to_points_n(black_box(&bytes), 5))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, points);
+criterion_main!(benches);
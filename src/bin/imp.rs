@@ -0,0 +1,140 @@
+use std::io::{Read, Write};
+use std::process::ExitCode;
+
+use imp_encode::{
+    BoxEncoding, BoxLayoutConfig, CursedConfig, CursedEncoding, Encoding, FutharkEncoding,
+    ALPHA_NUM, FUTHARK,
+};
+
+const USAGE: &str = "Usage: imp <encode|decode> [options]
+
+Encode reads bytes from stdin and writes the encoded text to stdout. Decode does the reverse.
+Box diagrams may decode with extra zero bytes after the data.
+
+Options:
+    --futhark            Write runes from the futhark alphabet (the default)
+    --alpha              Write runes from the alphanumeric alphabet
+    --boxes              Draw box diagrams
+    --cursed             Spread diactrics over the text given by --text
+    --text TEXT          The cover text for cursed encoding
+    --max-width N        Limit the width of box diagrams
+    --max-height N       Limit the height of box diagrams
+    --break STYLE        Break between cursed diactrics with zwsp, zwnj, zwj, mvs, or none
+    --help               Show this message";
+
+enum Format {
+    Futhark,
+    Alpha,
+    Boxes,
+    Cursed,
+}
+
+struct Args {
+    decode: bool,
+    format: Format,
+    box_config: BoxLayoutConfig,
+    curse_break: Option<String>,
+    text: Option<String>,
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Args, String> {
+    let decode = match args.next().as_deref() {
+        Some("encode") => false,
+        Some("decode") => true,
+        Some(other) => return Err(format!("unknown command: {}", other)),
+        None => return Err("missing command".to_string()),
+    };
+    let mut parsed = Args {
+        decode,
+        format: Format::Futhark,
+        box_config: BoxLayoutConfig::default(),
+        curse_break: None,
+        text: None,
+    };
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| format!("{} needs a value", arg));
+        match arg.as_str() {
+            "--futhark" => parsed.format = Format::Futhark,
+            "--alpha" => parsed.format = Format::Alpha,
+            "--boxes" => parsed.format = Format::Boxes,
+            "--cursed" => parsed.format = Format::Cursed,
+            "--text" => parsed.text = Some(value()?),
+            "--max-width" => parsed.box_config.max_width = Some(parse_number(&value()?)?),
+            "--max-height" => parsed.box_config.max_height = Some(parse_number(&value()?)?),
+            "--break" => parsed.curse_break = Some(value()?),
+            _ => return Err(format!("unknown option: {}", arg)),
+        }
+    }
+    Ok(parsed)
+}
+
+fn parse_number(value: &str) -> Result<usize, String> {
+    value
+        .parse()
+        .map_err(|_| format!("not a number: {}", value))
+}
+
+fn cursed_config(curse_break: Option<&str>) -> Result<CursedConfig, String> {
+    let config = CursedConfig::new();
+    Ok(match curse_break {
+        None => config,
+        Some("zwsp") => config.with_zwsp_break(),
+        Some("zwnj") => config.with_zwnj_break(),
+        Some("zwj") => config.with_zwj_break(),
+        Some("mvs") => config.with_mvs_break(),
+        Some("none") => config.with_no_break(),
+        Some(other) => return Err(format!("unknown break style: {}", other)),
+    })
+}
+
+fn encoding(args: Args) -> Result<Box<dyn Encoding>, String> {
+    Ok(match args.format {
+        Format::Futhark => Box::new(FutharkEncoding(FUTHARK.clone())),
+        Format::Alpha => Box::new(FutharkEncoding(ALPHA_NUM.clone())),
+        Format::Boxes => Box::new(BoxEncoding(args.box_config)),
+        Format::Cursed => Box::new(CursedEncoding(
+            cursed_config(args.curse_break.as_deref())?,
+            // Decoding only needs the diactrics, so the cover text is only required to encode.
+            match args.text {
+                Some(text) => text,
+                None if args.decode => String::new(),
+                None => return Err("--cursed needs --text to encode".to_string()),
+            },
+        )),
+    })
+}
+
+fn run(args: Args) -> Result<(), String> {
+    let decode = args.decode;
+    let encoding = encoding(args)?;
+    let mut input = Vec::new();
+    std::io::stdin()
+        .read_to_end(&mut input)
+        .map_err(|e| e.to_string())?;
+    let output = if decode {
+        let text = String::from_utf8(input).map_err(|e| e.to_string())?;
+        encoding.decode_bytes(&text).map_err(|e| e.to_string())?
+    } else {
+        let mut text = encoding.encode_bytes(&input).map_err(|e| e.to_string())?;
+        text.push('\n');
+        text.into_bytes()
+    };
+    std::io::stdout()
+        .write_all(&output)
+        .map_err(|e| e.to_string())
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.iter().any(|arg| arg == "--help") {
+        println!("{}", USAGE);
+        return ExitCode::SUCCESS;
+    }
+    match parse_args(args.into_iter()).and_then(run) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(message) => {
+            eprintln!("imp: {}\n\n{}", message, USAGE);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -1,795 +1,3378 @@
-use unicode_segmentation::UnicodeSegmentation;
-
-const TOP_LEFT: &str = "\u{250C}\u{250D}\u{250E}\u{250F}";
-const TOP_RIGHT: &str = "\u{2510}\u{2511}\u{2512}\u{2513}";
-const BOTTOM_LEFT: &str = "\u{2514}\u{2515}\u{2516}\u{2517}";
-const BOTTOM_RIGHT: &str = "\u{2518}\u{2519}\u{251A}\u{251B}";
-const LEFT: &str = "\u{251C}\u{251D}\u{251E}\u{251F}\u{2520}\u{2521}\u{2522}\u{2523}";
-const RIGHT: &str = "\u{2524}\u{2525}\u{2526}\u{2527}\u{2528}\u{2529}\u{252A}\u{252B}";
-const TOP: &str = "\u{252C}\u{252D}\u{252E}\u{252F}\u{2530}\u{2531}\u{2532}\u{2533}";
-const BOTTOM: &str = "\u{2534}\u{2535}\u{2536}\u{2537}\u{2538}\u{2539}\u{253A}\u{253B}";
-const CROSS: &str = "\u{253C}\u{253D}\u{253E}\u{253F}\u{2540}\u{2541}\u{2542}\u{2543}\u{2544}\u{2545}\u{2546}\u{2547}\u{2548}\u{2549}\u{254A}\u{254B}";
-const HORIZONTAL: &str = "\u{2500}\u{257C}\u{2501}\u{257E}";
-const VERTICAL: &str = "\u{2502}\u{257D}\u{2503}\u{257F}";
-
-#[derive(Default, Debug)]
-pub struct BoxLayoutConfig {
-    pub min_width: Option<usize>,
-    pub max_width: Option<usize>,
-    pub min_height: Option<usize>,
-    pub max_height: Option<usize>,
-    pub aspect_ratio: Option<f32>,
-    pub blackouts: Vec<(usize, usize, String)>,
-}
-
-const FILLED: &str = "#";
-
-/**
-Defines a 2d layout of data vertices.
-*/
-pub struct BoxLayout(pub Vec<Vec<String>>);
-
-impl BoxLayout {
-    pub fn new(width: usize, height: usize) -> Self {
-        BoxLayout(vec![vec![FILLED.to_string(); width]; height])
-    }
-
-    pub fn width(&self) -> usize {
-        self.0[0].len()
-    }
-
-    pub fn height(&self) -> usize {
-        self.0.len()
-    }
-
-    pub fn calculate_bits(&self) -> usize {
-        let mut bit_count = 0;
-        for (y, row) in self.0.iter().enumerate() {
-            for (x, value) in row.iter().enumerate() {
-                if value == FILLED {
-                    let mut active_neighbors = 0;
-                    // We only count towards cells we can't seen yet.
-                    // Each neighbor has two bits: one for the neighbor, and one for the current cell.
-                    if x < self.0[y].len() - 1 && self.0[y][x + 1] == FILLED {
-                        active_neighbors += 2;
-                    }
-                    if y < self.0.len() - 1 && self.0[y + 1][x] == FILLED {
-                        active_neighbors += 2;
-                    }
-                    bit_count += active_neighbors;
-                }
-            }
-        }
-        bit_count
-    }
-
-    pub fn estimate_bits(width: usize, height: usize) -> usize {
-        let length_wise = (width - 1) * 2 * height;
-        let height_wise = (height - 1) * 2 * width;
-        length_wise + height_wise
-    }
-
-    pub fn is_filled(&self, x: usize, y: usize) -> bool {
-        self.0[y][x] == FILLED
-    }
-
-    pub fn get_blackout_at(&self, x: usize, y: usize) -> Option<&str> {
-        self.0.get(y).and_then(|row| row.get(x)).and_then(|s| {
-            if s.eq(FILLED) {
-                None
-            } else {
-                Some(s.as_str())
-            }
-        })
-    }
-
-    pub fn get_connections_at(&self, x: usize, y: usize) -> Option<Connections> {
-        if !self.is_filled(x, y) {
-            return None;
-        }
-        let right = x < self.width() - 1 && self.is_filled(x + 1, y);
-        let left = x > 0 && self.is_filled(x - 1, y);
-        let down = y < self.height() - 1 && self.is_filled(x, y + 1);
-        let up = y > 0 && self.is_filled(x, y - 1);
-        match (right, left, down, up) {
-            (true, true, false, false) => Some(Connections::RightLeft),
-            (false, false, true, true) => Some(Connections::DownUp),
-            (true, false, true, false) => Some(Connections::RightDown),
-            (false, true, true, false) => Some(Connections::LeftDown),
-            (true, false, false, true) => Some(Connections::RightUp),
-            (false, true, false, true) => Some(Connections::LeftUp),
-            (true, true, true, false) => Some(Connections::RightLeftDown),
-            (true, true, false, true) => Some(Connections::RightLeftUp),
-            (true, false, true, true) => Some(Connections::RightDownUp),
-            (false, true, true, true) => Some(Connections::LeftDownUp),
-            (true, true, true, true) => Some(Connections::All),
-            _ => None,
-        }
-    }
-
-    // Unlike Base32 futhark encoding, we have variable bits per point.
-    pub fn bytes_to_points(&self, bytes: &[u8]) -> Vec<u8> {
-        let mut results = Vec::new();
-        let mut bits: u32 = 0;
-        let mut offset = 0;
-        let mut x = 0;
-        let mut y = 0;
-        for byte in bytes {
-            bits |= (*byte as u32) << offset;
-            offset += 8;
-            'push_bits: loop {
-                if let Some(connection) = self.get_connections_at(x, y) {
-                    let connection_bits = connection.get_bits();
-                    if offset >= connection_bits {
-                        results.push((bits & ((1 << connection_bits) - 1)) as u8);
-                        bits >>= connection_bits;
-                        offset -= connection_bits;
-                    } else {
-                        break 'push_bits;
-                    }
-                }
-                if x < self.width() - 1 {
-                    x += 1;
-                } else {
-                    x = 0;
-                    y += 1;
-                }
-                if offset == 0 {
-                    break 'push_bits;
-                }
-            }
-        }
-        results
-    }
-
-    pub fn display_bytes(&self, bytes: &[u8]) -> String {
-        let mut result = String::new();
-        let points = self.bytes_to_points(bytes);
-        let mut x = 0;
-        let mut y = 0;
-        for point in points {
-            let mut pushed_point = false;
-            while !pushed_point {
-                if let Some(blackout) = self.get_blackout_at(x, y) {
-                    result.push_str(blackout);
-                } else if let Some(connection) = self.get_connections_at(x, y) {
-                    result.push(connection.get_character(point));
-                    pushed_point = true;
-                }
-                if x < self.width() - 1 {
-                    x += 1;
-                } else {
-                    x = 0;
-                    y += 1;
-                    if y < self.height() {
-                        result.push('\n');
-                    }
-                }
-            }
-        }
-        while y < self.height() {
-            'push_str: loop {
-                if let Some(connection) = self.get_connections_at(x, y) {
-                    result.push(connection.get_character(0));
-                } else if let Some(blackout) = self.get_blackout_at(x, y) {
-                    result.push_str(blackout);
-                } else {
-                    result.push(' ');
-                }
-                if x < self.width() - 1 {
-                    x += 1;
-                } else {
-                    break 'push_str;
-                }
-            }
-            x = 0;
-            y += 1;
-            if y < self.height() {
-                result.push('\n');
-            }
-        }
-        result
-    }
-}
-
-#[derive(Clone, Copy, Debug)]
-pub enum Connections {
-    RightDown,
-    LeftDown,
-    RightUp,
-    LeftUp,
-    DownUp,
-    RightLeft,
-    RightLeftDown,
-    RightLeftUp,
-    RightDownUp,
-    LeftDownUp,
-    All,
-}
-
-impl Connections {
-    pub fn get_bits(self) -> usize {
-        match self {
-            Connections::RightDown => 2,
-            Connections::LeftDown => 2,
-            Connections::RightUp => 2,
-            Connections::LeftUp => 2,
-            Connections::DownUp => 2,
-            Connections::RightLeft => 2,
-            Connections::RightLeftDown => 3,
-            Connections::RightLeftUp => 3,
-            Connections::RightDownUp => 3,
-            Connections::LeftDownUp => 3,
-            Connections::All => 4,
-        }
-    }
-
-    pub fn get_character(self, point: u8) -> char {
-        let my_chars = match self {
-            Connections::RightDown => TOP_LEFT,
-            Connections::LeftDown => TOP_RIGHT,
-            Connections::RightUp => BOTTOM_LEFT,
-            Connections::LeftUp => BOTTOM_RIGHT,
-            Connections::DownUp => VERTICAL,
-            Connections::RightLeft => HORIZONTAL,
-            Connections::RightLeftDown => TOP,
-            Connections::RightLeftUp => BOTTOM,
-            Connections::RightDownUp => LEFT,
-            Connections::LeftDownUp => RIGHT,
-            Connections::All => CROSS,
-        };
-        my_chars
-            .graphemes(true)
-            .nth(point as usize)
-            .unwrap()
-            .chars()
-            .next()
-            .unwrap()
-    }
-}
-
-pub fn layout_byte_length(length: usize, config: Option<BoxLayoutConfig>) -> Option<BoxLayout> {
-    let bit_length = length * 8;
-    let mut min_width = config.as_ref().and_then(|c| c.min_width).unwrap_or(2);
-    let mut min_height = config.as_ref().and_then(|c| c.min_height).unwrap_or(2);
-    let max_width = config
-        .as_ref()
-        .and_then(|c| c.max_width)
-        .unwrap_or(bit_length);
-    let max_height = config
-        .as_ref()
-        .and_then(|c| c.max_height)
-        .unwrap_or(bit_length);
-    let aspect_ratio = config.as_ref().and_then(|c| c.aspect_ratio).unwrap_or(1.0);
-    for (left, top, value) in config
-        .as_ref()
-        .and_then(|c| Some(c.blackouts.clone()))
-        .unwrap_or_default()
-    {
-        // We want to have a box around any text, so we need to add 1 past that.
-        // If the user wants to center the text, they can add their own whitespace.
-        min_width = min_width.max(left + value.len() + 1);
-        min_height = min_height.max(top + 1);
-    }
-    // We establish the base layout, with everything filled in...
-    let mut layout = BoxLayout::new(min_width, min_height);
-    // And then we blackout the areas that the user wants to blackout.
-    for (left, top, value) in config
-        .as_ref()
-        .and_then(|c| Some(c.blackouts.clone()))
-        .unwrap_or_default()
-    {
-        for (i, c) in value.chars().enumerate() {
-            layout.0[top][left + i] = c.to_string();
-        }
-    }
-    while layout.calculate_bits() < bit_length
-        && !(layout.height() >= max_height && layout.width() >= max_width)
-    {
-        let current_aspect_ratio = layout.height() as f32 / layout.width() as f32;
-        let new_row = (current_aspect_ratio < aspect_ratio && layout.height() < max_height)
-            || layout.width() >= max_width;
-        if new_row {
-            // We need to add a row.
-            layout.0.push(vec![FILLED.to_string(); layout.width()]);
-        } else {
-            // We need to add a column.
-            for row in layout.0.iter_mut() {
-                row.push(FILLED.to_string());
-            }
-        }
-    }
-    if layout.calculate_bits() >= bit_length
-        && layout.height() <= max_height
-        && layout.width() <= max_width
-    {
-        Some(layout)
-    } else {
-        None
-    }
-}
-
-pub fn generate_boxes(bytes: &[u8], config: Option<BoxLayoutConfig>) -> String {
-    let layout = layout_byte_length(bytes.len(), config).unwrap();
-    layout.display_bytes(bytes)
-}
-
-pub fn create_boxes<T: serde::Serialize>(t: &T, config: Option<BoxLayoutConfig>) -> String {
-    let data = postcard::to_allocvec(t).unwrap();
-    generate_boxes(data.as_slice(), config)
-}
-
-pub fn create_boxes_with_layout<T: serde::Serialize>(t: &T, layout: BoxLayout) -> String {
-    let data = postcard::to_allocvec(t).unwrap();
-    layout.display_bytes(data.as_slice())
-}
-
-fn point_from_grapheme_in_set(grapheme: &str, set: &str) -> u8 {
-    set.graphemes(true)
-        .position(|g| g == grapheme)
-        .map(|p| p as u8)
-        .unwrap()
-}
-
-pub fn parse_boxes_to_points(s: &str) -> Vec<(u8, usize)> {
-    let mut points = Vec::new();
-    for grapheme in s.graphemes(true) {
-        if CROSS.contains(grapheme) {
-            points.push((point_from_grapheme_in_set(grapheme, CROSS), 4));
-        } else if LEFT.contains(grapheme) {
-            points.push((point_from_grapheme_in_set(grapheme, LEFT), 3));
-        } else if RIGHT.contains(grapheme) {
-            points.push((point_from_grapheme_in_set(grapheme, RIGHT), 3));
-        } else if TOP.contains(grapheme) {
-            points.push((point_from_grapheme_in_set(grapheme, TOP), 3));
-        } else if BOTTOM.contains(grapheme) {
-            points.push((point_from_grapheme_in_set(grapheme, BOTTOM), 3));
-        } else if TOP_LEFT.contains(grapheme) {
-            points.push((point_from_grapheme_in_set(grapheme, TOP_LEFT), 2));
-        } else if TOP_RIGHT.contains(grapheme) {
-            points.push((point_from_grapheme_in_set(grapheme, TOP_RIGHT), 2));
-        } else if BOTTOM_LEFT.contains(grapheme) {
-            points.push((point_from_grapheme_in_set(grapheme, BOTTOM_LEFT), 2));
-        } else if BOTTOM_RIGHT.contains(grapheme) {
-            points.push((point_from_grapheme_in_set(grapheme, BOTTOM_RIGHT), 2));
-        } else if HORIZONTAL.contains(grapheme) {
-            points.push((point_from_grapheme_in_set(grapheme, HORIZONTAL), 2));
-        } else if VERTICAL.contains(grapheme) {
-            points.push((point_from_grapheme_in_set(grapheme, VERTICAL), 2));
-        }
-        // Ignore non-box characters.
-    }
-    points
-}
-
-pub fn box_points_to_bytes(points: &[(u8, usize)]) -> Vec<u8> {
-    let mut bytes = Vec::new();
-    let mut bits = 0;
-    let mut offset = 0;
-    for (point, bits_per_point) in points {
-        if offset == 0 {
-            bits = *point as u32;
-            offset = *bits_per_point;
-        } else {
-            bits |= (*point as u32) << offset;
-            offset += *bits_per_point;
-        }
-        while offset >= 8 {
-            bytes.push((bits & 0xff) as u8);
-            bits >>= 8;
-            offset -= 8;
-        }
-    }
-    bytes
-}
-
-pub fn parse_boxes<T: serde::de::DeserializeOwned>(s: &str) -> Result<T, postcard::Error> {
-    let points = parse_boxes_to_points(s);
-    let bytes = box_points_to_bytes(&points);
-    postcard::from_bytes(&bytes)
-}
-
-pub fn gen_layout(value: &str) -> BoxLayout {
-    BoxLayout(
-        value
-            .split('\n')
-            .map(|row| {
-                row.chars()
-                    .filter(|c| !c.is_whitespace())
-                    .map(|c| c.to_string())
-                    .collect()
-            })
-            .collect(),
-    )
-}
-
-#[cfg(test)]
-mod boxes_tests {
-    use super::*;
-
-    #[test]
-    fn test_box_chars() {
-        assert_eq!(TOP_LEFT, "┌┍┎┏");
-        assert_eq!(TOP_RIGHT, "┐┑┒┓");
-        assert_eq!(BOTTOM_LEFT, "└┕┖┗");
-        assert_eq!(BOTTOM_RIGHT, "┘┙┚┛");
-        assert_eq!(LEFT, "├┝┞┟┠┡┢┣");
-        assert_eq!(RIGHT, "┤┥┦┧┨┩┪┫");
-        assert_eq!(TOP, "┬┭┮┯┰┱┲┳");
-        assert_eq!(BOTTOM, "┴┵┶┷┸┹┺┻");
-        assert_eq!(CROSS, "┼┽┾┿╀╁╂╃╄╅╆╇╈╉╊╋");
-        assert_eq!(HORIZONTAL, "─╼━╾");
-        assert_eq!(VERTICAL, "│╽┃╿");
-    }
-
-    #[test]
-    fn test_calculate_bits_in_layout() {
-        let layout = gen_layout(
-            "##\n\
-             ##",
-        );
-        assert_eq!(layout.calculate_bits(), 8);
-        assert_eq!(BoxLayout::estimate_bits(2, 2), 8);
-        let layout = gen_layout(
-            "####\n\
-             #XX#\n\
-             ####",
-        );
-        assert_eq!(layout.calculate_bits(), 20);
-        let layout = gen_layout(
-            "####\n\
-             ####\n\
-             ####",
-        );
-        assert_eq!(layout.calculate_bits(), 34);
-        assert_eq!(BoxLayout::estimate_bits(4, 3), 34);
-    }
-
-    #[test]
-    fn test_bytes_to_points() {
-        let layout = gen_layout(
-            "##\n\
-             ##",
-        );
-        assert_eq!(layout.bytes_to_points(&[0b01010101]), vec![1, 1, 1, 1]);
-        assert_eq!(layout.bytes_to_points(&[0b11110000]), vec![0, 0, 3, 3]);
-        let layout = gen_layout(
-            "####\n\
-             #XX#\n\
-             ####",
-        );
-        assert_eq!(
-            layout.bytes_to_points(&[0b01010101, 0b01010101]),
-            vec![1, 1, 1, 1, 1, 1, 1, 1]
-        );
-        assert_eq!(
-            layout.bytes_to_points(&[0b11110000, 0b11110000]),
-            vec![0, 0, 3, 3, 0, 0, 3, 3]
-        );
-    }
-
-    #[test]
-    fn test_display_bytes() {
-        let layout = gen_layout(
-            "##\n\
-             ##",
-        );
-        assert_eq!(layout.display_bytes(&[0]), "┌┐\n└┘");
-        assert_eq!(layout.display_bytes(&[0b01010101]), "┍┑\n┕┙");
-        assert_eq!(layout.display_bytes(&[0b11110000]), "┌┐\n┗┛");
-        let layout = gen_layout(
-            "####\n\
-             #XX#\n\
-             ####",
-        );
-        assert_eq!(
-            layout.display_bytes(&[0b01010101, 0b01010101]),
-            "┍╼╼┑\n╽XX╽\n┕╼─┘"
-        );
-        let layout = gen_layout(
-            "####\n\
-             ####\n\
-             #XX#",
-        );
-        assert_eq!(
-            layout.display_bytes(&[0b11110000, 0b11110000]),
-            "┌┰┳┐\n┠┻┴┤\n XX "
-        );
-        let layout = gen_layout(
-            "####\n\
-             ####\n\
-             ##XX",
-        );
-        assert_eq!(
-            layout.display_bytes(&[0b11110000, 0b11110000]),
-            "┌┰┳┐\n┠┼┴┘\n└┘XX"
-        );
-    }
-
-    #[test]
-    fn test_layout_data() {
-        let config = BoxLayoutConfig {
-            min_width: Some(4),
-            min_height: Some(3),
-            max_width: None,
-            max_height: None,
-            aspect_ratio: Some(1.0),
-            blackouts: vec![(1, 1, "Hello".to_string())],
-        };
-        let layout = layout_byte_length(8, Some(config)).unwrap();
-        assert_eq!(layout.width(), 7);
-        assert_eq!(layout.height(), 5);
-        assert_eq!(layout.calculate_bits(), 84);
-
-        let layout = layout_byte_length(8, None).unwrap();
-        assert_eq!(layout.width(), 5);
-        assert_eq!(layout.height(), 5);
-        assert_eq!(layout.calculate_bits(), 80);
-
-        let config = BoxLayoutConfig {
-            max_width: Some(80),
-            max_height: Some(5),
-            ..Default::default()
-        };
-        let layout = layout_byte_length(150, Some(config)).unwrap();
-        assert_eq!(layout.width(), 68);
-        assert_eq!(layout.height(), 5);
-        assert_eq!(layout.calculate_bits(), 1214);
-    }
-
-    use serde::{Deserialize, Serialize};
-
-    #[derive(Serialize, Deserialize, PartialEq, Debug)]
-    struct TestStruct {
-        comments: String,
-        code: u32,
-    }
-
-    #[test]
-    fn test_create_boxes() {
-        let test = TestStruct {
-            comments: "Hello".to_string(),
-            code: 42,
-        };
-        let config = BoxLayoutConfig {
-            min_width: Some(4),
-            min_height: Some(3),
-            max_width: None,
-            max_height: None,
-            aspect_ratio: Some(1.0),
-            blackouts: vec![(1, 1, " C+c ".to_string())],
-        };
-        let boxes = create_boxes(&test, Some(config));
-        assert_eq!(
-            boxes,
-            "┍╼───━┐\n\
-             ╽ C+c ╽\n\
-             ┝┯┰┱┭┲┪\n\
-             ┖┻┺┸┶┵┘"
-        );
-    }
-
-    #[test]
-    fn test_parse_boxes_to_points() {
-        let boxes = "┍╼───━┐\n\
-                           ╽ C+c ╽\n\
-                           ┝┯┰┱┭┲┪\n\
-                           ┖┻┺┸┶┵┘";
-        assert_eq!(
-            parse_boxes_to_points(boxes),
-            vec![
-                (1, 2),
-                (1, 2),
-                (0, 2),
-                (0, 2),
-                (0, 2),
-                (2, 2),
-                (0, 2),
-                (1, 2),
-                (1, 2),
-                (1, 3),
-                (3, 3),
-                (4, 3),
-                (5, 3),
-                (1, 3),
-                (6, 3),
-                (6, 3),
-                (2, 2),
-                (7, 3),
-                (6, 3),
-                (4, 3),
-                (2, 3),
-                (1, 3),
-                (0, 2)
-            ]
-        );
-    }
-
-    #[test]
-    fn test_box_points_to_bytes() {
-        let box_points = parse_boxes_to_points("┌┐\n└┘");
-        assert_eq!(box_points_to_bytes(&box_points), vec![0b00000000]);
-        let box_points = parse_boxes_to_points("┍╼╼┑\n╽XX╽\n┕╼─┘");
-        assert_eq!(box_points_to_bytes(&box_points), [0b01010101, 0b01010101]);
-    }
-
-    #[test]
-    fn test_parse_boxes() {
-        let boxes = "┍╼───━┐\n\
-                           ╽ C+c ╽\n\
-                           ┝┯┰┱┭┲┪\n\
-                           ┖┻┺┸┶┵┘";
-        let test: TestStruct = parse_boxes(boxes).unwrap();
-        assert_eq!(
-            test,
-            TestStruct {
-                comments: "Hello".to_string(),
-                code: 42
-            }
-        );
-    }
-}
-
-fn simple_get_connections_at(
-    width: usize,
-    height: usize,
-    x: usize,
-    y: usize,
-) -> Option<Connections> {
-    let right = x < width - 1;
-    let left = x > 0;
-    let down = y < height - 1;
-    let up = y > 0;
-    match (right, left, down, up) {
-        (true, false, true, false) => Some(Connections::RightDown),
-        (false, true, true, false) => Some(Connections::LeftDown),
-        (true, false, false, true) => Some(Connections::RightUp),
-        (false, true, false, true) => Some(Connections::LeftUp),
-        (true, true, true, false) => Some(Connections::RightLeftDown),
-        (true, true, false, true) => Some(Connections::RightLeftUp),
-        (true, false, true, true) => Some(Connections::RightDownUp),
-        (false, true, true, true) => Some(Connections::LeftDownUp),
-        (true, true, true, true) => Some(Connections::All),
-        _ => None,
-    }
-}
-
-fn simple_bytes_to_points(width: usize, height: usize, bytes: &[u8]) -> Vec<u8> {
-    let mut results = Vec::new();
-    let mut bits: u32 = 0;
-    let mut offset = 0;
-    let mut x = 0;
-    let mut y = 0;
-    for byte in bytes {
-        bits |= (*byte as u32) << offset;
-        offset += 8;
-        push_points_and_move_cursor(
-            width,
-            height,
-            &mut x,
-            &mut y,
-            &mut offset,
-            &mut results,
-            &mut bits,
-        );
-    }
-    results
-}
-
-fn push_points_and_move_cursor(
-    width: usize,
-    height: usize,
-    x: &mut usize,
-    y: &mut usize,
-    offset: &mut usize,
-    results: &mut Vec<u8>,
-    bits: &mut u32,
-) {
-    loop {
-        if let Some(connection) = simple_get_connections_at(width, height, *x, *y) {
-            let connection_bits = connection.get_bits();
-            if *offset >= connection_bits {
-                results.push((*bits & ((1 << connection_bits) - 1)) as u8);
-                *bits >>= connection_bits;
-                *offset -= connection_bits;
-            } else {
-                break;
-            }
-        }
-        if *x < width - 1 {
-            *x += 1;
-        } else {
-            *x = 0;
-            *y += 1;
-        }
-        if *offset == 0 {
-            break;
-        }
-    }
-}
-
-fn simple_display_bytes(width: usize, height: usize, bytes: &[u8]) -> String {
-    let mut result = String::new();
-    let points = simple_bytes_to_points(width, height, bytes);
-    let mut x = 0;
-    let mut y = 0;
-    for point in points {
-        let mut pushed_point = false;
-        while !pushed_point {
-            if let Some(connection) = simple_get_connections_at(width, height, x, y) {
-                result.push(connection.get_character(point));
-                pushed_point = true;
-            }
-            if x < width - 1 {
-                x += 1;
-            } else {
-                x = 0;
-                y += 1;
-                if y < height {
-                    result.push('\n');
-                }
-            }
-        }
-    }
-    while y < height {
-        'push_str: loop {
-            if let Some(connection) = simple_get_connections_at(width, height, x, y) {
-                result.push(connection.get_character(0));
-            } else {
-                result.push(' ');
-            }
-            if x < width - 1 {
-                x += 1;
-            } else {
-                break 'push_str;
-            }
-        }
-        x = 0;
-        y += 1;
-        if y < height {
-            result.push('\n');
-        }
-    }
-    result
-}
-
-#[cfg(test)]
-mod cac_tests {
-    use super::*;
-    #[test]
-    fn draw_some_boxes() {
-        let layout = gen_layout(
-            "##\n\
-             ##",
-        );
-        println!("{}", layout.display_bytes(&[0]));
-        let layout = gen_layout(
-            "###\n\
-             #.#\n\
-             ###",
-        );
-        println!("{}", layout.display_bytes(&[0, 0]));
-        let layout = gen_layout(
-            "#####\n\
-             #####\n\
-             ###..\n\
-             ###..",
-        );
-        // Editted in article.
-        println!("{}", layout.display_bytes(&[255, 255, 255, 255]));
-        let layout = gen_layout(
-            "#####\n\
-             #####\n\
-             #####\n\
-             #####\n\
-             #####",
-        );
-        println!("{}", layout.display_bytes(&[255, 255, 255, 255]));
-        let layout = gen_layout(
-            "###\n\
-             ###\n\
-             ###",
-        );
-        println!("{}", layout.display_bytes(&[0, 0]));
-    }
-}
+use crate::{decode_postcard, push_length_bytes, take_length_bytes, DecodeError};
+use unicode_segmentation::UnicodeSegmentation;
+
+const TOP_LEFT: &str = "\u{250C}\u{250D}\u{250E}\u{250F}";
+const TOP_RIGHT: &str = "\u{2510}\u{2511}\u{2512}\u{2513}";
+const BOTTOM_LEFT: &str = "\u{2514}\u{2515}\u{2516}\u{2517}";
+const BOTTOM_RIGHT: &str = "\u{2518}\u{2519}\u{251A}\u{251B}";
+const LEFT: &str = "\u{251C}\u{251D}\u{251E}\u{251F}\u{2520}\u{2521}\u{2522}\u{2523}";
+const RIGHT: &str = "\u{2524}\u{2525}\u{2526}\u{2527}\u{2528}\u{2529}\u{252A}\u{252B}";
+const TOP: &str = "\u{252C}\u{252D}\u{252E}\u{252F}\u{2530}\u{2531}\u{2532}\u{2533}";
+const BOTTOM: &str = "\u{2534}\u{2535}\u{2536}\u{2537}\u{2538}\u{2539}\u{253A}\u{253B}";
+const CROSS: &str = "\u{253C}\u{253D}\u{253E}\u{253F}\u{2540}\u{2541}\u{2542}\u{2543}\u{2544}\u{2545}\u{2546}\u{2547}\u{2548}\u{2549}\u{254A}\u{254B}";
+const HORIZONTAL: &str = "\u{2500}\u{257C}\u{2501}\u{257E}";
+const VERTICAL: &str = "\u{2502}\u{257D}\u{2503}\u{257F}";
+
+const ROUNDED_TOP_LEFT: &str = "\u{256D}";
+const ROUNDED_TOP_RIGHT: &str = "\u{256E}";
+const ROUNDED_BOTTOM_LEFT: &str = "\u{2570}";
+const ROUNDED_BOTTOM_RIGHT: &str = "\u{256F}";
+
+// The double-line sets start with the all-double grapheme, followed by the double horizontal, single vertical one.
+const DOUBLE_TOP_LEFT: &str = "\u{2554}\u{2552}";
+const DOUBLE_TOP_RIGHT: &str = "\u{2557}\u{2555}";
+const DOUBLE_BOTTOM_LEFT: &str = "\u{255A}\u{2558}";
+const DOUBLE_BOTTOM_RIGHT: &str = "\u{255D}\u{255B}";
+const DOUBLE_LEFT: &str = "\u{2560}\u{255E}";
+const DOUBLE_RIGHT: &str = "\u{2563}\u{2561}";
+const DOUBLE_TOP: &str = "\u{2566}\u{2564}";
+const DOUBLE_BOTTOM: &str = "\u{2569}\u{2567}";
+const DOUBLE_CROSS: &str = "\u{256C}\u{256A}";
+const DOUBLE_HORIZONTAL: &str = "\u{2550}";
+const DOUBLE_VERTICAL: &str = "\u{2551}";
+
+// A fully connected cell in the diagonal style is drawn from 32 graphemes unused by the other styles, so the cell can
+// be read back as 5 bits without knowing the style. The diagonals come first, then the dashed and partial lines, and
+// finally the quadrant blocks.
+const DIAGONAL_CROSS: &str = "\u{2573}\u{2571}\u{2572}\u{2504}\u{2505}\u{2506}\u{2507}\u{2508}\u{2509}\u{250A}\u{250B}\u{254C}\u{254D}\u{254E}\u{254F}\u{2574}\u{2575}\u{2576}\u{2577}\u{2578}\u{2579}\u{257A}\u{257B}\u{2596}\u{2597}\u{2598}\u{2599}\u{259A}\u{259B}\u{259C}\u{259D}\u{259E}";
+
+/**
+The set of box-drawing graphemes used to draw a diagram.
+
+Each style has its own number of variants per connection, so the bits carried by each cell depend on the style.
+*/
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum BoxStyle {
+    /// Light and heavy line weights, carrying 2 to 4 bits per cell.
+    #[default]
+    Weighted,
+    /// Like Weighted, but with rounded corners. The corners have a single variant, so carry no bits.
+    Rounded,
+    /// Double lines, with single line variants. Carries at most 1 bit per cell, and none on straight lines.
+    Double,
+    /// Like Weighted, but fully connected cells use diagonal and other extra graphemes to carry 5 bits.
+    Diagonal,
+}
+
+impl BoxStyle {
+    pub const ALL: [BoxStyle; 4] = [
+        BoxStyle::Weighted,
+        BoxStyle::Rounded,
+        BoxStyle::Double,
+        BoxStyle::Diagonal,
+    ];
+
+    /**
+    Returns the set of graphemes a connection can be drawn with in this style, indexed by point.
+    */
+    pub fn get_characters(self, connection: Connections) -> &'static str {
+        match (self, connection) {
+            (BoxStyle::Rounded, Connections::RightDown) => ROUNDED_TOP_LEFT,
+            (BoxStyle::Rounded, Connections::LeftDown) => ROUNDED_TOP_RIGHT,
+            (BoxStyle::Rounded, Connections::RightUp) => ROUNDED_BOTTOM_LEFT,
+            (BoxStyle::Rounded, Connections::LeftUp) => ROUNDED_BOTTOM_RIGHT,
+            (BoxStyle::Diagonal, Connections::All) => DIAGONAL_CROSS,
+            (BoxStyle::Weighted | BoxStyle::Rounded | BoxStyle::Diagonal, connection) => {
+                match connection {
+                    Connections::RightDown => TOP_LEFT,
+                    Connections::LeftDown => TOP_RIGHT,
+                    Connections::RightUp => BOTTOM_LEFT,
+                    Connections::LeftUp => BOTTOM_RIGHT,
+                    Connections::DownUp => VERTICAL,
+                    Connections::RightLeft => HORIZONTAL,
+                    Connections::RightLeftDown => TOP,
+                    Connections::RightLeftUp => BOTTOM,
+                    Connections::RightDownUp => LEFT,
+                    Connections::LeftDownUp => RIGHT,
+                    Connections::All => CROSS,
+                }
+            }
+            (BoxStyle::Double, connection) => match connection {
+                Connections::RightDown => DOUBLE_TOP_LEFT,
+                Connections::LeftDown => DOUBLE_TOP_RIGHT,
+                Connections::RightUp => DOUBLE_BOTTOM_LEFT,
+                Connections::LeftUp => DOUBLE_BOTTOM_RIGHT,
+                Connections::DownUp => DOUBLE_VERTICAL,
+                Connections::RightLeft => DOUBLE_HORIZONTAL,
+                Connections::RightLeftDown => DOUBLE_TOP,
+                Connections::RightLeftUp => DOUBLE_BOTTOM,
+                Connections::RightDownUp => DOUBLE_LEFT,
+                Connections::LeftDownUp => DOUBLE_RIGHT,
+                Connections::All => DOUBLE_CROSS,
+            },
+        }
+    }
+
+    /**
+    The number of bits a connection carries in this style. Only whole bits are used, so a set of 3 graphemes would
+    carry 1 bit.
+    */
+    pub fn get_bits(self, connection: Connections) -> usize {
+        self.get_characters(connection)
+            .graphemes(true)
+            .count()
+            .ilog2() as usize
+    }
+}
+
+#[derive(Clone, Default, Debug)]
+pub struct BoxLayoutConfig {
+    pub min_width: Option<usize>,
+    pub max_width: Option<usize>,
+    pub min_height: Option<usize>,
+    pub max_height: Option<usize>,
+    pub aspect_ratio: Option<f32>,
+    pub blackouts: Vec<(usize, usize, String)>,
+    pub style: BoxStyle,
+    /// Start growing from the smallest square estimate_bits says could hold the data, rather than from the minimums.
+    pub seed_square: bool,
+    /// Cells, as (x, y), which are always drawn with a solid block for alignment, and never carry data.
+    pub anchors: Vec<(usize, usize)>,
+    /// How the cells left over after the data are drawn.
+    pub pad: PadPolicy,
+    /// The order data flows through the cells. Anything but RowMajor must be read back with bytes_from_boxes_ordered.
+    pub traversal: TraversalOrder,
+    /// Blackouts placed against an edge of the final layout, as (anchor, x offset, y offset, text), with the offsets
+    /// measured inwards from the anchor. They're placed once the layout has grown, growing it further if needed.
+    pub anchored_blackouts: Vec<(BlackoutAnchor, usize, usize, String)>,
+}
+
+impl BoxLayoutConfig {
+    /**
+    Every field, with the aspect ratio as its bits, so configs can be compared and hashed as cache keys.
+    */
+    fn key(&self) -> impl Eq + std::hash::Hash + '_ {
+        let BoxLayoutConfig {
+            min_width,
+            max_width,
+            min_height,
+            max_height,
+            aspect_ratio,
+            blackouts,
+            style,
+            seed_square,
+            anchors,
+            pad,
+            traversal,
+            anchored_blackouts,
+        } = self;
+        (
+            (min_width, max_width, min_height, max_height),
+            aspect_ratio.map(f32::to_bits),
+            blackouts,
+            style,
+            seed_square,
+            anchors,
+            pad,
+            traversal,
+            anchored_blackouts,
+        )
+    }
+}
+
+/**
+The point of a layout an anchored blackout is placed from.
+*/
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum BlackoutAnchor {
+    #[default]
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+    /// The middle of the layout, with the offsets moving the blackout right and down.
+    Center,
+}
+
+impl BlackoutAnchor {
+    /**
+    The (left, top) of a blackout with the given extent, placed this way in a layout of the given size. Like any other
+    blackout, a column is left to its right for the box around it. Returns None if it doesn't fit.
+    */
+    fn resolve(
+        self,
+        (width, height): (usize, usize),
+        (blackout_width, blackout_height): (usize, usize),
+        (x, y): (usize, usize),
+    ) -> Option<(usize, usize)> {
+        let free_width = width.checked_sub(blackout_width + 1)?;
+        let free_height = height.checked_sub(blackout_height)?;
+        let (left, top) = match self {
+            BlackoutAnchor::TopLeft => (x, y),
+            BlackoutAnchor::TopRight => (free_width.checked_sub(x)?, y),
+            BlackoutAnchor::BottomLeft => (x, free_height.checked_sub(y)?),
+            BlackoutAnchor::BottomRight => {
+                (free_width.checked_sub(x)?, free_height.checked_sub(y)?)
+            }
+            BlackoutAnchor::Center => (free_width / 2 + x, free_height / 2 + y),
+        };
+        (left <= free_width && top <= free_height).then_some((left, top))
+    }
+}
+
+impl PartialEq for BoxLayoutConfig {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for BoxLayoutConfig {}
+
+impl std::hash::Hash for BoxLayoutConfig {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.key().hash(state)
+    }
+}
+
+/**
+The order the cells of a layout are filled with data, and read back in.
+*/
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum TraversalOrder {
+    /// Left to right along each row, from the top row down, the same order as the text.
+    #[default]
+    RowMajor,
+    /// Top to bottom down each column, from the left column across.
+    ColumnMajor,
+    /// Like RowMajor, but every other row runs right to left, so the data snakes back and forth.
+    Boustrophedonic,
+}
+
+impl TraversalOrder {
+    /**
+    Every (x, y) of a grid, in this order.
+    */
+    fn positions(self, width: usize, height: usize) -> Vec<(usize, usize)> {
+        match self {
+            TraversalOrder::RowMajor => (0..height)
+                .flat_map(|y| (0..width).map(move |x| (x, y)))
+                .collect(),
+            TraversalOrder::ColumnMajor => (0..width)
+                .flat_map(|x| (0..height).map(move |y| (x, y)))
+                .collect(),
+            TraversalOrder::Boustrophedonic => (0..height)
+                .flat_map(|y| {
+                    (0..width).map(move |x| {
+                        if y % 2 == 0 {
+                            (x, y)
+                        } else {
+                            (width - 1 - x, y)
+                        }
+                    })
+                })
+                .collect(),
+        }
+    }
+}
+
+/**
+How generate_boxes draws the cells left over once the data runs out.
+*/
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum PadPolicy {
+    /// Drawn as zero points, which keeps every box whole, but reads back as zero bytes after the data.
+    #[default]
+    Zeroes,
+    /// Left as spaces, which decoders skip, so the diagram reads back as exactly the data.
+    Blank,
+    /// Drawn with a grapheme from outside every style's sets, which decoders skip like Blank.
+    Filler(char),
+}
+
+/// The marker for filled cells in layout templates, like those read by gen_layout.
+const FILLED: &str = "#";
+
+/// The grapheme drawn in anchor cells. It isn't a box-drawing grapheme, so decoders skip it like blackout text.
+const ANCHOR: &str = "\u{2588}";
+
+/**
+A single cell of a layout: either part of the data, or blacked out with some text.
+*/
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Cell {
+    Filled,
+    Blackout(String),
+}
+
+impl From<&str> for Cell {
+    /**
+    Reads a cell from its template form, where the fill marker is a filled cell and anything else is a blackout.
+    */
+    fn from(value: &str) -> Self {
+        if value == FILLED {
+            Cell::Filled
+        } else {
+            Cell::Blackout(value.to_string())
+        }
+    }
+}
+
+/**
+How densely a layout carries data, from BoxLayout::stats.
+*/
+#[derive(Clone, Debug, PartialEq)]
+pub struct BoxStats {
+    pub total_cells: usize,
+    pub filled_cells: usize,
+    pub blackout_cells: usize,
+    /// The bits the layout carries in the weighted style, as from calculate_bits.
+    pub total_bits: usize,
+    /// The average bits carried by a filled cell, including the filled cells too isolated to carry any.
+    pub bits_per_filled_cell: f32,
+}
+
+/**
+Defines a 2d layout of data vertices.
+*/
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct BoxLayout(pub Vec<Vec<Cell>>);
+
+impl From<Vec<Vec<String>>> for BoxLayout {
+    fn from(rows: Vec<Vec<String>>) -> Self {
+        BoxLayout(
+            rows.iter()
+                .map(|row| row.iter().map(|cell| Cell::from(cell.as_str())).collect())
+                .collect(),
+        )
+    }
+}
+
+impl std::str::FromStr for BoxLayout {
+    type Err = std::convert::Infallible;
+
+    /**
+    Reads a layout template, like gen_layout.
+    */
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(gen_layout(s))
+    }
+}
+
+impl std::fmt::Display for BoxLayout {
+    /**
+    Writes the layout as a template, with the fill marker for filled cells and the stored text for blackouts.
+
+    Templates are read a character at a time, skipping whitespace, so only layouts whose blackouts are single
+    non-whitespace characters read back the same. Layouts from gen_layout always are.
+    */
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (y, row) in self.to_strings().iter().enumerate() {
+            if y > 0 {
+                writeln!(f)?;
+            }
+            for cell in row {
+                write!(f, "{}", cell)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl BoxLayout {
+    pub fn new(width: usize, height: usize) -> Self {
+        BoxLayout(vec![vec![Cell::Filled; width]; height])
+    }
+
+    /**
+    Converts the layout back to its template form, with the fill marker for filled cells.
+    */
+    pub fn to_strings(&self) -> Vec<Vec<String>> {
+        self.0
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|cell| match cell {
+                        Cell::Filled => FILLED.to_string(),
+                        Cell::Blackout(value) => value.clone(),
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /**
+    The number of columns, or 0 for a layout with no rows.
+    */
+    pub fn width(&self) -> usize {
+        self.0.first().map_or(0, |row| row.len())
+    }
+
+    pub fn height(&self) -> usize {
+        self.0.len()
+    }
+
+    /**
+    Sums the bits carried by each connected cell.
+
+    Filled cells with fewer than two filled neighbors can't be drawn as a connection, so they carry no bits. They are
+    drawn as blank space instead.
+    */
+    pub fn calculate_bits(&self) -> usize {
+        self.calculate_bits_with_style(BoxStyle::Weighted)
+    }
+
+    /**
+    Sums the bits carried by each connected cell, when drawn in the given style.
+    */
+    pub fn calculate_bits_with_style(&self, style: BoxStyle) -> usize {
+        let mut bit_count = 0;
+        for y in 0..self.height() {
+            for x in 0..self.width() {
+                if let Some(connection) = self.get_connections_at(x, y) {
+                    bit_count += connection.get_bits(style);
+                }
+            }
+        }
+        bit_count
+    }
+
+    /**
+    The number of whole bytes this layout can hold.
+    */
+    pub fn capacity_bytes(&self) -> usize {
+        self.calculate_bits() / 8
+    }
+
+    /**
+    Counts the cells of the layout and the bits they carry, for comparing layouts.
+    */
+    pub fn stats(&self) -> BoxStats {
+        let total_cells = self.0.iter().map(|row| row.len()).sum();
+        let blackout_cells = self
+            .0
+            .iter()
+            .flatten()
+            .filter(|cell| matches!(cell, Cell::Blackout(_)))
+            .count();
+        let filled_cells = total_cells - blackout_cells;
+        let total_bits = self.calculate_bits();
+        BoxStats {
+            total_cells,
+            filled_cells,
+            blackout_cells,
+            total_bits,
+            bits_per_filled_cell: if filled_cells == 0 {
+                0.0
+            } else {
+                total_bits as f32 / filled_cells as f32
+            },
+        }
+    }
+
+    pub fn estimate_bits(width: usize, height: usize) -> usize {
+        let length_wise = width.saturating_sub(1) * 2 * height;
+        let height_wise = height.saturating_sub(1) * 2 * width;
+        length_wise + height_wise
+    }
+
+    /**
+    The (width, height) of the smallest full layout which holds length bytes, near the aspect ratio (height / width).
+
+    This follows the same steps as layout_byte_length without building any layouts, so it gives the same size when
+    there's no config besides the aspect ratio.
+    */
+    pub fn dimensions_for_bytes(length: usize, aspect_ratio: f32) -> (usize, usize) {
+        let (mut width, mut height) = (2, 2);
+        while BoxLayout::estimate_bits(width, height) < length * 8 {
+            if row_is_closer(width, height, aspect_ratio) {
+                height += 1;
+            } else {
+                width += 1;
+            }
+        }
+        (width, height)
+    }
+
+    /**
+    Blacks out the cells starting at (left, top) with the graphemes of value. Each line of value is placed on the next
+    row down, starting from the same left column. Returns None if value runs off the layout.
+
+    A grapheme wider than one column is followed by empty blackout cells, so the columns after it stay aligned.
+    */
+    fn set_blackout(&mut self, left: usize, top: usize, value: &str) -> Option<()> {
+        for (j, line) in value.split('\n').enumerate() {
+            let row = self.0.get_mut(top + j)?;
+            let mut x = left;
+            for grapheme in line.graphemes(true) {
+                *row.get_mut(x)? = Cell::Blackout(grapheme.to_string());
+                for extra in 1..grapheme_width(grapheme) {
+                    *row.get_mut(x + extra)? = Cell::Blackout(String::new());
+                }
+                x += grapheme_width(grapheme);
+            }
+        }
+        Some(())
+    }
+
+    /**
+    Whether the cell at (x, y) is filled. Cells outside the layout aren't.
+    */
+    pub fn is_filled(&self, x: usize, y: usize) -> bool {
+        self.0.get(y).and_then(|row| row.get(x)) == Some(&Cell::Filled)
+    }
+
+    pub fn get_blackout_at(&self, x: usize, y: usize) -> Option<&str> {
+        self.0
+            .get(y)
+            .and_then(|row| row.get(x))
+            .and_then(|cell| match cell {
+                Cell::Filled => None,
+                Cell::Blackout(value) => Some(value.as_str()),
+            })
+    }
+
+    pub fn get_connections_at(&self, x: usize, y: usize) -> Option<Connections> {
+        if !self.is_filled(x, y) {
+            return None;
+        }
+        let right = x < self.width() - 1 && self.is_filled(x + 1, y);
+        let left = x > 0 && self.is_filled(x - 1, y);
+        let down = y < self.height() - 1 && self.is_filled(x, y + 1);
+        let up = y > 0 && self.is_filled(x, y - 1);
+        match (right, left, down, up) {
+            (true, true, false, false) => Some(Connections::RightLeft),
+            (false, false, true, true) => Some(Connections::DownUp),
+            (true, false, true, false) => Some(Connections::RightDown),
+            (false, true, true, false) => Some(Connections::LeftDown),
+            (true, false, false, true) => Some(Connections::RightUp),
+            (false, true, false, true) => Some(Connections::LeftUp),
+            (true, true, true, false) => Some(Connections::RightLeftDown),
+            (true, true, false, true) => Some(Connections::RightLeftUp),
+            (true, false, true, true) => Some(Connections::RightDownUp),
+            (false, true, true, true) => Some(Connections::LeftDownUp),
+            (true, true, true, true) => Some(Connections::All),
+            _ => None,
+        }
+    }
+
+    // Unlike Base32 futhark encoding, we have variable bits per point.
+    // Any bits which don't fit in the layout are dropped.
+    pub fn bytes_to_points(&self, bytes: &[u8]) -> Vec<u8> {
+        self.bytes_to_points_with_style(bytes, BoxStyle::Weighted)
+    }
+
+    pub fn bytes_to_points_with_style(&self, bytes: &[u8], style: BoxStyle) -> Vec<u8> {
+        self.bytes_to_points_ordered(bytes, style, TraversalOrder::RowMajor)
+    }
+
+    /**
+    Converts bytes to points like bytes_to_points_with_style, visiting the cells in the given order.
+    */
+    pub fn bytes_to_points_ordered(
+        &self,
+        bytes: &[u8],
+        style: BoxStyle,
+        order: TraversalOrder,
+    ) -> Vec<u8> {
+        let positions = order.positions(self.width(), self.height());
+        let mut results = Vec::new();
+        let mut bits: u32 = 0;
+        let mut offset = 0;
+        let mut cell = 0;
+        for byte in bytes {
+            bits |= (*byte as u32) << offset;
+            offset += 8;
+            'push_bits: loop {
+                let Some(&(x, y)) = positions.get(cell) else {
+                    // The layout is full, so there's nowhere to put the rest.
+                    return results;
+                };
+                if let Some(connection) = self.get_connections_at(x, y) {
+                    let connection_bits = connection.get_bits(style);
+                    if offset >= connection_bits {
+                        results.push((bits & ((1 << connection_bits) - 1)) as u8);
+                        bits >>= connection_bits;
+                        offset -= connection_bits;
+                    } else {
+                        break 'push_bits;
+                    }
+                }
+                cell += 1;
+                if offset == 0 {
+                    break 'push_bits;
+                }
+            }
+        }
+        if offset != 0 {
+            // The cursor is on the cell which couldn't be filled, so the leftover bits go there, padded with zeroes.
+            results.push(bits as u8);
+        }
+        results
+    }
+
+    /**
+    Converts bytes to points like bytes_to_points, paired with the bits each point's cell carries, so the points can be
+    read back with decode_points without drawing the diagram and parsing it again.
+    */
+    pub fn encode_points(&self, bytes: &[u8]) -> Vec<(u8, usize)> {
+        self.encode_points_with_style(bytes, BoxStyle::Weighted)
+    }
+
+    pub fn encode_points_with_style(&self, bytes: &[u8], style: BoxStyle) -> Vec<(u8, usize)> {
+        self.bytes_to_points_with_style(bytes, style)
+            .into_iter()
+            .zip(self.data_connections())
+            .map(|(point, connection)| (point, connection.get_bits(style)))
+            .collect()
+    }
+
+    /**
+    Reads the bytes back from points made by encode_points, checking them against the layout's data cells first.
+
+    Fails if there are more points than data cells, or a point's bits aren't what its cell carries in any style.
+    */
+    pub fn decode_points(&self, points: &[(u8, usize)]) -> Result<Vec<u8>, BoxError> {
+        let connections = self.data_connections();
+        if points.len() > connections.len() {
+            return Err(BoxError::Mismatch {
+                expected: connections.len(),
+                found: points.len(),
+            });
+        }
+        for (index, ((_, bits), connection)) in points.iter().zip(connections).enumerate() {
+            if !BoxStyle::ALL
+                .iter()
+                .any(|style| connection.get_bits(*style) == *bits)
+            {
+                return Err(BoxError::CellMismatch { index });
+            }
+        }
+        Ok(box_points_to_bytes(points))
+    }
+
+    /**
+    The connections of the data cells, in row-major order.
+    */
+    fn data_connections(&self) -> Vec<Connections> {
+        (0..self.height())
+            .flat_map(|y| (0..self.width()).filter_map(move |x| self.get_connections_at(x, y)))
+            .collect()
+    }
+
+    /**
+    Draws each data cell as the connection it resolves to, with the lightest grapheme of the weighted style, regardless
+    of any data. Blackouts show their text, and filled cells with no connection show the fill marker.
+
+    This is for checking how a layout will be drawn while authoring it, so nothing drawn here carries data.
+    */
+    pub fn display_connections(&self) -> String {
+        (0..self.height())
+            .map(|y| {
+                (0..self.width())
+                    .map(
+                        |x| match (self.get_connections_at(x, y), self.get_blackout_at(x, y)) {
+                            (Some(connection), _) => connection.to_string(),
+                            (None, Some(value)) => value.to_string(),
+                            (None, None) => FILLED.to_string(),
+                        },
+                    )
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /**
+    Draws the bytes in the layout, one line per row. Rows are joined by newlines with none after the last, however
+    much of the layout the data fills.
+    */
+    pub fn display_bytes(&self, bytes: &[u8]) -> String {
+        self.display_bytes_with_style(bytes, BoxStyle::Weighted)
+    }
+
+    pub fn display_bytes_with_style(&self, bytes: &[u8], style: BoxStyle) -> String {
+        self.display_bytes_padded(bytes, style, PadPolicy::Zeroes)
+    }
+
+    /**
+    Displays bytes like display_bytes_with_style, drawing the cells after the data as the pad policy says.
+    */
+    pub fn display_bytes_padded(&self, bytes: &[u8], style: BoxStyle, pad: PadPolicy) -> String {
+        self.display_bytes_with_config(
+            bytes,
+            &BoxLayoutConfig {
+                style,
+                pad,
+                ..Default::default()
+            },
+        )
+    }
+
+    /**
+    Displays bytes with the config's style, padding, and traversal order, like generate_boxes does once it has a
+    layout.
+    */
+    pub fn display_bytes_with_config(&self, bytes: &[u8], config: &BoxLayoutConfig) -> String {
+        self.render_cells(bytes, config.style, config.pad, config.traversal)
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|cell| match cell {
+                        RenderedCell::Data(c) | RenderedCell::Padding(c) => c.to_string(),
+                        RenderedCell::Blackout(value) => value.to_string(),
+                        RenderedCell::Blank => " ".to_string(),
+                    })
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /**
+    Displays bytes like display_bytes, but wraps each cell in ANSI color codes from the palette.
+
+    Data cells and padding cells (which are drawn after the data runs out) are colored one character at a time, while
+    each run of blackout cells is colored as a whole. Blank cells are left uncolored. The box-drawing graphemes are
+    unchanged, so the result can still be parsed once the escape codes are stripped.
+    */
+    pub fn display_bytes_colored(&self, bytes: &[u8], palette: &ColorPalette) -> String {
+        let mut result = String::new();
+        for (y, row) in self
+            .render_cells(
+                bytes,
+                palette.style,
+                PadPolicy::Zeroes,
+                TraversalOrder::RowMajor,
+            )
+            .iter()
+            .enumerate()
+        {
+            if y > 0 {
+                result.push('\n');
+            }
+            let mut in_blackout = false;
+            for cell in row {
+                if in_blackout && !matches!(cell, RenderedCell::Blackout(_)) {
+                    result.push_str(SGR_RESET);
+                    in_blackout = false;
+                }
+                match cell {
+                    RenderedCell::Data(c) => push_colored(&mut result, &palette.data, *c),
+                    RenderedCell::Padding(c) => push_colored(&mut result, &palette.padding, *c),
+                    RenderedCell::Blackout(value) => {
+                        if !in_blackout {
+                            result.push_str(&format!("\x1b[{}m", palette.blackout));
+                            in_blackout = true;
+                        }
+                        result.push_str(value);
+                    }
+                    RenderedCell::Blank => result.push(' '),
+                }
+            }
+            if in_blackout {
+                result.push_str(SGR_RESET);
+            }
+        }
+        result
+    }
+
+    /**
+    Works out what to draw in each cell, row by row.
+    */
+    fn render_cells(
+        &self,
+        bytes: &[u8],
+        style: BoxStyle,
+        pad: PadPolicy,
+        order: TraversalOrder,
+    ) -> Vec<Vec<RenderedCell<'_>>> {
+        // Points are handed out in the traversal order, but the cells are still drawn row by row.
+        let mut points = self
+            .bytes_to_points_ordered(bytes, style, order)
+            .into_iter();
+        let mut cell_points = vec![None; self.width() * self.height()];
+        for (x, y) in order.positions(self.width(), self.height()) {
+            if self.get_connections_at(x, y).is_some() {
+                cell_points[y * self.width() + x] = points.next();
+            }
+        }
+        let mut rows = Vec::new();
+        for y in 0..self.height() {
+            let mut row = Vec::new();
+            for x in 0..self.width() {
+                if let Some(blackout) = self.get_blackout_at(x, y) {
+                    row.push(RenderedCell::Blackout(blackout));
+                } else if let Some(connection) = self.get_connections_at(x, y) {
+                    match cell_points[y * self.width() + x] {
+                        Some(point) => {
+                            row.push(RenderedCell::Data(connection.get_character(point, style)))
+                        }
+                        None => row.push(RenderedCell::Padding(match pad {
+                            PadPolicy::Zeroes => connection.get_character(0, style),
+                            PadPolicy::Blank => ' ',
+                            PadPolicy::Filler(filler) => filler,
+                        })),
+                    }
+                } else {
+                    row.push(RenderedCell::Blank);
+                }
+            }
+            rows.push(row);
+        }
+        rows
+    }
+}
+
+enum RenderedCell<'a> {
+    Data(char),
+    Padding(char),
+    Blackout(&'a str),
+    Blank,
+}
+
+const SGR_RESET: &str = "\x1b[0m";
+
+fn push_colored(result: &mut String, color: &str, c: char) {
+    result.push_str(&format!("\x1b[{}m{}{}", color, c, SGR_RESET));
+}
+
+/**
+ANSI SGR parameters (like "32" for green, or "1;34" for bold blue) used by display_bytes_colored.
+*/
+#[derive(Clone, Debug)]
+pub struct ColorPalette {
+    pub data: String,
+    pub blackout: String,
+    pub padding: String,
+    pub style: BoxStyle,
+}
+
+impl Default for ColorPalette {
+    fn default() -> Self {
+        Self {
+            data: "32".to_string(),
+            blackout: "1;33".to_string(),
+            padding: "90".to_string(),
+            style: BoxStyle::default(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Connections {
+    RightDown,
+    LeftDown,
+    RightUp,
+    LeftUp,
+    DownUp,
+    RightLeft,
+    RightLeftDown,
+    RightLeftUp,
+    RightDownUp,
+    LeftDownUp,
+    All,
+}
+
+impl Connections {
+    pub const ALL: [Connections; 11] = [
+        Connections::RightDown,
+        Connections::LeftDown,
+        Connections::RightUp,
+        Connections::LeftUp,
+        Connections::DownUp,
+        Connections::RightLeft,
+        Connections::RightLeftDown,
+        Connections::RightLeftUp,
+        Connections::RightDownUp,
+        Connections::LeftDownUp,
+        Connections::All,
+    ];
+
+    pub fn get_bits(self, style: BoxStyle) -> usize {
+        style.get_bits(self)
+    }
+
+    /**
+    Returns the set of graphemes this connection can be drawn with, indexed by point.
+    */
+    pub fn get_characters(self, style: BoxStyle) -> &'static str {
+        style.get_characters(self)
+    }
+
+    pub fn get_character(self, point: u8, style: BoxStyle) -> char {
+        self.get_characters(style)
+            .graphemes(true)
+            .nth(point as usize)
+            .unwrap()
+            .chars()
+            .next()
+            .unwrap()
+    }
+
+    /**
+    Finds the connection a box-drawing grapheme draws, and the point it carries, in whichever style it belongs to.
+
+    Returns None for anything which isn't a whole box-drawing grapheme.
+    */
+    pub fn from_grapheme(grapheme: &str) -> Option<(Connections, u8)> {
+        find_box_grapheme(grapheme).map(|(_, connection, point)| (connection, point))
+    }
+
+    /**
+    Which sides of the weighted style's grapheme for the point are drawn heavy. Returns None if the point is out of
+    range for this connection.
+
+    The weighted sets aren't ordered bit by bit, so this is what a point looks like, rather than how it's packed.
+    */
+    pub fn weights(self, point: u8) -> Option<SideWeights> {
+        self.weight_table()
+            .get(point as usize)
+            .map(|mask| SideWeights::from_mask(*mask))
+    }
+
+    /**
+    The weighted style's grapheme with exactly the given sides drawn heavy, the inverse of weights. Returns None if a
+    side this connection doesn't have is heavy.
+    */
+    pub fn character_for_weights(self, weights: SideWeights) -> Option<char> {
+        let point = self
+            .weight_table()
+            .iter()
+            .position(|mask| *mask == weights.to_mask())?;
+        Some(self.get_character(point as u8, BoxStyle::Weighted))
+    }
+
+    /**
+    The heavy sides of each grapheme in the weighted style's set, indexed by point, as SideWeights masks.
+    */
+    fn weight_table(self) -> &'static [u8] {
+        const L: u8 = SideWeights::LEFT;
+        const R: u8 = SideWeights::RIGHT;
+        const U: u8 = SideWeights::UP;
+        const D: u8 = SideWeights::DOWN;
+        match self {
+            Connections::RightDown => &[0, R, D, R | D],
+            Connections::LeftDown => &[0, L, D, L | D],
+            Connections::RightUp => &[0, R, U, R | U],
+            Connections::LeftUp => &[0, L, U, L | U],
+            Connections::DownUp => &[0, D, U | D, U],
+            Connections::RightLeft => &[0, R, L | R, L],
+            Connections::RightLeftDown => &[0, L, R, L | R, D, L | D, R | D, L | R | D],
+            Connections::RightLeftUp => &[0, L, R, L | R, U, L | U, R | U, L | R | U],
+            Connections::RightDownUp => &[0, R, U, D, U | D, R | U, R | D, R | U | D],
+            Connections::LeftDownUp => &[0, L, U, D, U | D, L | U, L | D, L | U | D],
+            Connections::All => &[
+                0,
+                L,
+                R,
+                L | R,
+                U,
+                D,
+                U | D,
+                L | U,
+                R | U,
+                L | D,
+                R | D,
+                L | R | U,
+                L | R | D,
+                L | U | D,
+                R | U | D,
+                L | R | U | D,
+            ],
+        }
+    }
+}
+
+/**
+Which sides of a box-drawing grapheme are drawn with a heavy line, rather than a light one.
+*/
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct SideWeights {
+    pub left: bool,
+    pub right: bool,
+    pub up: bool,
+    pub down: bool,
+}
+
+impl SideWeights {
+    const LEFT: u8 = 1;
+    const RIGHT: u8 = 2;
+    const UP: u8 = 4;
+    const DOWN: u8 = 8;
+
+    fn from_mask(mask: u8) -> Self {
+        SideWeights {
+            left: mask & Self::LEFT != 0,
+            right: mask & Self::RIGHT != 0,
+            up: mask & Self::UP != 0,
+            down: mask & Self::DOWN != 0,
+        }
+    }
+
+    fn to_mask(self) -> u8 {
+        (self.left as u8 * Self::LEFT)
+            | (self.right as u8 * Self::RIGHT)
+            | (self.up as u8 * Self::UP)
+            | (self.down as u8 * Self::DOWN)
+    }
+}
+
+/**
+Every grapheme which can be drawn in a box diagram, in any style, with the number of bits it carries. This is built from
+the same sets parse_boxes_to_points reads with, for tools which highlight or validate diagrams.
+*/
+pub fn box_alphabet() -> &'static [(char, usize)] {
+    static ALPHABET: std::sync::OnceLock<Vec<(char, usize)>> = std::sync::OnceLock::new();
+    ALPHABET.get_or_init(|| {
+        let mut alphabet: Vec<(char, usize)> = Vec::new();
+        for style in BoxStyle::ALL {
+            for connection in Connections::ALL {
+                let bits = style.get_bits(connection);
+                for c in connection.get_characters(style).chars() {
+                    // Graphemes shared between styles carry the same bits in each, so the first is kept.
+                    if !alphabet.iter().any(|(seen, _)| *seen == c) {
+                        alphabet.push((c, bits));
+                    }
+                }
+            }
+        }
+        alphabet
+    })
+}
+
+/**
+Finds the style, connection, and point of a box-drawing grapheme. Graphemes shared between styles are reported in the
+first style of BoxStyle::ALL that draws them, which always carries the same bits as the others.
+*/
+fn find_box_grapheme(grapheme: &str) -> Option<(BoxStyle, Connections, u8)> {
+    BoxStyle::ALL.iter().find_map(|style| {
+        Connections::ALL.iter().find_map(|connection| {
+            connection
+                .get_characters(*style)
+                .graphemes(true)
+                .position(|g| g == grapheme)
+                .map(|point| (*style, *connection, point as u8))
+        })
+    })
+}
+
+impl std::fmt::Display for Connections {
+    /**
+    Writes the connection as its lightest grapheme in the weighted style, like \u{253C} for All.
+    */
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.get_character(0, BoxStyle::Weighted))
+    }
+}
+
+/**
+Reasons a layout could not be found for some data.
+*/
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LayoutError {
+    /// Even the largest layout permitted by the config can't hold the data.
+    CapacityExceeded {
+        needed_bits: usize,
+        available_bits: usize,
+    },
+    /// A blackout (plus the box around it) doesn't fit within the config's max width or height.
+    BlackoutOutOfBounds { left: usize, top: usize },
+    /// The config's min width or height is larger than its max.
+    MinExceedsMax,
+    /// A blackout contains a box-drawing grapheme, which would be read back as data.
+    BlackoutContainsBoxChar { grapheme: String },
+    /// An anchor doesn't fit within the config's max width or height.
+    AnchorOutOfBounds { x: usize, y: usize },
+    /// The padding filler is a box-drawing grapheme, which would be read back as data.
+    FillerIsBoxChar { filler: char },
+}
+
+impl std::fmt::Display for LayoutError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LayoutError::CapacityExceeded {
+                needed_bits,
+                available_bits,
+            } => write!(
+                f,
+                "data needs {} bits, but the layout can only hold {}",
+                needed_bits, available_bits
+            ),
+            LayoutError::BlackoutOutOfBounds { left, top } => {
+                write!(
+                    f,
+                    "blackout at ({}, {}) does not fit in the layout",
+                    left, top
+                )
+            }
+            LayoutError::MinExceedsMax => write!(f, "min dimensions are larger than max"),
+            LayoutError::BlackoutContainsBoxChar { grapheme } => {
+                write!(f, "blackout contains the box-drawing grapheme {}", grapheme)
+            }
+            LayoutError::AnchorOutOfBounds { x, y } => {
+                write!(f, "anchor at ({}, {}) does not fit in the layout", x, y)
+            }
+            LayoutError::FillerIsBoxChar { filler } => {
+                write!(f, "padding filler {} is a box-drawing grapheme", filler)
+            }
+        }
+    }
+}
+
+impl std::error::Error for LayoutError {}
+
+/**
+Reasons a box diagram could not be read back against its layout.
+*/
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BoxError {
+    /// The diagram has a different number of box-drawing cells than the layout, usually because it was truncated.
+    Mismatch { expected: usize, found: usize },
+    /// The cell at this index, counting only data cells, isn't drawn with a connection the layout allows there.
+    CellMismatch { index: usize },
+    /// The cells matched the layout, but the bytes could not be deserialized.
+    Deserialize(postcard::Error),
+}
+
+impl std::fmt::Display for BoxError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BoxError::Mismatch { expected, found } => write!(
+                f,
+                "layout has {} data cells, but the diagram has {}",
+                expected, found
+            ),
+            BoxError::CellMismatch { index } => {
+                write!(f, "data cell {} does not match the layout", index)
+            }
+            BoxError::Deserialize(e) => write!(f, "could not deserialize boxes: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for BoxError {}
+
+pub fn layout_byte_length(length: usize, config: Option<BoxLayoutConfig>) -> Option<BoxLayout> {
+    layout_byte_length_checked(length, config).ok()
+}
+
+/**
+Finds a layout which can hold the given number of bytes, like layout_byte_length, but explains why when it can't.
+*/
+pub fn layout_byte_length_checked(
+    length: usize,
+    config: Option<BoxLayoutConfig>,
+) -> Result<BoxLayout, LayoutError> {
+    let bounds = LayoutBounds::from_config(length, config.as_ref())?;
+    // We establish the base layout, with everything filled in...
+    let mut layout = BoxLayout::new(bounds.min_width, bounds.min_height);
+    // And then we blackout the areas that the user wants to blackout.
+    bounds.apply_blackouts(&mut layout);
+    bounds.grow(&mut layout);
+    bounds.check(bounds.place_anchored(layout)?)
+}
+
+/**
+Finds a layout which can hold the given number of bytes, sizing the grid in two phases.
+
+The grid is first grown as though every cell were usable, and only then are the blackouts overlaid. If the blackouts
+cost too much capacity, the grid grows further from there. This keeps the size of the layout driven by the data, with
+the blackouts only adding what they take away, where layout_byte_length_checked grows around the blackouts from the
+start.
+*/
+pub fn layout_byte_length_v2(
+    length: usize,
+    config: Option<BoxLayoutConfig>,
+) -> Result<BoxLayout, LayoutError> {
+    let bounds = LayoutBounds::from_config(length, config.as_ref())?;
+    let mut layout = BoxLayout::new(bounds.min_width, bounds.min_height);
+    bounds.grow(&mut layout);
+    bounds.apply_blackouts(&mut layout);
+    bounds.grow(&mut layout);
+    bounds.check(bounds.place_anchored(layout)?)
+}
+
+/**
+The limits a layout has to grow within, resolved from a config.
+*/
+struct LayoutBounds {
+    bit_length: usize,
+    min_width: usize,
+    min_height: usize,
+    max_width: usize,
+    max_height: usize,
+    aspect_ratio: f32,
+    style: BoxStyle,
+    blackouts: Vec<(usize, usize, String)>,
+    anchored_blackouts: Vec<(BlackoutAnchor, usize, usize, String)>,
+}
+
+impl LayoutBounds {
+    fn from_config(length: usize, config: Option<&BoxLayoutConfig>) -> Result<Self, LayoutError> {
+        let bit_length = length * 8;
+        let mut min_width = config.and_then(|c| c.min_width).unwrap_or(2);
+        let mut min_height = config.and_then(|c| c.min_height).unwrap_or(2);
+        // Without a max, the layout can grow as far as the data needs, and never needs to be smaller than the min.
+        let max_width = config
+            .and_then(|c| c.max_width)
+            .unwrap_or(bit_length.max(min_width));
+        let max_height = config
+            .and_then(|c| c.max_height)
+            .unwrap_or(bit_length.max(min_height));
+        let aspect_ratio = config.and_then(|c| c.aspect_ratio).unwrap_or(1.0);
+        let style = config.map(|c| c.style).unwrap_or_default();
+        let mut blackouts = config.map(|c| c.blackouts.clone()).unwrap_or_default();
+        for (left, top, value) in &blackouts {
+            // We want to have a box around any text, so we need to add 1 past that.
+            // If the user wants to center the text, they can add their own whitespace.
+            if let Some(grapheme) = value.graphemes(true).find(|g| is_box_grapheme(g)) {
+                return Err(LayoutError::BlackoutContainsBoxChar {
+                    grapheme: grapheme.to_string(),
+                });
+            }
+            let (width, height) = blackout_extent(value);
+            if left + width + 1 > max_width || top + height > max_height {
+                return Err(LayoutError::BlackoutOutOfBounds {
+                    left: *left,
+                    top: *top,
+                });
+            }
+            min_width = min_width.max(left + width + 1);
+            min_height = min_height.max(top + height);
+        }
+        let anchored_blackouts = config
+            .map(|c| c.anchored_blackouts.clone())
+            .unwrap_or_default();
+        for (anchor, x, y, value) in &anchored_blackouts {
+            if let Some(grapheme) = value.graphemes(true).find(|g| is_box_grapheme(g)) {
+                return Err(LayoutError::BlackoutContainsBoxChar {
+                    grapheme: grapheme.to_string(),
+                });
+            }
+            // Wherever it's anchored, the layout must be at least as large as the blackout and its offsets. From the
+            // center, the offsets only count from half of the free space, so they need twice as much room.
+            let (width, height) = blackout_extent(value);
+            let (x_room, y_room) = match anchor {
+                BlackoutAnchor::Center => (2 * x, 2 * y),
+                _ => (*x, *y),
+            };
+            if x_room + width + 1 > max_width || y_room + height > max_height {
+                return Err(LayoutError::BlackoutOutOfBounds { left: *x, top: *y });
+            }
+            min_width = min_width.max(x_room + width + 1);
+            min_height = min_height.max(y_room + height);
+        }
+        if let Some(PadPolicy::Filler(filler)) = config.map(|c| c.pad) {
+            if is_box_grapheme(filler.encode_utf8(&mut [0; 4])) {
+                return Err(LayoutError::FillerIsBoxChar { filler });
+            }
+        }
+        // Anchors are single cell blackouts, but unlike blackout text they can sit against the edge of the layout.
+        for (x, y) in config.map(|c| c.anchors.as_slice()).unwrap_or_default() {
+            if *x >= max_width || *y >= max_height {
+                return Err(LayoutError::AnchorOutOfBounds { x: *x, y: *y });
+            }
+            min_width = min_width.max(x + 1);
+            min_height = min_height.max(y + 1);
+            blackouts.push((*x, *y, ANCHOR.to_string()));
+        }
+        if min_width > max_width || min_height > max_height {
+            return Err(LayoutError::MinExceedsMax);
+        }
+        if config.is_some_and(|c| c.seed_square)
+            && BoxLayout::new(min_width, min_height).calculate_bits_with_style(style) < bit_length
+        {
+            // estimate_bits counts a fully filled, weighted layout, so the square is a starting point, not a promise.
+            let mut side = 2;
+            while BoxLayout::estimate_bits(side, side) < bit_length
+                && (side < max_width || side < max_height)
+            {
+                side += 1;
+            }
+            min_width = min_width.max(side.min(max_width));
+            min_height = min_height.max(side.min(max_height));
+        }
+        Ok(LayoutBounds {
+            bit_length,
+            min_width,
+            min_height,
+            max_width,
+            max_height,
+            aspect_ratio,
+            style,
+            blackouts,
+            anchored_blackouts,
+        })
+    }
+
+    fn apply_blackouts(&self, layout: &mut BoxLayout) {
+        for (left, top, value) in &self.blackouts {
+            layout.set_blackout(*left, *top, value).unwrap();
+        }
+    }
+
+    /**
+    Adds rows or columns until the layout holds enough bits, or can't grow any more.
+    */
+    fn grow(&self, layout: &mut BoxLayout) {
+        while layout.calculate_bits_with_style(self.style) < self.bit_length
+            && self.can_grow(layout)
+        {
+            self.grow_step(layout);
+        }
+    }
+
+    fn can_grow(&self, layout: &BoxLayout) -> bool {
+        !(layout.height() >= self.max_height && layout.width() >= self.max_width)
+    }
+
+    /**
+    Adds whichever of a row or a column keeps the layout closer to the aspect ratio, within the max dimensions.
+    */
+    fn grow_step(&self, layout: &mut BoxLayout) {
+        let new_row = (row_is_closer(layout.width(), layout.height(), self.aspect_ratio)
+            && layout.height() < self.max_height)
+            || layout.width() >= self.max_width;
+        if new_row {
+            // We need to add a row.
+            layout.0.push(vec![Cell::Filled; layout.width()]);
+        } else {
+            // We need to add a column.
+            for row in layout.0.iter_mut() {
+                row.push(Cell::Filled);
+            }
+        }
+    }
+
+    /**
+    Places the anchored blackouts against the grown layout. If they cost too much capacity, the layout grows further
+    and they're placed again against its new edges.
+
+    from_config makes the minimum size large enough for every anchored blackout, so this only fails with
+    BlackoutOutOfBounds if a layout somehow ends up smaller than that.
+    */
+    fn place_anchored(&self, mut layout: BoxLayout) -> Result<BoxLayout, LayoutError> {
+        loop {
+            let mut placed = layout.clone();
+            for (anchor, x, y, value) in &self.anchored_blackouts {
+                anchor
+                    .resolve(
+                        (placed.width(), placed.height()),
+                        blackout_extent(value),
+                        (*x, *y),
+                    )
+                    .and_then(|(left, top)| placed.set_blackout(left, top, value))
+                    .ok_or(LayoutError::BlackoutOutOfBounds { left: *x, top: *y })?;
+            }
+            if placed.calculate_bits_with_style(self.style) >= self.bit_length
+                || !self.can_grow(&layout)
+            {
+                return Ok(placed);
+            }
+            self.grow_step(&mut layout);
+        }
+    }
+
+    fn check(&self, layout: BoxLayout) -> Result<BoxLayout, LayoutError> {
+        let available_bits = layout.calculate_bits_with_style(self.style);
+        if available_bits >= self.bit_length {
+            Ok(layout)
+        } else {
+            Err(LayoutError::CapacityExceeded {
+                needed_bits: self.bit_length,
+                available_bits,
+            })
+        }
+    }
+}
+
+/**
+Whether adding a row, rather than a column, lands closer to the requested aspect ratio.
+*/
+fn row_is_closer(width: usize, height: usize, aspect_ratio: f32) -> bool {
+    let (width, height) = (width as f32, height as f32);
+    let row_deviation = ((height + 1.0) / width - aspect_ratio).abs();
+    let column_deviation = (height / (width + 1.0) - aspect_ratio).abs();
+    row_deviation < column_deviation
+}
+
+/**
+Checks whether a grapheme belongs to any style's box-drawing sets.
+*/
+fn is_box_grapheme(grapheme: &str) -> bool {
+    find_box_grapheme(grapheme).is_some()
+}
+
+/**
+The width of the widest line and the number of lines in a blackout value.
+*/
+fn blackout_extent(value: &str) -> (usize, usize) {
+    let lines = value.split('\n');
+    (
+        lines
+            .clone()
+            .map(|line| line.graphemes(true).map(grapheme_width).sum())
+            .max()
+            .unwrap_or(0),
+        lines.count(),
+    )
+}
+
+/**
+The number of cells a blackout grapheme takes up. Without the unicode-width feature, every grapheme takes one cell.
+*/
+#[cfg(feature = "unicode-width")]
+fn grapheme_width(grapheme: &str) -> usize {
+    unicode_width::UnicodeWidthStr::width(grapheme).max(1)
+}
+
+#[cfg(not(feature = "unicode-width"))]
+fn grapheme_width(_grapheme: &str) -> usize {
+    1
+}
+
+/**
+Reports how many bytes the largest layout permitted by the config can hold, after blackouts and anchors are removed.
+
+Returns None if the config doesn't set both a max width and max height, since the layout could then grow without
+limit, or if a blackout doesn't fit within the max dimensions.
+*/
+pub fn max_bytes_for_config(config: &BoxLayoutConfig) -> Option<usize> {
+    let mut layout = BoxLayout::new(config.max_width?, config.max_height?);
+    for (left, top, value) in &config.blackouts {
+        layout.set_blackout(*left, *top, value)?;
+    }
+    for (x, y) in &config.anchors {
+        layout.set_blackout(*x, *y, ANCHOR)?;
+    }
+    for (anchor, x, y, value) in &config.anchored_blackouts {
+        let (left, top) = anchor.resolve(
+            (layout.width(), layout.height()),
+            blackout_extent(value),
+            (*x, *y),
+        )?;
+        layout.set_blackout(left, top, value)?;
+    }
+    Some(layout.calculate_bits_with_style(config.style) / 8)
+}
+
+/**
+Draws boxes for the bytes. Panics if the config can't fit them; see generate_boxes_checked.
+*/
+pub fn generate_boxes(bytes: &[u8], config: Option<BoxLayoutConfig>) -> String {
+    generate_boxes_checked(bytes, config).unwrap()
+}
+
+/**
+Draws boxes for the bytes, or explains why the config can't fit them, like CapacityExceeded when even the largest
+layout the config allows is too small.
+*/
+pub fn generate_boxes_checked(
+    bytes: &[u8],
+    config: Option<BoxLayoutConfig>,
+) -> Result<String, LayoutError> {
+    let config = config.unwrap_or_default();
+    let layout = layout_byte_length_checked(bytes.len(), Some(config.clone()))?;
+    Ok(layout.display_bytes_with_config(bytes, &config))
+}
+
+/**
+Serializes a value and draws it as boxes. Panics if the config can't fit it; see create_boxes_checked.
+*/
+pub fn create_boxes<T: serde::Serialize>(t: &T, config: Option<BoxLayoutConfig>) -> String {
+    create_boxes_checked(t, config).unwrap()
+}
+
+/**
+Serializes a value and draws it as boxes, or explains why the config can't fit it. A value too large for the config's
+max width and height gives CapacityExceeded, with the bits the value needs and the bits the largest layout holds.
+*/
+pub fn create_boxes_checked<T: serde::Serialize>(
+    t: &T,
+    config: Option<BoxLayoutConfig>,
+) -> Result<String, LayoutError> {
+    let data = postcard::to_allocvec(t).unwrap();
+    generate_boxes_checked(data.as_slice(), config)
+}
+
+pub fn create_boxes_with_layout<T: serde::Serialize>(t: &T, layout: BoxLayout) -> String {
+    let data = postcard::to_allocvec(t).unwrap();
+    layout.display_bytes(data.as_slice())
+}
+
+/**
+Reads the point and bit width of every box-drawing grapheme in a diagram, skipping everything else.
+
+Graphemes are matched whole against each connection's set, so the order the sets are checked in doesn't matter.
+*/
+pub fn parse_boxes_to_points(s: &str) -> Vec<(u8, usize)> {
+    s.graphemes(true)
+        // Ignore non-box characters.
+        .filter_map(find_box_grapheme)
+        .map(|(style, connection, point)| (point, style.get_bits(connection)))
+        .collect()
+}
+
+/**
+The number of data bits a box diagram actually carries, summed from its graphemes, without knowing the layout that
+drew it. For a diagram drawn with PadPolicy::Zeroes, this is the layout's calculate_bits_with_style.
+*/
+pub fn realized_bits(s: &str) -> usize {
+    parse_boxes_to_points(s).iter().map(|(_, bits)| bits).sum()
+}
+
+pub fn box_points_to_bytes(points: &[(u8, usize)]) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    let mut bits = 0;
+    let mut offset = 0;
+    for (point, bits_per_point) in points {
+        if offset == 0 {
+            bits = *point as u32;
+            offset = *bits_per_point;
+        } else {
+            bits |= (*point as u32) << offset;
+            offset += *bits_per_point;
+        }
+        while offset >= 8 {
+            bytes.push((bits & 0xff) as u8);
+            bits >>= 8;
+            offset -= 8;
+        }
+    }
+    bytes
+}
+
+/**
+Reads the raw bytes back out of a box diagram, mirroring generate_boxes.
+
+Cells left over after the data are drawn as zero points by default, so the result may be followed by zero bytes that
+weren't in the original data. Diagrams drawn with PadPolicy::Blank or PadPolicy::Filler read back as exactly the data.
+*/
+pub fn bytes_from_boxes(s: &str) -> Vec<u8> {
+    box_points_to_bytes(&parse_boxes_to_points(s))
+}
+
+/**
+Reads the point and bit width of every box-drawing grapheme in a diagram, visiting the cells in the given order.
+
+The grid is rebuilt from the lines of the diagram, with wide graphemes taking as many cells as they do in a layout, so
+this reads diagrams drawn with BoxLayoutConfig::traversal. RowMajor gives the same points as parse_boxes_to_points.
+*/
+pub fn parse_boxes_to_points_ordered(s: &str, order: TraversalOrder) -> Vec<(u8, usize)> {
+    let rows: Vec<Vec<Option<&str>>> = s
+        .split('\n')
+        .map(|line| {
+            line.graphemes(true)
+                .flat_map(|grapheme| {
+                    core::iter::once(Some(grapheme))
+                        .chain(core::iter::repeat_n(None, grapheme_width(grapheme) - 1))
+                })
+                .collect()
+        })
+        .collect();
+    let width = rows.iter().map(|row| row.len()).max().unwrap_or(0);
+    order
+        .positions(width, rows.len())
+        .into_iter()
+        .filter_map(|(x, y)| rows[y].get(x).copied().flatten())
+        .filter_map(find_box_grapheme)
+        .map(|(style, connection, point)| (point, style.get_bits(connection)))
+        .collect()
+}
+
+/**
+Reads the raw bytes back out of a box diagram drawn in the given traversal order, like bytes_from_boxes.
+*/
+pub fn bytes_from_boxes_ordered(s: &str, order: TraversalOrder) -> Vec<u8> {
+    box_points_to_bytes(&parse_boxes_to_points_ordered(s, order))
+}
+
+/**
+Draws boxes for the bytes, prefixed with their length, so bytes_from_boxes_framed can drop the padding after them.
+Fails like generate_boxes_checked if the config can't fit the bytes and their length.
+*/
+pub fn generate_boxes_framed(
+    bytes: &[u8],
+    config: Option<BoxLayoutConfig>,
+) -> Result<String, LayoutError> {
+    let mut data = Vec::new();
+    push_length_bytes(bytes.len(), &mut data);
+    data.extend_from_slice(bytes);
+    generate_boxes_checked(&data, config)
+}
+
+/**
+Reads exactly the bytes drawn by generate_boxes_framed.
+
+Returns None if the length prefix is missing, or there are fewer bytes than the prefix promises.
+*/
+pub fn bytes_from_boxes_framed(s: &str) -> Option<Vec<u8>> {
+    let bytes = bytes_from_boxes(s);
+    let (length, consumed) = take_length_bytes(&bytes)?;
+    bytes
+        .get(consumed..consumed + length)
+        .map(|bytes| bytes.to_vec())
+}
+
+/**
+Opens the ruler lines which delimit a box diagram, marking its left edge.
+*/
+pub const DELIMITER_OPEN: char = '⟦';
+/**
+Closes the ruler lines which delimit a box diagram, marking its right edge.
+*/
+pub const DELIMITER_CLOSE: char = '⟧';
+
+/**
+Puts ruler lines above and below a box diagram, spanning exactly its columns, so it can be framed with other box art.
+*/
+pub fn delimit_boxes(diagram: &str) -> String {
+    let width = diagram
+        .split('\n')
+        .map(|line| line.graphemes(true).map(grapheme_width).sum::<usize>())
+        .max()
+        .unwrap_or(0)
+        .max(2);
+    let mut ruler = String::new();
+    ruler.push(DELIMITER_OPEN);
+    ruler.extend(core::iter::repeat_n('=', width - 2));
+    ruler.push(DELIMITER_CLOSE);
+    format!("{}\n{}\n{}", ruler, diagram, ruler)
+}
+
+/**
+Draws boxes for the bytes between ruler lines, like delimit_boxes.
+*/
+pub fn generate_boxes_delimited(
+    bytes: &[u8],
+    config: Option<BoxLayoutConfig>,
+) -> Result<String, LayoutError> {
+    Ok(delimit_boxes(&generate_boxes_checked(bytes, config)?))
+}
+
+/**
+The columns spanned by a ruler line, from its DELIMITER_OPEN to its DELIMITER_CLOSE, inclusive.
+*/
+fn ruler_columns(line: &str) -> Option<(usize, usize)> {
+    let mut open = None;
+    let mut column = 0;
+    for grapheme in line.graphemes(true) {
+        if grapheme.starts_with(DELIMITER_OPEN) {
+            open = Some(column);
+        } else if grapheme.starts_with(DELIMITER_CLOSE) {
+            if let Some(open) = open {
+                return Some((open, column));
+            }
+        }
+        column += grapheme_width(grapheme);
+    }
+    None
+}
+
+/**
+Finds the first diagram between ruler lines and crops it to the ruler's columns, dropping any box art framing it.
+
+Returns None if there is no opening ruler, or no closing ruler after it.
+*/
+pub fn extract_delimited_boxes(s: &str) -> Option<String> {
+    let mut lines = s.split('\n');
+    let (start, end) = lines.by_ref().find_map(ruler_columns)?;
+    let mut block = Vec::new();
+    for line in lines {
+        if ruler_columns(line).is_some() {
+            return Some(block.join("\n"));
+        }
+        let mut column = 0;
+        let mut cropped = String::new();
+        for grapheme in line.graphemes(true) {
+            let width = grapheme_width(grapheme);
+            if column >= start && column + width <= end + 1 {
+                cropped.push_str(grapheme);
+            }
+            column += width;
+        }
+        block.push(cropped);
+    }
+    None
+}
+
+/**
+Reads the bytes of a diagram drawn by generate_boxes_delimited, ignoring any box art outside its rulers.
+*/
+pub fn bytes_from_boxes_delimited(s: &str) -> Option<Vec<u8>> {
+    extract_delimited_boxes(s).map(|diagram| bytes_from_boxes(&diagram))
+}
+
+/**
+Draws boxes for the bytes like generate_boxes_framed, but the first byte is a header with the id of the config's style,
+its index in BoxStyle::ALL. Box graphemes are read the same whichever style drew them, so the header is there to tell
+a reader which style to answer in, and to mark the diagram as self-describing.
+*/
+pub fn generate_boxes_self_describing(
+    bytes: &[u8],
+    config: Option<BoxLayoutConfig>,
+) -> Result<String, LayoutError> {
+    let style = config.as_ref().map(|c| c.style).unwrap_or_default();
+    let id = BoxStyle::ALL.iter().position(|s| *s == style).unwrap();
+    let mut data = vec![id as u8];
+    push_length_bytes(bytes.len(), &mut data);
+    data.extend_from_slice(bytes);
+    generate_boxes_checked(&data, config)
+}
+
+/**
+Reads the style and exactly the bytes drawn by generate_boxes_self_describing.
+
+Returns None if the header isn't a known style, or the bytes are cut short.
+*/
+pub fn bytes_from_boxes_self_describing(s: &str) -> Option<(BoxStyle, Vec<u8>)> {
+    let bytes = bytes_from_boxes(s);
+    let (id, bytes) = bytes.split_first()?;
+    let style = *BoxStyle::ALL.get(*id as usize)?;
+    let (length, consumed) = take_length_bytes(bytes)?;
+    let bytes = bytes.get(consumed..consumed + length)?;
+    Some((style, bytes.to_vec()))
+}
+
+/**
+Reads a value from a box diagram. A DecodeError::Truncated suggests the diagram was cut off when it was copied.
+*/
+pub fn parse_boxes<T: serde::de::DeserializeOwned>(s: &str) -> Result<T, DecodeError> {
+    decode_postcard(&bytes_from_boxes(s))
+}
+
+/**
+Parses a box diagram by walking the cells of the layout which produced it, in the same order as the encoder.
+
+Blackout cells are skipped by their length, rather than by guessing which graphemes are box characters, so blackout
+text may safely contain box-drawing characters. Parsing stops at the first data cell which doesn't hold a grapheme
+from its connection's set.
+*/
+pub fn parse_boxes_to_points_with_layout(s: &str, layout: &BoxLayout) -> Vec<(u8, usize)> {
+    let mut points = Vec::new();
+    for (y, row) in s.split('\n').take(layout.height()).enumerate() {
+        let mut graphemes = row.graphemes(true).peekable();
+        for x in 0..layout.width() {
+            if let Some(blackout) = layout.get_blackout_at(x, y) {
+                for _ in blackout.graphemes(true) {
+                    graphemes.next();
+                }
+            } else if let Some(connection) = layout.get_connections_at(x, y) {
+                // Whichever style the grapheme belongs to determines how many bits it carries.
+                let point = graphemes.next().and_then(|grapheme| {
+                    BoxStyle::ALL.iter().find_map(|style| {
+                        connection
+                            .get_characters(*style)
+                            .graphemes(true)
+                            .position(|g| g == grapheme)
+                            .map(|point| (point as u8, connection.get_bits(*style)))
+                    })
+                });
+                match point {
+                    Some(point) => points.push(point),
+                    None => return points,
+                }
+            } else if graphemes.peek() == Some(&" ") {
+                graphemes.next();
+            }
+        }
+    }
+    points
+}
+
+pub fn parse_boxes_with_layout<T: serde::de::DeserializeOwned>(
+    s: &str,
+    layout: &BoxLayout,
+) -> Result<T, DecodeError> {
+    let points = parse_boxes_to_points_with_layout(s, layout);
+    let bytes = box_points_to_bytes(&points);
+    decode_postcard(&bytes)
+}
+
+/**
+Checks that the box-drawing graphemes in a diagram line up with the data cells of the layout, before reading them.
+
+Each cell must carry as many bits as its connection does in some style, so a diagram which has been truncated, or
+had lines added or removed, is reported rather than decoded into garbage.
+*/
+pub fn parse_boxes_verified<T: serde::de::DeserializeOwned>(
+    s: &str,
+    layout: &BoxLayout,
+) -> Result<T, BoxError> {
+    let points = parse_boxes_to_points(s);
+    let connections = layout.data_connections();
+    if points.len() != connections.len() {
+        return Err(BoxError::Mismatch {
+            expected: connections.len(),
+            found: points.len(),
+        });
+    }
+    for (index, ((_, bits), connection)) in points.iter().zip(connections).enumerate() {
+        if !BoxStyle::ALL
+            .iter()
+            .any(|style| connection.get_bits(*style) == *bits)
+        {
+            return Err(BoxError::CellMismatch { index });
+        }
+    }
+    postcard::from_bytes(&box_points_to_bytes(&points)).map_err(BoxError::Deserialize)
+}
+
+pub fn gen_layout(value: &str) -> BoxLayout {
+    BoxLayout(
+        value
+            .split('\n')
+            .map(|row| {
+                row.chars()
+                    .filter(|c| !c.is_whitespace())
+                    .map(|c| Cell::from(c.to_string().as_str()))
+                    .collect()
+            })
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod boxes_tests {
+    use super::*;
+
+    #[test]
+    fn test_box_chars() {
+        assert_eq!(TOP_LEFT, "┌┍┎┏");
+        assert_eq!(TOP_RIGHT, "┐┑┒┓");
+        assert_eq!(BOTTOM_LEFT, "└┕┖┗");
+        assert_eq!(BOTTOM_RIGHT, "┘┙┚┛");
+        assert_eq!(LEFT, "├┝┞┟┠┡┢┣");
+        assert_eq!(RIGHT, "┤┥┦┧┨┩┪┫");
+        assert_eq!(TOP, "┬┭┮┯┰┱┲┳");
+        assert_eq!(BOTTOM, "┴┵┶┷┸┹┺┻");
+        assert_eq!(CROSS, "┼┽┾┿╀╁╂╃╄╅╆╇╈╉╊╋");
+        assert_eq!(HORIZONTAL, "─╼━╾");
+        assert_eq!(VERTICAL, "│╽┃╿");
+    }
+
+    #[test]
+    fn test_layout_template_round_trip() {
+        let template = "##X##\n\
+                        #HI##\n\
+                        #####";
+        let layout: BoxLayout = template.parse().unwrap();
+        assert_eq!(layout.get_blackout_at(2, 0), Some("X"));
+        assert!(layout.is_filled(0, 1));
+        assert_eq!(layout.to_string(), template);
+        let reloaded: BoxLayout = layout.to_string().parse().unwrap();
+        assert_eq!(reloaded.to_strings(), layout.to_strings());
+    }
+
+    #[test]
+    fn test_calculate_bits_in_layout() {
+        let layout = gen_layout(
+            "##\n\
+             ##",
+        );
+        assert_eq!(layout.calculate_bits(), 8);
+        assert_eq!(BoxLayout::estimate_bits(2, 2), 8);
+        let layout = gen_layout(
+            "####\n\
+             #XX#\n\
+             ####",
+        );
+        assert_eq!(layout.calculate_bits(), 20);
+        let layout = gen_layout(
+            "####\n\
+             ####\n\
+             ####",
+        );
+        assert_eq!(layout.calculate_bits(), 34);
+        assert_eq!(BoxLayout::estimate_bits(4, 3), 34);
+    }
+
+    #[test]
+    fn test_dimensions_for_bytes() {
+        for aspect_ratio in [1.0, 0.5, 0.25, 2.0] {
+            for length in [0, 1, 7, 32, 100, 500] {
+                let layout = layout_byte_length(
+                    length,
+                    Some(BoxLayoutConfig {
+                        aspect_ratio: Some(aspect_ratio),
+                        ..Default::default()
+                    }),
+                )
+                .unwrap();
+                assert_eq!(
+                    BoxLayout::dimensions_for_bytes(length, aspect_ratio),
+                    (layout.width(), layout.height())
+                );
+            }
+        }
+        assert_eq!(BoxLayout::dimensions_for_bytes(0, 1.0), (2, 2));
+        let (width, height) = BoxLayout::dimensions_for_bytes(100, 0.25);
+        assert!(width > height * 3);
+    }
+
+    #[test]
+    fn test_cells() {
+        let layout = gen_layout(
+            "###\n\
+             #X#",
+        );
+        assert_eq!(layout.0[1][1], Cell::Blackout("X".to_string()));
+        assert_eq!(
+            layout.to_strings(),
+            vec![vec!["#", "#", "#"], vec!["#", "X", "#"]]
+        );
+        assert_eq!(BoxLayout::from(layout.to_strings()).0, layout.0);
+
+        // A blackout of the fill marker is still a blackout.
+        let config = BoxLayoutConfig {
+            blackouts: vec![(1, 1, "#1".to_string())],
+            ..Default::default()
+        };
+        let layout = layout_byte_length(4, Some(config)).unwrap();
+        assert_eq!(layout.get_blackout_at(1, 1), Some("#"));
+        assert!(!layout.is_filled(1, 1));
+        let boxes = layout.display_bytes(&[1, 2, 3, 4]);
+        assert_eq!(boxes.lines().nth(1).unwrap().get(3..5), Some("#1"));
+    }
+
+    #[test]
+    fn test_unconnected_cells() {
+        // The top right cell is isolated, and the bottom right cell only has one neighbor.
+        let layout = gen_layout(
+            "###.#\n\
+             ###..\n\
+             ####.",
+        );
+        assert_eq!(layout.get_connections_at(4, 0), None);
+        assert_eq!(layout.get_connections_at(3, 2), None);
+        assert_eq!(layout.calculate_bits(), 25);
+        let bytes = [0b10110111, 0b01101100];
+        let boxes = layout.display_bytes(&bytes);
+        assert_eq!(boxes, "┏┱┑. \n┝╇┥..\n└┴┴ .");
+        // Unused cells are drawn as zeroes, so there may be extra bytes after the data.
+        assert!(box_points_to_bytes(&parse_boxes_to_points(&boxes)).starts_with(&bytes));
+        assert!(
+            box_points_to_bytes(&parse_boxes_to_points_with_layout(&boxes, &layout))
+                .starts_with(&bytes)
+        );
+    }
+
+    #[test]
+    fn test_trailing_bits() {
+        let layout = gen_layout(
+            "###\n\
+             ###",
+        );
+        // 8 bits don't line up with the 2 and 3 bit cells, so the last point is padded.
+        assert_eq!(layout.bytes_to_points(&[0xff]), vec![3, 7, 3, 1]);
+        let boxes = layout.display_bytes(&[0xff]);
+        assert_eq!(box_points_to_bytes(&parse_boxes_to_points(&boxes)), [0xff]);
+    }
+
+    #[test]
+    fn test_bytes_to_points() {
+        let layout = gen_layout(
+            "##\n\
+             ##",
+        );
+        assert_eq!(layout.bytes_to_points(&[0b01010101]), vec![1, 1, 1, 1]);
+        assert_eq!(layout.bytes_to_points(&[0b11110000]), vec![0, 0, 3, 3]);
+        let layout = gen_layout(
+            "####\n\
+             #XX#\n\
+             ####",
+        );
+        assert_eq!(
+            layout.bytes_to_points(&[0b01010101, 0b01010101]),
+            vec![1, 1, 1, 1, 1, 1, 1, 1]
+        );
+        assert_eq!(
+            layout.bytes_to_points(&[0b11110000, 0b11110000]),
+            vec![0, 0, 3, 3, 0, 0, 3, 3]
+        );
+    }
+
+    #[test]
+    fn test_display_bytes() {
+        let layout = gen_layout(
+            "##\n\
+             ##",
+        );
+        assert_eq!(layout.display_bytes(&[0]), "┌┐\n└┘");
+        assert_eq!(layout.display_bytes(&[0b01010101]), "┍┑\n┕┙");
+        assert_eq!(layout.display_bytes(&[0b11110000]), "┌┐\n┗┛");
+        let layout = gen_layout(
+            "####\n\
+             #XX#\n\
+             ####",
+        );
+        assert_eq!(
+            layout.display_bytes(&[0b01010101, 0b01010101]),
+            "┍╼╼┑\n╽XX╽\n┕╼─┘"
+        );
+        let layout = gen_layout(
+            "####\n\
+             ####\n\
+             #XX#",
+        );
+        assert_eq!(
+            layout.display_bytes(&[0b11110000, 0b11110000]),
+            "┌┰┳┐\n┠┻┴┤\n XX "
+        );
+        let layout = gen_layout(
+            "####\n\
+             ####\n\
+             ##XX",
+        );
+        assert_eq!(
+            layout.display_bytes(&[0b11110000, 0b11110000]),
+            "┌┰┳┐\n┠╃┴┘\n└┘XX"
+        );
+    }
+
+    #[test]
+    fn test_display_bytes_colored() {
+        let layout = gen_layout(
+            "####\n\
+             ####\n\
+             #XX#",
+        );
+        let palette = ColorPalette::default();
+        let colored = layout.display_bytes_colored(&[0b11110000], &palette);
+        assert_eq!(
+            colored,
+            "\x1b[32m┌\x1b[0m\x1b[32m┰\x1b[0m\x1b[32m┳\x1b[0m\x1b[90m┐\x1b[0m\n\
+             \x1b[90m├\x1b[0m\x1b[90m┴\x1b[0m\x1b[90m┴\x1b[0m\x1b[90m┤\x1b[0m\n \
+             \x1b[1;33mXX\x1b[0m "
+        );
+        let stripped = colored
+            .split('\x1b')
+            .enumerate()
+            .map(|(i, part)| {
+                if i == 0 {
+                    part
+                } else {
+                    &part[part.find('m').unwrap() + 1..]
+                }
+            })
+            .collect::<String>();
+        assert_eq!(stripped, layout.display_bytes(&[0b11110000]));
+        assert_eq!(
+            parse_boxes_to_points(&colored),
+            parse_boxes_to_points(&stripped)
+        );
+    }
+
+    #[test]
+    fn test_layout_data() {
+        let config = BoxLayoutConfig {
+            min_width: Some(4),
+            min_height: Some(3),
+            max_width: None,
+            max_height: None,
+            aspect_ratio: Some(1.0),
+            blackouts: vec![(1, 1, "Hello".to_string())],
+            ..Default::default()
+        };
+        let layout = layout_byte_length(8, Some(config)).unwrap();
+        assert_eq!(layout.width(), 7);
+        assert_eq!(layout.height(), 5);
+        assert_eq!(layout.calculate_bits(), 84);
+
+        let layout = layout_byte_length(8, None).unwrap();
+        assert_eq!(layout.width(), 5);
+        assert_eq!(layout.height(), 5);
+        assert_eq!(layout.calculate_bits(), 80);
+
+        let config = BoxLayoutConfig {
+            max_width: Some(80),
+            max_height: Some(5),
+            ..Default::default()
+        };
+        let layout = layout_byte_length(150, Some(config)).unwrap();
+        assert_eq!(layout.width(), 68);
+        assert_eq!(layout.height(), 5);
+        assert_eq!(layout.calculate_bits(), 1214);
+    }
+
+    #[test]
+    fn test_layout_data_v2() {
+        let config = BoxLayoutConfig {
+            min_width: Some(4),
+            min_height: Some(3),
+            aspect_ratio: Some(1.0),
+            blackouts: vec![(1, 1, "Hello".to_string())],
+            ..Default::default()
+        };
+        let layout = layout_byte_length_v2(8, Some(config.clone())).unwrap();
+        assert!(layout.calculate_bits() >= 64);
+        assert_eq!(layout.0[1][1], Cell::Blackout("H".to_string()));
+        assert_eq!(
+            layout_byte_length_v2(8, None).unwrap().0,
+            layout_byte_length_checked(8, None).unwrap().0
+        );
+        let config = BoxLayoutConfig {
+            max_width: Some(10),
+            max_height: Some(3),
+            ..Default::default()
+        };
+        assert_eq!(
+            layout_byte_length_v2(20, Some(config)).map(|_| ()),
+            Err(LayoutError::CapacityExceeded {
+                needed_bits: 160,
+                available_bits: 94
+            })
+        );
+    }
+
+    #[test]
+    fn test_seed_square() {
+        let seeded = |config: BoxLayoutConfig| BoxLayoutConfig {
+            seed_square: true,
+            ..config
+        };
+        let layout = layout_byte_length(150, Some(seeded(BoxLayoutConfig::default()))).unwrap();
+        assert_eq!((layout.width(), layout.height()), (18, 18));
+        // Limits still win over the square.
+        let config = BoxLayoutConfig {
+            max_width: Some(80),
+            max_height: Some(5),
+            ..Default::default()
+        };
+        let layout = layout_byte_length(150, Some(seeded(config))).unwrap();
+        assert_eq!((layout.width(), layout.height()), (68, 5));
+        // So do larger minimums.
+        let config = BoxLayoutConfig {
+            min_width: Some(30),
+            min_height: Some(2),
+            ..Default::default()
+        };
+        assert_eq!(
+            layout_byte_length(20, Some(seeded(config.clone())))
+                .unwrap()
+                .0,
+            layout_byte_length(20, Some(config)).unwrap().0
+        );
+    }
+
+    #[test]
+    fn test_stats() {
+        let layout = gen_layout(
+            "####\n\
+             #XX#\n\
+             ####",
+        );
+        assert_eq!(
+            layout.stats(),
+            BoxStats {
+                total_cells: 12,
+                filled_cells: 10,
+                blackout_cells: 2,
+                total_bits: layout.calculate_bits(),
+                bits_per_filled_cell: layout.calculate_bits() as f32 / 10.0,
+            }
+        );
+        let layout = gen_layout("XX");
+        assert_eq!(layout.stats().filled_cells, 0);
+        assert_eq!(layout.stats().bits_per_filled_cell, 0.0);
+    }
+
+    #[test]
+    fn test_display_bytes_newlines() {
+        let layout = BoxLayout::new(2, 2);
+        assert_eq!(layout.capacity_bytes(), 1);
+        let full = layout.display_bytes(&[0xff]);
+        let partial = layout.display_bytes(&[]);
+        assert_eq!(full, "┏┓\n┗┛");
+        assert_eq!(partial, "┌┐\n└┘");
+        for width in 2..6 {
+            for height in 2..6 {
+                let layout = BoxLayout::new(width, height);
+                for length in 0..=layout.capacity_bytes() {
+                    let boxes = layout.display_bytes(&vec![0xa5; length]);
+                    assert!(!boxes.ends_with('\n'));
+                    assert_eq!(boxes.split('\n').count(), height);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_traversal_order() {
+        let bytes: Vec<u8> = (0..12u8).map(|i| i.wrapping_mul(37)).collect();
+        let layout = BoxLayout::new(9, 4);
+        assert!(layout.capacity_bytes() >= bytes.len());
+        let config = |traversal| BoxLayoutConfig {
+            traversal,
+            ..Default::default()
+        };
+        let row_major = layout.display_bytes_with_config(&bytes, &config(TraversalOrder::RowMajor));
+        assert_eq!(row_major, layout.display_bytes(&bytes));
+        assert_eq!(
+            parse_boxes_to_points_ordered(&row_major, TraversalOrder::RowMajor),
+            parse_boxes_to_points(&row_major)
+        );
+        for order in [TraversalOrder::ColumnMajor, TraversalOrder::Boustrophedonic] {
+            let boxes = layout.display_bytes_with_config(&bytes, &config(order));
+            assert_ne!(boxes, row_major);
+            assert_eq!(
+                bytes_from_boxes_ordered(&boxes, order)[..bytes.len()],
+                bytes[..]
+            );
+            // The connections don't depend on the data, so only the weights move around.
+            assert_eq!(strip_weights(&boxes), strip_weights(&row_major));
+        }
+        let config = BoxLayoutConfig {
+            min_width: Some(9),
+            blackouts: vec![(2, 1, "Wide: 字".to_string())],
+            traversal: TraversalOrder::ColumnMajor,
+            pad: PadPolicy::Blank,
+            ..Default::default()
+        };
+        let boxes = generate_boxes(&bytes, Some(config));
+        assert_eq!(
+            bytes_from_boxes_ordered(&boxes, TraversalOrder::ColumnMajor),
+            bytes
+        );
+    }
+
+    /**
+    The connection drawn by each box grapheme, keeping everything else, so diagrams can be compared without data.
+    */
+    fn strip_weights(s: &str) -> String {
+        s.graphemes(true)
+            .map(|g| match Connections::from_grapheme(g) {
+                Some((connection, _)) => connection.to_string(),
+                None => g.to_string(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_layout_cache() {
+        let config = BoxLayoutConfig {
+            aspect_ratio: Some(0.5),
+            blackouts: vec![(1, 1, "Cached".to_string())],
+            ..Default::default()
+        };
+        let mut cache: std::collections::HashMap<(usize, BoxLayoutConfig), BoxLayout> =
+            std::collections::HashMap::new();
+        let mut built = 0;
+        for _ in 0..3 {
+            cache.entry((20, config.clone())).or_insert_with(|| {
+                built += 1;
+                layout_byte_length(20, Some(config.clone())).unwrap()
+            });
+        }
+        assert_eq!(built, 1);
+        assert_eq!(cache.len(), 1);
+        assert_eq!(
+            cache[&(20, config.clone())],
+            layout_byte_length(20, Some(config.clone())).unwrap()
+        );
+        let other = BoxLayoutConfig {
+            aspect_ratio: Some(2.0),
+            ..config.clone()
+        };
+        assert_ne!(other, config);
+        assert!(!cache.contains_key(&(20, other)));
+        // Blackout text is part of a layout, not just which cells are filled.
+        assert_ne!(gen_layout("###\n#A#\n###"), gen_layout("###\n#B#\n###"));
+        assert_eq!(gen_layout("###\n#A#"), gen_layout("###\n#A#").clone());
+    }
+
+    #[test]
+    fn test_create_boxes_checked() {
+        let config = BoxLayoutConfig {
+            max_width: Some(4),
+            max_height: Some(4),
+            ..Default::default()
+        };
+        let small = create_boxes_checked(&"Hi", Some(config.clone())).unwrap();
+        assert_eq!(small, create_boxes(&"Hi", Some(config.clone())));
+        assert_eq!(parse_boxes::<String>(&small).unwrap(), "Hi");
+        let large = "Far too long for a 4 by 4 layout";
+        assert_eq!(
+            create_boxes_checked(&large, Some(config.clone())),
+            Err(LayoutError::CapacityExceeded {
+                needed_bits: (large.len() + 1) * 8,
+                available_bits: BoxLayout::estimate_bits(4, 4),
+            })
+        );
+        assert!(generate_boxes_checked(large.as_bytes(), Some(config)).is_err());
+    }
+
+    #[test]
+    fn test_realized_bits() {
+        let config = BoxLayoutConfig {
+            blackouts: vec![(1, 1, "Bits".to_string())],
+            ..Default::default()
+        };
+        let bytes = b"Some bytes to count";
+        let layout = layout_byte_length(bytes.len(), Some(config.clone())).unwrap();
+        let boxes = generate_boxes(bytes, Some(config));
+        assert_eq!(realized_bits(&boxes), layout.calculate_bits());
+        assert!(realized_bits(&boxes) >= bytes.len() * 8);
+        for style in BoxStyle::ALL {
+            let layout = BoxLayout::new(5, 4);
+            let boxes = layout.display_bytes_with_style(bytes, style);
+            assert_eq!(
+                realized_bits(&boxes),
+                layout.calculate_bits_with_style(style)
+            );
+        }
+        assert_eq!(realized_bits("No boxes here"), 0);
+    }
+
+    #[test]
+    fn test_pad_policy() {
+        for pad in [PadPolicy::Blank, PadPolicy::Filler('.')] {
+            let config = BoxLayoutConfig {
+                pad,
+                ..Default::default()
+            };
+            for length in 0..40 {
+                let bytes: Vec<u8> = (0..length).map(|i| 0xff - i as u8).collect();
+                let boxes = generate_boxes(&bytes, Some(config.clone()));
+                assert_eq!(bytes_from_boxes(&boxes), bytes);
+            }
+        }
+        let bytes = [0xff; 5];
+        let zeroes = generate_boxes(&bytes, None);
+        let blank = generate_boxes(
+            &bytes,
+            Some(BoxLayoutConfig {
+                pad: PadPolicy::Blank,
+                ..Default::default()
+            }),
+        );
+        let filled = generate_boxes(
+            &bytes,
+            Some(BoxLayoutConfig {
+                pad: PadPolicy::Filler('.'),
+                ..Default::default()
+            }),
+        );
+        assert!(bytes_from_boxes(&zeroes).len() > bytes.len());
+        assert_eq!(blank, filled.replace('.', " "));
+        assert_eq!(
+            zeroes.lines().next().unwrap(),
+            blank.lines().next().unwrap()
+        );
+        assert_eq!(
+            generate_boxes_checked(
+                &bytes,
+                Some(BoxLayoutConfig {
+                    pad: PadPolicy::Filler('┼'),
+                    ..Default::default()
+                })
+            ),
+            Err(LayoutError::FillerIsBoxChar { filler: '┼' })
+        );
+    }
+
+    #[test]
+    fn test_anchored_blackouts() {
+        let config = BoxLayoutConfig {
+            anchored_blackouts: vec![
+                (BlackoutAnchor::TopRight, 0, 0, "TR".to_string()),
+                (BlackoutAnchor::BottomLeft, 1, 0, "BL".to_string()),
+                (BlackoutAnchor::BottomRight, 0, 1, "BR".to_string()),
+                (BlackoutAnchor::Center, 0, 0, "C".to_string()),
+            ],
+            ..Default::default()
+        };
+        let value = ("Labels in the corners of a grown layout".to_string(), 7u32);
+        let length = postcard::to_allocvec(&value).unwrap().len();
+        let layout = layout_byte_length(length, Some(config.clone())).unwrap();
+        let (width, height) = (layout.width(), layout.height());
+        // Each label is measured from its corner, with a column to its right for the box around it.
+        assert_eq!(layout.get_blackout_at(width - 3, 0), Some("T"));
+        assert_eq!(layout.get_blackout_at(1, height - 1), Some("B"));
+        assert_eq!(layout.get_blackout_at(width - 3, height - 2), Some("B"));
+        assert_eq!(layout.get_blackout_at(width - 2, height - 2), Some("R"));
+        assert_eq!(
+            layout.get_blackout_at((width - 2) / 2, (height - 1) / 2),
+            Some("C")
+        );
+        assert!(layout.calculate_bits() >= length * 8);
+
+        let boxes = create_boxes(&value, Some(config.clone()));
+        assert_eq!(parse_boxes::<(String, u32)>(&boxes), Ok(value.clone()));
+        assert_eq!(parse_boxes_with_layout(&boxes, &layout), Ok(value));
+
+        let config = BoxLayoutConfig {
+            max_width: Some(4),
+            anchored_blackouts: vec![(BlackoutAnchor::BottomRight, 1, 0, "Wide".to_string())],
+            ..Default::default()
+        };
+        assert_eq!(
+            layout_byte_length_checked(4, Some(config)).err(),
+            Some(LayoutError::BlackoutOutOfBounds { left: 1, top: 0 })
+        );
+
+        // Offsets from the center need room on both sides, so the layout grows to fit them rather than panicking.
+        let config = BoxLayoutConfig {
+            anchored_blackouts: vec![(BlackoutAnchor::Center, 3, 1, "C".to_string())],
+            ..Default::default()
+        };
+        for layout in [
+            layout_byte_length_checked(1, Some(config.clone())).unwrap(),
+            layout_byte_length_v2(1, Some(config.clone())).unwrap(),
+        ] {
+            let (width, height) = (layout.width(), layout.height());
+            assert!(width >= 8 && height >= 3);
+            assert_eq!(
+                layout.get_blackout_at((width - 2) / 2 + 3, (height - 1) / 2 + 1),
+                Some("C")
+            );
+        }
+        let config = BoxLayoutConfig {
+            max_width: Some(7),
+            ..config
+        };
+        assert_eq!(
+            layout_byte_length_checked(1, Some(config)).err(),
+            Some(LayoutError::BlackoutOutOfBounds { left: 3, top: 1 })
+        );
+    }
+
+    #[test]
+    fn test_anchors() {
+        let config = BoxLayoutConfig {
+            min_width: Some(10),
+            max_width: Some(10),
+            min_height: Some(6),
+            max_height: Some(6),
+            anchors: vec![(0, 0), (9, 0), (0, 5), (9, 5)],
+            ..Default::default()
+        };
+        let value = ("Anchored".to_string(), 42u16);
+        let boxes = create_boxes(&value, Some(config.clone()));
+        let lines: Vec<Vec<&str>> = boxes
+            .lines()
+            .map(|line| line.graphemes(true).collect())
+            .collect();
+        for (x, y) in &config.anchors {
+            assert_eq!(lines[*y][*x], ANCHOR);
+        }
+        assert_eq!(parse_boxes::<(String, u16)>(&boxes), Ok(value.clone()));
+        let layout = layout_byte_length(
+            postcard::to_allocvec(&value).unwrap().len(),
+            Some(config.clone()),
+        )
+        .unwrap();
+        assert_eq!(parse_boxes_with_layout(&boxes, &layout), Ok(value));
+        assert_eq!(
+            max_bytes_for_config(&config),
+            Some(layout.calculate_bits() / 8)
+        );
+        let config = BoxLayoutConfig {
+            max_width: Some(10),
+            max_height: Some(6),
+            anchors: vec![(10, 0)],
+            ..Default::default()
+        };
+        assert_eq!(
+            layout_byte_length_checked(4, Some(config)).err(),
+            Some(LayoutError::AnchorOutOfBounds { x: 10, y: 0 })
+        );
+    }
+
+    #[test]
+    fn test_empty_layouts() {
+        for layout in [gen_layout(""), BoxLayout::new(0, 0), BoxLayout(vec![])] {
+            assert_eq!(layout.width(), 0);
+            assert_eq!(layout.calculate_bits(), 0);
+            assert_eq!(layout.capacity_bytes(), 0);
+            assert_eq!(layout.stats().total_cells, 0);
+            assert!(layout.bytes_to_points(&[1, 2, 3]).is_empty());
+            assert_eq!(layout.display_bytes(&[1, 2, 3]).trim(), "");
+            assert_eq!(layout.display_connections().trim(), "");
+            assert!(parse_boxes_to_points_with_layout("", &layout).is_empty());
+            assert!(parse_boxes_to_points_with_layout("┌┐\n└┘", &layout).is_empty());
+            assert_eq!(layout.to_string().trim(), "");
+        }
+        assert_eq!(BoxLayout::estimate_bits(0, 0), 0);
+        let boxes = generate_boxes(&[], None);
+        assert_eq!(boxes, "┌┐\n└┘");
+        assert_eq!(parse_boxes::<()>(&boxes), Ok(()));
+        assert_eq!(bytes_from_boxes(""), Vec::<u8>::new());
+    }
+
+    #[test]
+    fn test_capacity() {
+        let layout = gen_layout(
+            "####\n\
+             ####\n\
+             ####",
+        );
+        assert_eq!(layout.capacity_bytes(), 4);
+        let config = BoxLayoutConfig {
+            max_width: Some(80),
+            max_height: Some(5),
+            ..Default::default()
+        };
+        assert_eq!(
+            max_bytes_for_config(&config),
+            Some(BoxLayout::estimate_bits(80, 5) / 8)
+        );
+        let layout = layout_byte_length(max_bytes_for_config(&config).unwrap(), Some(config));
+        assert!(layout.is_some());
+        let config = BoxLayoutConfig {
+            max_width: Some(80),
+            max_height: Some(5),
+            blackouts: vec![(1, 1, "Hello".to_string())],
+            ..Default::default()
+        };
+        assert_eq!(max_bytes_for_config(&config), Some(174));
+        assert!(layout_byte_length(174, Some(config)).is_some());
+        let config = BoxLayoutConfig {
+            max_width: Some(80),
+            ..Default::default()
+        };
+        assert_eq!(max_bytes_for_config(&config), None);
+    }
+
+    #[test]
+    fn test_layout_errors() {
+        let config = BoxLayoutConfig {
+            max_width: Some(10),
+            max_height: Some(3),
+            ..Default::default()
+        };
+        assert_eq!(
+            layout_byte_length_checked(20, Some(config)).err(),
+            Some(LayoutError::CapacityExceeded {
+                needed_bits: 160,
+                available_bits: 94
+            })
+        );
+        let config = BoxLayoutConfig {
+            max_width: Some(10),
+            blackouts: vec![(5, 1, "Hello".to_string())],
+            ..Default::default()
+        };
+        assert_eq!(
+            layout_byte_length_checked(4, Some(config)).err(),
+            Some(LayoutError::BlackoutOutOfBounds { left: 5, top: 1 })
+        );
+
+        for blackout in ["─┼─", "a╳b", "╔"] {
+            let config = BoxLayoutConfig {
+                blackouts: vec![(1, 1, blackout.to_string())],
+                ..Default::default()
+            };
+            assert_eq!(
+                layout_byte_length_checked(4, Some(config)).err(),
+                Some(LayoutError::BlackoutContainsBoxChar {
+                    grapheme: blackout.chars().find(|c| *c != 'a').unwrap().to_string()
+                })
+            );
+        }
+        let config = BoxLayoutConfig {
+            min_width: Some(12),
+            max_width: Some(10),
+            ..Default::default()
+        };
+        assert_eq!(
+            layout_byte_length_checked(4, Some(config)).err(),
+            Some(LayoutError::MinExceedsMax)
+        );
+        let config = BoxLayoutConfig {
+            max_width: Some(10),
+            max_height: Some(3),
+            ..Default::default()
+        };
+        assert!(generate_boxes_checked(&[0; 20], Some(config)).is_err());
+        assert_eq!(
+            generate_boxes_checked(&[0], None),
+            Ok(generate_boxes(&[0], None))
+        );
+    }
+
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    struct TestStruct {
+        comments: String,
+        code: u32,
+    }
+
+    #[test]
+    fn test_create_boxes() {
+        let test = TestStruct {
+            comments: "Hello".to_string(),
+            code: 42,
+        };
+        let config = BoxLayoutConfig {
+            min_width: Some(4),
+            min_height: Some(3),
+            max_width: None,
+            max_height: None,
+            aspect_ratio: Some(1.0),
+            blackouts: vec![(1, 1, " C+c ".to_string())],
+            ..Default::default()
+        };
+        let boxes = create_boxes(&test, Some(config));
+        assert_eq!(
+            boxes,
+            "┍╼───━┐\n\
+             ╽ C+c ╽\n\
+             ┝┯┰┱┭┲┪\n\
+             ┖┻┺┸┶┵┘"
+        );
+    }
+
+    #[test]
+    fn test_parse_boxes_to_points() {
+        let boxes = "┍╼───━┐\n\
+                           ╽ C+c ╽\n\
+                           ┝┯┰┱┭┲┪\n\
+                           ┖┻┺┸┶┵┘";
+        assert_eq!(
+            parse_boxes_to_points(boxes),
+            vec![
+                (1, 2),
+                (1, 2),
+                (0, 2),
+                (0, 2),
+                (0, 2),
+                (2, 2),
+                (0, 2),
+                (1, 2),
+                (1, 2),
+                (1, 3),
+                (3, 3),
+                (4, 3),
+                (5, 3),
+                (1, 3),
+                (6, 3),
+                (6, 3),
+                (2, 2),
+                (7, 3),
+                (6, 3),
+                (4, 3),
+                (2, 3),
+                (1, 3),
+                (0, 2)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_box_points_to_bytes() {
+        let box_points = parse_boxes_to_points("┌┐\n└┘");
+        assert_eq!(box_points_to_bytes(&box_points), vec![0b00000000]);
+        let box_points = parse_boxes_to_points("┍╼╼┑\n╽XX╽\n┕╼─┘");
+        assert_eq!(box_points_to_bytes(&box_points), [0b01010101, 0b01010101]);
+    }
+
+    #[test]
+    fn test_display_connections() {
+        assert_eq!(Connections::All.to_string(), "\u{253C}");
+        assert_eq!(Connections::RightDown.to_string(), "\u{250C}");
+        let layout = gen_layout(
+            "####\n\
+             #XX#\n\
+             ####",
+        );
+        assert_eq!(layout.display_connections(), "┌──┐\n│XX│\n└──┘");
+        let layout = gen_layout(
+            "###\n\
+             X#X",
+        );
+        // Cells with a single neighbour have no connection, so carry nothing.
+        assert_eq!(layout.display_connections(), "#┬#\nX#X");
+        assert_eq!(layout.to_string(), "###\nX#X");
+    }
+
+    #[test]
+    fn test_side_weights() {
+        let heavy = |left, right, up, down| SideWeights {
+            left,
+            right,
+            up,
+            down,
+        };
+        for connection in Connections::ALL {
+            let sides = match connection {
+                Connections::RightDown => heavy(false, true, false, true),
+                Connections::LeftDown => heavy(true, false, false, true),
+                Connections::RightUp => heavy(false, true, true, false),
+                Connections::LeftUp => heavy(true, false, true, false),
+                Connections::DownUp => heavy(false, false, true, true),
+                Connections::RightLeft => heavy(true, true, false, false),
+                Connections::RightLeftDown => heavy(true, true, false, true),
+                Connections::RightLeftUp => heavy(true, true, true, false),
+                Connections::RightDownUp => heavy(false, true, true, true),
+                Connections::LeftDownUp => heavy(true, false, true, true),
+                Connections::All => heavy(true, true, true, true),
+            };
+            let graphemes = connection
+                .get_characters(BoxStyle::Weighted)
+                .graphemes(true);
+            let count = graphemes.clone().count();
+            let mut seen = Vec::new();
+            for (point, grapheme) in graphemes.enumerate() {
+                let weights = connection.weights(point as u8).unwrap();
+                assert!(!seen.contains(&weights));
+                seen.push(weights);
+                // Only the sides the connection has can be heavy.
+                assert_eq!(weights.to_mask() & !sides.to_mask(), 0);
+                assert_eq!(
+                    connection
+                        .character_for_weights(weights)
+                        .unwrap()
+                        .to_string(),
+                    grapheme
+                );
+            }
+            assert!(seen.contains(&sides));
+            assert_eq!(connection.weights(count as u8), None);
+        }
+        assert_eq!(
+            Connections::RightDown.character_for_weights(heavy(false, true, false, false)),
+            Some('┍')
+        );
+        assert_eq!(
+            Connections::RightLeft.character_for_weights(heavy(false, true, false, false)),
+            Some('╼')
+        );
+        assert_eq!(
+            Connections::DownUp.character_for_weights(heavy(false, false, false, true)),
+            Some('╽')
+        );
+        assert_eq!(
+            Connections::RightDownUp.character_for_weights(heavy(false, true, true, false)),
+            Some('┡')
+        );
+        assert_eq!(
+            Connections::All.character_for_weights(heavy(true, true, true, false)),
+            Some('╇')
+        );
+        assert_eq!(
+            Connections::All.character_for_weights(heavy(false, false, true, true)),
+            Some('╂')
+        );
+        assert_eq!(
+            Connections::RightLeft.character_for_weights(heavy(false, false, true, false)),
+            None
+        );
+        assert_eq!(Connections::All.weights(0), Some(SideWeights::default()));
+    }
+
+    #[test]
+    fn test_connections_from_grapheme() {
+        for style in BoxStyle::ALL {
+            for connection in Connections::ALL {
+                for (point, grapheme) in
+                    connection.get_characters(style).graphemes(true).enumerate()
+                {
+                    assert_eq!(
+                        Connections::from_grapheme(grapheme),
+                        Some((connection, point as u8))
+                    );
+                }
+            }
+        }
+        assert_eq!(
+            Connections::from_grapheme("\u{254B}"),
+            Some((Connections::All, 15))
+        );
+        assert_eq!(Connections::from_grapheme("x"), None);
+        assert_eq!(Connections::from_grapheme("\u{254B}\u{0300}"), None);
+        assert_eq!(Connections::from_grapheme(""), None);
+    }
+
+    #[test]
+    fn test_parse_every_box_grapheme() {
+        let mut seen: Vec<(&str, Connections, usize)> = Vec::new();
+        for style in BoxStyle::ALL {
+            for connection in Connections::ALL {
+                let bits = style.get_bits(connection);
+                for (point, grapheme) in
+                    connection.get_characters(style).graphemes(true).enumerate()
+                {
+                    assert_eq!(parse_boxes_to_points(grapheme), vec![(point as u8, bits)]);
+                    // A grapheme shared between styles must mean the same thing in each.
+                    for (other, other_connection, other_bits) in &seen {
+                        if *other == grapheme {
+                            assert_eq!((*other_connection, *other_bits), (connection, bits));
+                        }
+                    }
+                    seen.push((grapheme, connection, bits));
+                }
+            }
+        }
+        let all: String = seen.iter().map(|(grapheme, _, _)| *grapheme).collect();
+        assert_eq!(
+            parse_boxes_to_points(&all),
+            seen.iter()
+                .map(|(grapheme, _, _)| parse_boxes_to_points(grapheme)[0])
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_box_alphabet() {
+        let alphabet = box_alphabet();
+        assert_eq!(alphabet.len(), 128);
+        for (c, bits) in alphabet {
+            assert_eq!(parse_boxes_to_points(&c.to_string())[0].1, *bits);
+        }
+        assert!(alphabet.contains(&('\u{254B}', 4)));
+        assert!(alphabet.contains(&('\u{2573}', 5)));
+        assert!(alphabet.contains(&('\u{256D}', 0)));
+    }
+
+    #[test]
+    fn test_parse_boxes_verified() {
+        let test = TestStruct {
+            comments: "Hello".to_string(),
+            code: 42,
+        };
+        let config = || BoxLayoutConfig {
+            min_width: Some(4),
+            min_height: Some(3),
+            blackouts: vec![(1, 1, "C+c".to_string())],
+            ..Default::default()
+        };
+        let boxes = create_boxes(&test, Some(config()));
+        let layout =
+            layout_byte_length(postcard::to_allocvec(&test).unwrap().len(), Some(config()))
+                .unwrap();
+        assert_eq!(parse_boxes_verified(&boxes, &layout), Ok(test));
+
+        // Losing the last line of the diagram loses its cells.
+        let lines: Vec<&str> = boxes.lines().collect();
+        let truncated = lines[..lines.len() - 1].join("\n");
+        let cells = parse_boxes_to_points(&boxes).len();
+        assert_eq!(
+            parse_boxes_verified::<TestStruct>(&truncated, &layout),
+            Err(BoxError::Mismatch {
+                expected: cells,
+                found: parse_boxes_to_points(&truncated).len(),
+            })
+        );
+
+        // Swapping a corner for a cross keeps the count, but not the shape.
+        let corner = boxes.graphemes(true).next().unwrap();
+        let swapped = boxes.replacen(corner, "┼", 1);
+        assert_eq!(
+            parse_boxes_verified::<TestStruct>(&swapped, &layout),
+            Err(BoxError::CellMismatch { index: 0 })
+        );
+    }
+
+    #[test]
+    fn test_parse_boxes_with_layout() {
+        let test = TestStruct {
+            comments: "Hello".to_string(),
+            code: 42,
+        };
+        let config = || BoxLayoutConfig {
+            min_width: Some(4),
+            min_height: Some(3),
+            aspect_ratio: Some(1.0),
+            blackouts: vec![(1, 1, " C+c ".to_string())],
+            ..Default::default()
+        };
+        let boxes = create_boxes(&test, Some(config()));
+        let layout =
+            layout_byte_length(postcard::to_allocvec(&test).unwrap().len(), Some(config()))
+                .unwrap();
+        assert_eq!(
+            parse_boxes_to_points_with_layout(&boxes, &layout),
+            parse_boxes_to_points(&boxes)
+        );
+        let test_2: TestStruct = parse_boxes_with_layout(&boxes, &layout).unwrap();
+        assert_eq!(test, test_2);
+
+        // Blackouts which contain box characters would be read as data without the layout.
+        // Configs reject these, so the blackout is placed by hand.
+        let config = BoxLayoutConfig {
+            min_width: Some(4),
+            min_height: Some(3),
+            aspect_ratio: Some(1.0),
+            blackouts: vec![(1, 1, "XXX".to_string())],
+            ..Default::default()
+        };
+        let mut layout =
+            layout_byte_length(postcard::to_allocvec(&test).unwrap().len(), Some(config)).unwrap();
+        layout.set_blackout(1, 1, "─┼─").unwrap();
+        let boxes = layout.display_bytes(&postcard::to_allocvec(&test).unwrap());
+        assert!(parse_boxes::<TestStruct>(&boxes).map_or(true, |t| t != test));
+        let test_2: TestStruct = parse_boxes_with_layout(&boxes, &layout).unwrap();
+        assert_eq!(test, test_2);
+    }
+
+    #[test]
+    fn test_aspect_ratio() {
+        for (aspect_ratio, (width, height)) in [(2.0, (13, 25)), (3.0, (11, 31)), (0.5, (25, 13))] {
+            let config = BoxLayoutConfig {
+                aspect_ratio: Some(aspect_ratio),
+                ..Default::default()
+            };
+            let layout = layout_byte_length(150, Some(config)).unwrap();
+            assert_eq!((layout.width(), layout.height()), (width, height));
+        }
+        let layout = layout_byte_length(150, None).unwrap();
+        assert_eq!((layout.width(), layout.height()), (18, 18));
+    }
+
+    #[test]
+    fn test_wide_blackouts() {
+        let config = || BoxLayoutConfig {
+            max_width: Some(20),
+            blackouts: vec![(1, 1, "日本語".to_string())],
+            ..Default::default()
+        };
+        let layout = layout_byte_length(1, Some(config())).unwrap();
+        let columns = if cfg!(feature = "unicode-width") {
+            6
+        } else {
+            3
+        };
+        assert_eq!(layout.width(), 1 + columns + 1);
+        assert_eq!(layout.get_blackout_at(1, 1), Some("日"));
+        assert!(layout.get_blackout_at(columns, 1).is_some());
+        assert!(layout.get_blackout_at(columns + 1, 1).is_none());
+
+        let test = TestStruct {
+            comments: "Hello".to_string(),
+            code: 42,
+        };
+        let boxes = create_boxes(&test, Some(config()));
+        assert!(boxes.contains("日本語"));
+        let test_2: TestStruct = parse_boxes(&boxes).unwrap();
+        assert_eq!(test, test_2);
+        let layout =
+            layout_byte_length(postcard::to_allocvec(&test).unwrap().len(), Some(config()))
+                .unwrap();
+        let test_3: TestStruct = parse_boxes_with_layout(&boxes, &layout).unwrap();
+        assert_eq!(test, test_3);
+    }
+
+    #[test]
+    fn test_bytes_from_boxes() {
+        let config = || BoxLayoutConfig {
+            min_width: Some(10),
+            max_width: Some(10),
+            min_height: Some(10),
+            max_height: Some(10),
+            ..Default::default()
+        };
+        let capacity = max_bytes_for_config(&config()).unwrap();
+        for _ in 0..100 {
+            let length = rand::random::<usize>() % (capacity + 1);
+            let bytes: Vec<u8> = (0..length).map(|_| rand::random::<u8>()).collect();
+            let boxes = generate_boxes(&bytes, Some(config()));
+            let decoded = bytes_from_boxes(&boxes);
+            assert_eq!(decoded[..bytes.len()], bytes[..]);
+            assert!(decoded[bytes.len()..].iter().all(|b| *b == 0));
+        }
+    }
+
+    #[test]
+    fn test_bytes_from_boxes_framed() {
+        for style in [BoxStyle::Weighted, BoxStyle::Double, BoxStyle::Diagonal] {
+            for length in 0..48 {
+                let bytes: Vec<u8> = (0..length).map(|_| rand::random::<u8>()).collect();
+                let config = BoxLayoutConfig {
+                    style,
+                    ..Default::default()
+                };
+                let boxes = generate_boxes_framed(&bytes, Some(config)).unwrap();
+                assert_eq!(bytes_from_boxes_framed(&boxes), Some(bytes));
+            }
+        }
+        // A diagram with room to spare, so that unframed decoding picks up padding.
+        let config = BoxLayoutConfig {
+            min_width: Some(10),
+            min_height: Some(10),
+            ..Default::default()
+        };
+        let boxes = generate_boxes_framed(&[1, 2, 3], Some(config)).unwrap();
+        assert!(bytes_from_boxes(&boxes).len() > 4);
+        assert_eq!(bytes_from_boxes_framed(&boxes), Some(vec![1, 2, 3]));
+        assert_eq!(bytes_from_boxes_framed(""), None);
+        // A config too small for the bytes is an error, rather than a panic.
+        let config = BoxLayoutConfig {
+            max_width: Some(2),
+            max_height: Some(2),
+            ..Default::default()
+        };
+        assert!(generate_boxes_framed(&[0; 20], Some(config)).is_err());
+    }
+
+    #[test]
+    fn test_multi_line_blackouts() {
+        let config = BoxLayoutConfig {
+            blackouts: vec![(1, 1, "Hello\nWorld!".to_string())],
+            ..Default::default()
+        };
+        let layout = layout_byte_length(1, Some(config)).unwrap();
+        assert!(layout.width() >= 8);
+        assert!(layout.height() >= 3);
+        assert_eq!(layout.get_blackout_at(1, 1), Some("H"));
+        assert_eq!(layout.get_blackout_at(6, 1), None);
+        assert_eq!(layout.get_blackout_at(1, 2), Some("W"));
+        assert_eq!(layout.get_blackout_at(6, 2), Some("!"));
+
+        let test = TestStruct {
+            comments: "Hello".to_string(),
+            code: 42,
+        };
+        let config = BoxLayoutConfig {
+            blackouts: vec![(1, 1, " Two \n Lines ".to_string())],
+            ..Default::default()
+        };
+        let boxes = create_boxes(&test, Some(config));
+        assert!(boxes.contains(" Two "));
+        assert!(boxes.contains(" Lines "));
+        let test_2: TestStruct = parse_boxes(&boxes).unwrap();
+        assert_eq!(test, test_2);
+
+        let config = BoxLayoutConfig {
+            max_width: Some(10),
+            max_height: Some(3),
+            blackouts: vec![(1, 1, "A\nB\nC".to_string())],
+            ..Default::default()
+        };
+        assert_eq!(
+            layout_byte_length_checked(1, Some(config)).err(),
+            Some(LayoutError::BlackoutOutOfBounds { left: 1, top: 1 })
+        );
+    }
+
+    #[test]
+    fn test_box_styles() {
+        assert_eq!(DOUBLE_TOP_LEFT, "╔╒");
+        assert_eq!(DOUBLE_CROSS, "╬╪");
+        for connection in Connections::ALL {
+            assert_eq!(
+                connection.get_bits(BoxStyle::Weighted),
+                match connection {
+                    Connections::All => 4,
+                    Connections::RightLeftDown
+                    | Connections::RightLeftUp
+                    | Connections::RightDownUp
+                    | Connections::LeftDownUp => 3,
+                    _ => 2,
+                }
+            );
+        }
+        assert_eq!(Connections::RightDown.get_bits(BoxStyle::Rounded), 0);
+        assert_eq!(Connections::All.get_bits(BoxStyle::Rounded), 4);
+        assert_eq!(Connections::RightLeft.get_bits(BoxStyle::Double), 0);
+        assert_eq!(Connections::All.get_bits(BoxStyle::Double), 1);
+        assert_eq!(Connections::All.get_bits(BoxStyle::Diagonal), 5);
+        assert_eq!(Connections::RightLeftDown.get_bits(BoxStyle::Diagonal), 3);
+        assert_eq!(DIAGONAL_CROSS.chars().count(), 32);
+        // The diagonal graphemes mustn't be mistaken for any other style's graphemes.
+        for style in [BoxStyle::Weighted, BoxStyle::Rounded, BoxStyle::Double] {
+            for connection in Connections::ALL {
+                for c in connection.get_characters(style).chars() {
+                    assert!(!DIAGONAL_CROSS.contains(c));
+                }
+            }
+        }
+        for point in 0..32 {
+            let c = Connections::All.get_character(point, BoxStyle::Diagonal);
+            assert_eq!(parse_boxes_to_points(&c.to_string()), vec![(point, 5)]);
+        }
+        let layout = BoxLayout::new(6, 6);
+        assert!(
+            layout.calculate_bits_with_style(BoxStyle::Diagonal)
+                > layout.calculate_bits_with_style(BoxStyle::Weighted)
+        );
+
+        let test = TestStruct {
+            comments: "Hello".to_string(),
+            code: 42,
+        };
+        for style in BoxStyle::ALL {
+            let config = BoxLayoutConfig {
+                blackouts: vec![(1, 1, " C+c ".to_string())],
+                style,
+                ..Default::default()
+            };
+            let boxes = create_boxes(&test, Some(config));
+            let test_2: TestStruct = parse_boxes(&boxes).unwrap();
+            assert_eq!(test, test_2);
+        }
+        let config = BoxLayoutConfig {
+            style: BoxStyle::Rounded,
+            ..Default::default()
+        };
+        let boxes = create_boxes(&test, Some(config));
+        assert_eq!(
+            boxes,
+            "╭┱┬┬╮\n\
+             ┠╀╁╂┨\n\
+             ┡┽╇╉┧\n\
+             ┟╆┾┼┤\n\
+             ╰┴┴┴╯"
+        );
+        let config = BoxLayoutConfig {
+            style: BoxStyle::Double,
+            ..Default::default()
+        };
+        let boxes = create_boxes(&test, Some(config));
+        assert_eq!(
+            boxes,
+            "╒╦╤╦╦╦╦╗\n\
+             ╠╬╬╪╬╬╪╣\n\
+             ╞╬╪╬╬╪╪╣\n\
+             ╠╬╪╪╬╪╪╣\n\
+             ╠╬╪╪╬╪╪╣\n\
+             ╞╪╪╪╬╪╪╣\n\
+             ╚╧╩╧╩╧╩╝"
+        );
+    }
+
+    #[test]
+    fn test_delimited_boxes() {
+        let bytes = b"Framed data";
+        let plain = generate_boxes(bytes, None);
+        let delimited = generate_boxes_delimited(bytes, None).unwrap();
+        assert_eq!(extract_delimited_boxes(&delimited), Some(plain.clone()));
+        assert_eq!(
+            bytes_from_boxes_delimited(&delimited),
+            Some(bytes_from_boxes(&plain))
+        );
+
+        // A decorative border around the rulers is box art too, but falls outside their columns.
+        let width = delimited.lines().next().unwrap().chars().count();
+        let mut framed = format!("Look at this:\n╔{}╗\n", "═".repeat(width + 2));
+        for line in delimited.lines() {
+            framed.push_str(&format!("║ {} ║\n", line));
+        }
+        framed.push_str(&format!("╚{}╝\nThanks!", "═".repeat(width + 2)));
+        assert_ne!(bytes_from_boxes(&framed), bytes_from_boxes(&plain));
+        assert_eq!(
+            bytes_from_boxes_delimited(&framed),
+            Some(bytes_from_boxes(&plain))
+        );
+
+        // Without a closing ruler, the diagram may have been cut short.
+        let cut: Vec<&str> = delimited.lines().take(3).collect();
+        assert_eq!(extract_delimited_boxes(&cut.join("\n")), None);
+        assert_eq!(bytes_from_boxes_delimited(&plain), None);
+    }
+
+    #[test]
+    fn test_encode_points() {
+        let bytes = b"Points without drawing";
+        let layout = layout_byte_length(bytes.len(), None).unwrap();
+        let points = layout.encode_points(bytes);
+        // The drawn diagram goes on to pad its remaining cells.
+        let drawn = parse_boxes_to_points(&layout.display_bytes(bytes));
+        assert_eq!(points, drawn[..points.len()]);
+        assert_eq!(layout.decode_points(&points).unwrap(), bytes);
+
+        let config = BoxLayoutConfig {
+            style: BoxStyle::Double,
+            ..Default::default()
+        };
+        let double_layout = layout_byte_length(bytes.len(), Some(config)).unwrap();
+        let double = double_layout.encode_points_with_style(bytes, BoxStyle::Double);
+        assert_eq!(double_layout.decode_points(&double).unwrap(), bytes);
+
+        let mut wrong = points.clone();
+        wrong[1].1 = 7;
+        assert_eq!(
+            layout.decode_points(&wrong),
+            Err(BoxError::CellMismatch { index: 1 })
+        );
+        let mut extra = points.clone();
+        extra.resize(layout.data_connections().len() + 1, (0, 1));
+        assert!(matches!(
+            layout.decode_points(&extra),
+            Err(BoxError::Mismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_parse_boxes() {
+        let boxes = "┍╼───━┐\n\
+                           ╽ C+c ╽\n\
+                           ┝┯┰┱┭┲┪\n\
+                           ┖┻┺┸┶┵┘";
+        let test: TestStruct = parse_boxes(boxes).unwrap();
+        assert_eq!(
+            test,
+            TestStruct {
+                comments: "Hello".to_string(),
+                code: 42
+            }
+        );
+        // Text pasted around the boxes is skipped.
+        let pasted = format!("Here you go:\n{}\nThanks!", boxes);
+        assert_eq!(parse_boxes::<TestStruct>(&pasted).unwrap(), test);
+        // Losing the bottom rows loses the end of the data.
+        let cut: Vec<&str> = boxes.split('\n').take(2).collect();
+        assert!(parse_boxes::<TestStruct>(&cut.join("\n"))
+            .unwrap_err()
+            .is_truncated());
+    }
+}
+
+#[allow(dead_code)]
+fn simple_get_connections_at(
+    width: usize,
+    height: usize,
+    x: usize,
+    y: usize,
+) -> Option<Connections> {
+    let right = x < width - 1;
+    let left = x > 0;
+    let down = y < height - 1;
+    let up = y > 0;
+    match (right, left, down, up) {
+        (true, false, true, false) => Some(Connections::RightDown),
+        (false, true, true, false) => Some(Connections::LeftDown),
+        (true, false, false, true) => Some(Connections::RightUp),
+        (false, true, false, true) => Some(Connections::LeftUp),
+        (true, true, true, false) => Some(Connections::RightLeftDown),
+        (true, true, false, true) => Some(Connections::RightLeftUp),
+        (true, false, true, true) => Some(Connections::RightDownUp),
+        (false, true, true, true) => Some(Connections::LeftDownUp),
+        (true, true, true, true) => Some(Connections::All),
+        _ => None,
+    }
+}
+
+#[allow(dead_code)]
+fn simple_bytes_to_points(width: usize, height: usize, bytes: &[u8]) -> Vec<u8> {
+    let mut results = Vec::new();
+    let mut bits: u32 = 0;
+    let mut offset = 0;
+    let mut x = 0;
+    let mut y = 0;
+    for byte in bytes {
+        bits |= (*byte as u32) << offset;
+        offset += 8;
+        push_points_and_move_cursor(
+            width,
+            height,
+            &mut x,
+            &mut y,
+            &mut offset,
+            &mut results,
+            &mut bits,
+        );
+    }
+    results
+}
+
+#[allow(dead_code)]
+fn push_points_and_move_cursor(
+    width: usize,
+    height: usize,
+    x: &mut usize,
+    y: &mut usize,
+    offset: &mut usize,
+    results: &mut Vec<u8>,
+    bits: &mut u32,
+) {
+    loop {
+        if let Some(connection) = simple_get_connections_at(width, height, *x, *y) {
+            let connection_bits = connection.get_bits(BoxStyle::Weighted);
+            if *offset >= connection_bits {
+                results.push((*bits & ((1 << connection_bits) - 1)) as u8);
+                *bits >>= connection_bits;
+                *offset -= connection_bits;
+            } else {
+                break;
+            }
+        }
+        if *x < width - 1 {
+            *x += 1;
+        } else {
+            *x = 0;
+            *y += 1;
+        }
+        if *offset == 0 {
+            break;
+        }
+    }
+}
+
+#[allow(dead_code)]
+fn simple_display_bytes(width: usize, height: usize, bytes: &[u8]) -> String {
+    let mut result = String::new();
+    let points = simple_bytes_to_points(width, height, bytes);
+    let mut x = 0;
+    let mut y = 0;
+    for point in points {
+        let mut pushed_point = false;
+        while !pushed_point {
+            if let Some(connection) = simple_get_connections_at(width, height, x, y) {
+                result.push(connection.get_character(point, BoxStyle::Weighted));
+                pushed_point = true;
+            }
+            if x < width - 1 {
+                x += 1;
+            } else {
+                x = 0;
+                y += 1;
+                if y < height {
+                    result.push('\n');
+                }
+            }
+        }
+    }
+    while y < height {
+        'push_str: loop {
+            if let Some(connection) = simple_get_connections_at(width, height, x, y) {
+                result.push(connection.get_character(0, BoxStyle::Weighted));
+            } else {
+                result.push(' ');
+            }
+            if x < width - 1 {
+                x += 1;
+            } else {
+                break 'push_str;
+            }
+        }
+        x = 0;
+        y += 1;
+        if y < height {
+            result.push('\n');
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod cac_tests {
+    use super::*;
+    #[test]
+    fn draw_some_boxes() {
+        let layout = gen_layout(
+            "##\n\
+             ##",
+        );
+        println!("{}", layout.display_bytes(&[0]));
+        let layout = gen_layout(
+            "###\n\
+             #.#\n\
+             ###",
+        );
+        println!("{}", layout.display_bytes(&[0, 0]));
+        let layout = gen_layout(
+            "#####\n\
+             #####\n\
+             ###..\n\
+             ###..",
+        );
+        // Editted in article.
+        println!("{}", layout.display_bytes(&[255, 255, 255, 255]));
+        let layout = gen_layout(
+            "#####\n\
+             #####\n\
+             #####\n\
+             #####\n\
+             #####",
+        );
+        println!("{}", layout.display_bytes(&[255, 255, 255, 255]));
+        let layout = gen_layout(
+            "###\n\
+             ###\n\
+             ###",
+        );
+        println!("{}", layout.display_bytes(&[0, 0]));
+    }
+}
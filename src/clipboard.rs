@@ -1,61 +1,258 @@
-use serde::de::DeserializeOwned;
-use serde::Serialize;
-
-fn get_clipboard_text() -> Result<String, String> {
-    arboard::Clipboard::new()
-        .and_then(|mut clipboard| clipboard.get_text())
-        .map_err(|e| e.to_string())
-}
-
-fn set_clipboard_text(runes: &String) -> Option<()> {
-    arboard::Clipboard::new()
-        .and_then(|mut clipboard| clipboard.set_text(runes.clone()))
-        .ok()
-}
-
-#[cfg(feature = "futhark")]
-pub fn retrieve_from_runes<T: DeserializeOwned>() -> Result<T, String> {
-    get_clipboard_text()
-        .map(|text| {
-            let futhark = crate::parse_runes(&text, crate::FUTHARK);
-            if futhark.len() > 0 {
-                futhark
-            } else {
-                crate::parse_runes(&text, crate::ALPHA_NUM)
-            }
-        })
-        .and_then(|bytes| postcard::from_bytes(&bytes).map_err(|e| e.to_string()))
-}
-
-#[cfg(feature = "futhark")]
-pub fn store_in_runes<T: Serialize>(t: &T) -> Option<()> {
-    let runes = crate::create_runes(t, crate::FUTHARK);
-    set_clipboard_text(&runes)
-}
-
-#[cfg(feature = "cursed")]
-pub fn retrieve_cursed<T: DeserializeOwned>() -> Option<T> {
-    get_clipboard_text()
-        .ok()
-        .and_then(|text| crate::read_from_curse(&text))
-}
-
-#[cfg(feature = "cursed")]
-pub fn retrieve_cursed_bytes() -> Option<Vec<u8>> {
-    get_clipboard_text()
-        .ok()
-        .map(|text| crate::bytes_from_curse(&text))
-}
-
-#[cfg(feature = "cursed")]
-impl crate::CursedConfig {
-    pub fn store_cursed<T: Serialize>(&self, t: &T, text: &str) -> Option<()> {
-        let curse = crate::create_curse(t, &self, text);
-        set_clipboard_text(&curse)
-    }
-
-    pub fn store_cursed_bytes(&self, bytes: &[u8], text: &str) -> Option<()> {
-        let curse = self.generate_curse(text, bytes);
-        set_clipboard_text(&curse)
-    }
-}
+use serde::de::DeserializeOwned;
+#[cfg(any(feature = "futhark", feature = "boxes", feature = "cursed"))]
+use serde::Serialize;
+
+/**
+Reasons data could not be stored in or retrieved from the clipboard.
+*/
+#[derive(Debug)]
+pub enum ClipboardError {
+    /// The clipboard itself failed, with the backend's message.
+    Backend(String),
+    /// The clipboard had encoded data, but it didn't decode to the requested type.
+    Decode(Box<dyn std::error::Error + Send + Sync>),
+    /// The clipboard didn't contain any data in the expected encoding.
+    NoData,
+    /// The data couldn't be encoded to put in the clipboard.
+    Encode(Box<dyn std::error::Error + Send + Sync>),
+}
+
+impl std::fmt::Display for ClipboardError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ClipboardError::Backend(message) => write!(f, "clipboard failed: {}", message),
+            ClipboardError::Decode(err) => write!(f, "could not decode clipboard: {}", err),
+            ClipboardError::NoData => write!(f, "no encoded data found in clipboard"),
+            ClipboardError::Encode(err) => write!(f, "could not encode for clipboard: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for ClipboardError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ClipboardError::Decode(err) | ClipboardError::Encode(err) => Some(err.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl From<arboard::Error> for ClipboardError {
+    fn from(err: arboard::Error) -> Self {
+        ClipboardError::Backend(err.to_string())
+    }
+}
+
+impl ClipboardError {
+    #[cfg(any(feature = "futhark", feature = "boxes", feature = "cursed"))]
+    fn decode(err: impl std::error::Error + Send + Sync + 'static) -> Self {
+        ClipboardError::Decode(Box::new(err))
+    }
+
+    #[cfg(any(feature = "boxes", feature = "cursed"))]
+    fn encode(err: impl std::error::Error + Send + Sync + 'static) -> Self {
+        ClipboardError::Encode(Box::new(err))
+    }
+}
+
+fn get_clipboard_text() -> Result<String, ClipboardError> {
+    Ok(arboard::Clipboard::new()?.get_text()?)
+}
+
+#[cfg(any(feature = "futhark", feature = "boxes", feature = "cursed"))]
+fn set_clipboard_text(text: &str) -> Result<(), ClipboardError> {
+    Ok(arboard::Clipboard::new()?.set_text(text)?)
+}
+
+#[cfg(feature = "futhark")]
+pub fn retrieve_from_runes<T: DeserializeOwned>() -> Result<T, ClipboardError> {
+    runes_from_text(&get_clipboard_text()?)
+}
+
+#[cfg(feature = "futhark")]
+fn runes_from_text<T: DeserializeOwned>(text: &str) -> Result<T, ClipboardError> {
+    let alphabet = crate::detect_alphabet(text).ok_or(ClipboardError::NoData)?;
+    let bytes = crate::bytes_from_runes(text, alphabet);
+    postcard::from_bytes(&bytes).map_err(ClipboardError::decode)
+}
+
+#[cfg(feature = "futhark")]
+pub fn store_in_runes<T: Serialize>(t: &T) -> Result<(), ClipboardError> {
+    let runes = crate::create_runes(t, &crate::FUTHARK);
+    set_clipboard_text(&runes)
+}
+
+#[cfg(feature = "boxes")]
+pub fn retrieve_boxes<T: DeserializeOwned>() -> Result<T, ClipboardError> {
+    boxes_from_text(&get_clipboard_text()?)
+}
+
+#[cfg(feature = "boxes")]
+fn boxes_from_text<T: DeserializeOwned>(text: &str) -> Result<T, ClipboardError> {
+    // Anything around the boxes in the clipboard isn't a box-drawing grapheme, so it's skipped.
+    if crate::parse_boxes_to_points(text).is_empty() {
+        return Err(ClipboardError::NoData);
+    }
+    crate::parse_boxes(text).map_err(ClipboardError::decode)
+}
+
+#[cfg(feature = "boxes")]
+pub fn store_boxes<T: Serialize>(
+    t: &T,
+    config: Option<crate::BoxLayoutConfig>,
+) -> Result<(), ClipboardError> {
+    let boxes = crate::create_boxes_checked(t, config).map_err(ClipboardError::encode)?;
+    set_clipboard_text(&boxes)
+}
+
+#[cfg(feature = "cursed")]
+pub fn retrieve_cursed<T: DeserializeOwned>() -> Result<T, ClipboardError> {
+    cursed_from_text(&get_clipboard_text()?)
+}
+
+#[cfg(feature = "cursed")]
+fn cursed_from_text<T: DeserializeOwned>(text: &str) -> Result<T, ClipboardError> {
+    if crate::parse_curse_to_points(text).is_empty() {
+        return Err(ClipboardError::NoData);
+    }
+    crate::read_from_curse_checked(text).map_err(ClipboardError::decode)
+}
+
+#[cfg(feature = "cursed")]
+pub fn retrieve_cursed_bytes() -> Result<Vec<u8>, ClipboardError> {
+    cursed_bytes_from_text(&get_clipboard_text()?)
+}
+
+#[cfg(feature = "cursed")]
+fn cursed_bytes_from_text(text: &str) -> Result<Vec<u8>, ClipboardError> {
+    if crate::parse_curse_to_points(text).is_empty() {
+        return Err(ClipboardError::NoData);
+    }
+    crate::bytes_from_curse_with_set(text, &crate::DiacriticSet::basic())
+        .map_err(ClipboardError::decode)
+}
+
+/**
+Retrieves the raw bytes of cursed text from the clipboard, along with the carrier text they were hidden in.
+
+The carrier is the clipboard text with the diactrics stripped, so it can be checked against what was stored.
+*/
+#[cfg(feature = "cursed")]
+pub fn retrieve_cursed_full() -> Result<(String, Vec<u8>), ClipboardError> {
+    cursed_full_from_text(&get_clipboard_text()?)
+}
+
+#[cfg(feature = "cursed")]
+fn cursed_full_from_text(text: &str) -> Result<(String, Vec<u8>), ClipboardError> {
+    let bytes = cursed_bytes_from_text(text)?;
+    Ok((crate::strip_curse(text), bytes))
+}
+
+#[cfg(feature = "cursed")]
+impl crate::CursedConfig {
+    pub fn store_cursed<T: Serialize>(&self, t: &T, text: &str) -> Result<(), ClipboardError> {
+        let curse = crate::create_curse(t, self, text).map_err(ClipboardError::encode)?;
+        set_clipboard_text(&curse)
+    }
+
+    pub fn store_cursed_bytes(&self, bytes: &[u8], text: &str) -> Result<(), ClipboardError> {
+        let curse = self
+            .generate_curse(text, bytes)
+            .map_err(ClipboardError::encode)?;
+        set_clipboard_text(&curse)
+    }
+}
+
+/**
+Retrieves a value from the clipboard without knowing which encoding produced it.
+
+Each enabled encoding is tried in turn: cursed text first, since its diactrics can sit on any cover text,
+then boxes, then runes, since the alphanumeric alphabet matches nearly any text.
+The first that deserializes into T is returned; if none do, the last failure is reported.
+*/
+pub fn retrieve_any<T: DeserializeOwned>() -> Result<T, ClipboardError> {
+    any_from_text(&get_clipboard_text()?)
+}
+
+type Decoder<T> = fn(&str) -> Result<T, ClipboardError>;
+
+fn any_from_text<T: DeserializeOwned>(text: &str) -> Result<T, ClipboardError> {
+    let decoders: &[Decoder<T>] = &[
+        #[cfg(feature = "cursed")]
+        cursed_from_text,
+        #[cfg(feature = "boxes")]
+        boxes_from_text,
+        #[cfg(feature = "futhark")]
+        runes_from_text,
+    ];
+    let mut result = Err(ClipboardError::NoData);
+    for decoder in decoders {
+        match decoder(text) {
+            Ok(t) => return Ok(t),
+            // Don't let an encoding that wasn't there hide why a plausible one failed.
+            Err(ClipboardError::NoData) => {}
+            Err(err) => result = Err(err),
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod clipboard_tests {
+    use super::*;
+
+    #[cfg(feature = "futhark")]
+    #[test]
+    fn test_any_from_runes() {
+        let runes = crate::create_runes(&("runes", 7u32), &crate::FUTHARK);
+        assert_eq!(
+            any_from_text(&runes).ok(),
+            Some(("runes".to_string(), 7u32))
+        );
+    }
+
+    #[cfg(feature = "boxes")]
+    #[test]
+    fn test_any_from_boxes() {
+        let boxes = format!("Here: {}", crate::create_boxes(&("boxes", 7u32), None));
+        assert_eq!(
+            any_from_text(&boxes).ok(),
+            Some(("boxes".to_string(), 7u32))
+        );
+    }
+
+    #[cfg(feature = "cursed")]
+    #[test]
+    fn test_any_from_curse() {
+        let curse =
+            crate::create_curse(&("cursed", 7u32), &crate::CursedConfig::new(), "Cover text")
+                .unwrap();
+        assert_eq!(
+            any_from_text(&curse).ok(),
+            Some(("cursed".to_string(), 7u32))
+        );
+    }
+
+    #[cfg(feature = "cursed")]
+    #[test]
+    fn test_cursed_full() {
+        let curse = crate::CursedConfig::new()
+            .generate_curse("Cover text", b"hidden")
+            .unwrap();
+        let (carrier, bytes) = cursed_full_from_text(&curse).unwrap();
+        assert_eq!(carrier, "Cover text");
+        assert_eq!(bytes, b"hidden");
+        assert!(matches!(
+            cursed_full_from_text("Cover text"),
+            Err(ClipboardError::NoData)
+        ));
+    }
+
+    #[test]
+    fn test_any_from_nothing() {
+        assert!(matches!(
+            any_from_text::<(String, u32)>("   "),
+            Err(ClipboardError::NoData)
+        ));
+    }
+}
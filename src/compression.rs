@@ -0,0 +1,220 @@
+use serde::{de::DeserializeOwned, Serialize};
+
+/// Marks bytes which were stored as they are, since compressing them didn't make them any smaller.
+const STORED: u8 = 0;
+/// Marks bytes which were compressed with deflate.
+const DEFLATED: u8 = 1;
+
+/**
+Reasons compressed bytes could not be decompressed.
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CompressionError {
+    /// There were no bytes, so not even a marker.
+    MissingMarker,
+    /// The first byte isn't a marker that compress_bytes writes.
+    UnknownMarker(u8),
+    /// The bytes were marked as deflated, but couldn't be inflated.
+    Inflate(String),
+}
+
+impl std::fmt::Display for CompressionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CompressionError::MissingMarker => write!(f, "no compression marker"),
+            CompressionError::UnknownMarker(marker) => {
+                write!(f, "unknown compression marker: {}", marker)
+            }
+            CompressionError::Inflate(message) => write!(f, "could not inflate: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for CompressionError {}
+
+/**
+Compresses bytes with deflate, behind a one byte marker so decompress_bytes knows whether compression was applied.
+
+If deflating doesn't save any space, as with short or random data, the bytes are stored as they are instead. Either
+way, the result is at most one byte longer than the input.
+*/
+pub fn compress_bytes(bytes: &[u8]) -> Vec<u8> {
+    let deflated = miniz_oxide::deflate::compress_to_vec(bytes, 10);
+    let (marker, body) = if deflated.len() < bytes.len() {
+        (DEFLATED, deflated.as_slice())
+    } else {
+        (STORED, bytes)
+    };
+    let mut result = Vec::with_capacity(body.len() + 1);
+    result.push(marker);
+    result.extend_from_slice(body);
+    result
+}
+
+/**
+Reverses compress_bytes.
+
+Anything after the end of a deflated stream is ignored, so padding left by an encoding, like the zero bytes after box
+data, is harmless.
+*/
+pub fn decompress_bytes(bytes: &[u8]) -> Result<Vec<u8>, CompressionError> {
+    match bytes.split_first() {
+        None => Err(CompressionError::MissingMarker),
+        Some((&STORED, body)) => Ok(body.to_vec()),
+        Some((&DEFLATED, body)) => miniz_oxide::inflate::decompress_to_vec(body)
+            .map_err(|err| CompressionError::Inflate(err.to_string())),
+        Some((&marker, _)) => Err(CompressionError::UnknownMarker(marker)),
+    }
+}
+
+/**
+Serializes a value with postcard, then compresses it with compress_bytes.
+*/
+pub fn compress_value<T: Serialize>(t: &T) -> Vec<u8> {
+    compress_bytes(&postcard::to_allocvec(t).unwrap())
+}
+
+/**
+Decompresses bytes from compress_value, then deserializes them with postcard.
+*/
+pub fn decompress_value<T: DeserializeOwned>(bytes: &[u8]) -> Option<T> {
+    postcard::from_bytes(&decompress_bytes(bytes).ok()?).ok()
+}
+
+/**
+Like create_runes, but compresses the serialized value first.
+*/
+#[cfg(feature = "futhark")]
+pub fn create_runes_compressed<T: Serialize>(t: &T, alphabet: &crate::Alphabet) -> String {
+    crate::generate_runes(&compress_value(t), alphabet)
+}
+
+/**
+Reads a value written by create_runes_compressed.
+*/
+#[cfg(feature = "futhark")]
+pub fn read_from_runes_compressed<T: DeserializeOwned>(
+    runes: &str,
+    alphabet: &crate::Alphabet,
+) -> Option<T> {
+    decompress_value(&crate::bytes_from_runes(runes, alphabet))
+}
+
+/**
+Like create_boxes, but compresses the serialized value first.
+*/
+#[cfg(feature = "boxes")]
+pub fn create_boxes_compressed<T: Serialize>(
+    t: &T,
+    config: Option<crate::BoxLayoutConfig>,
+) -> String {
+    crate::generate_boxes(&compress_value(t), config)
+}
+
+/**
+Reads a value written by create_boxes_compressed.
+*/
+#[cfg(feature = "boxes")]
+pub fn parse_boxes_compressed<T: DeserializeOwned>(s: &str) -> Option<T> {
+    decompress_value(&crate::bytes_from_boxes(s))
+}
+
+/**
+Like create_curse, but compresses the serialized value first.
+*/
+#[cfg(feature = "cursed")]
+pub fn create_curse_compressed<T: Serialize>(
+    t: &T,
+    config: &crate::CursedConfig,
+    text: &str,
+) -> Result<String, crate::CurseError> {
+    config.generate_curse(text, &compress_value(t))
+}
+
+/**
+Reads a value written by create_curse_compressed, with the same config.
+*/
+#[cfg(feature = "cursed")]
+pub fn read_from_curse_compressed<T: DeserializeOwned>(
+    text: &str,
+    config: &crate::CursedConfig,
+) -> Option<T> {
+    decompress_value(&config.read_curse_bytes(text).ok()?)
+}
+
+#[cfg(test)]
+mod compression_tests {
+    use super::*;
+
+    fn repetitive_json() -> String {
+        serde_json::to_string(&vec![("name", "value", 12345); 20]).unwrap()
+    }
+
+    #[test]
+    fn test_compresses_repetitive_data() {
+        let json = repetitive_json();
+        let compressed = compress_bytes(json.as_bytes());
+        assert_eq!(compressed[0], DEFLATED);
+        assert!(compressed.len() < json.len());
+        assert_eq!(decompress_bytes(&compressed).unwrap(), json.as_bytes());
+    }
+
+    #[test]
+    fn test_stores_incompressible_data() {
+        let bytes: Vec<u8> = (0..64).map(|_| rand::random()).collect();
+        let compressed = compress_bytes(&bytes);
+        assert_eq!(compressed[0], STORED);
+        assert_eq!(compressed.len(), bytes.len() + 1);
+        assert_eq!(decompress_bytes(&compressed).unwrap(), bytes);
+    }
+
+    #[test]
+    fn test_bad_markers() {
+        assert_eq!(decompress_bytes(&[]), Err(CompressionError::MissingMarker));
+        assert_eq!(
+            decompress_bytes(&[7, 1, 2, 3]),
+            Err(CompressionError::UnknownMarker(7))
+        );
+    }
+
+    #[cfg(feature = "futhark")]
+    #[test]
+    fn test_runes_compressed() {
+        let json = repetitive_json();
+        let runes = create_runes_compressed(&json, &crate::FUTHARK);
+        assert!(runes.len() < crate::create_runes(&json, &crate::FUTHARK).len());
+        assert_eq!(
+            read_from_runes_compressed::<String>(&runes, &crate::FUTHARK),
+            Some(json)
+        );
+    }
+
+    #[cfg(feature = "boxes")]
+    #[test]
+    fn test_boxes_compressed() {
+        let json = repetitive_json();
+        let boxes = create_boxes_compressed(&json, None);
+        assert_eq!(parse_boxes_compressed::<String>(&boxes), Some(json));
+    }
+
+    #[cfg(feature = "cursed")]
+    #[test]
+    fn test_curse_compressed() {
+        let json = repetitive_json();
+        for config in [
+            crate::CursedConfig::new(),
+            crate::CursedConfig::new()
+                .with_diacritic_set(crate::DiacriticSet::extended())
+                .with_checksum(),
+            crate::CursedConfig::new()
+                .max_diactrics_per_letter(20)
+                .two_channel(true),
+        ] {
+            let curse = create_curse_compressed(&json, &config, "Compressed").unwrap();
+            assert_eq!(
+                read_from_curse_compressed::<String>(&curse, &config),
+                Some(json.clone())
+            );
+        }
+    }
+}
@@ -1,345 +1,2029 @@
-use std::default;
-
-use serde::Serialize;
-
-const BASE_DIACTRICS_START: u32 = 0x0300;
-const BASE_DIACTRICS_END: u32 = 0x036F;
-const DIACTRICS_BASE: u32 = 0x70;
-const ZWSP: char = '\u{200B}';
-const ZWNJ: char = '\u{200C}';
-const ZWJ: char = '\u{200D}';
-const MVS: char = '\u{180E}';
-
-fn is_diactric(c: char) -> bool {
-    let c = c as u32;
-    c >= BASE_DIACTRICS_START && c <= BASE_DIACTRICS_END
-}
-
-pub fn bytes_to_diactrics_points(bytes: &[u8]) -> Vec<u8> {
-    let mut results = Vec::new();
-    let mut my_u32 = 0;
-    let mut bits = 0;
-    for byte in bytes {
-        my_u32 = (my_u32 << 8) | (*byte as u32);
-        bits += 8;
-        if bits == 32 {
-            results.push((my_u32 % DIACTRICS_BASE) as u8);
-            my_u32 = my_u32 / DIACTRICS_BASE;
-            results.push((my_u32 % DIACTRICS_BASE) as u8);
-            my_u32 = my_u32 / DIACTRICS_BASE;
-            results.push((my_u32 % DIACTRICS_BASE) as u8);
-            my_u32 = my_u32 / DIACTRICS_BASE;
-            results.push((my_u32 % DIACTRICS_BASE) as u8);
-            my_u32 = my_u32 / DIACTRICS_BASE;
-            results.push((my_u32 % DIACTRICS_BASE) as u8);
-            my_u32 = 0;
-            bits = 0;
-        }
-    }
-    if bits == 24 {
-        results.push((my_u32 % DIACTRICS_BASE) as u8);
-        let my_u32 = my_u32 / DIACTRICS_BASE;
-        results.push((my_u32 % DIACTRICS_BASE) as u8);
-        let my_u32 = my_u32 / DIACTRICS_BASE;
-        results.push((my_u32 % DIACTRICS_BASE) as u8);
-        let my_u32 = my_u32 / DIACTRICS_BASE;
-        results.push((my_u32 % DIACTRICS_BASE) as u8);
-    } else if bits == 16 {
-        results.push((my_u32 % DIACTRICS_BASE) as u8);
-        let my_u32 = my_u32 / DIACTRICS_BASE;
-        results.push((my_u32 % DIACTRICS_BASE) as u8);
-        let my_u32 = my_u32 / DIACTRICS_BASE;
-        results.push((my_u32 % DIACTRICS_BASE) as u8);
-    } else if bits == 8 {
-        results.push((my_u32 % DIACTRICS_BASE) as u8);
-        let my_u32 = my_u32 / DIACTRICS_BASE;
-        results.push((my_u32 % DIACTRICS_BASE) as u8);
-    }
-    results
-}
-
-pub fn diatric_points_to_bytes(points: Vec<u8>) -> Vec<u8> {
-    let mut results = Vec::new();
-    let mut my_u32 = 0;
-    let mut significance = 0;
-    for point in points.iter() {
-        my_u32 = my_u32 + (*point as u32) * DIACTRICS_BASE.pow(significance);
-        significance += 1;
-        if significance == 5 {
-            results.push((my_u32 >> 24 & 0xff) as u8);
-            results.push((my_u32 >> 16 & 0xff) as u8);
-            results.push((my_u32 >> 8 & 0xff) as u8);
-            results.push((my_u32 & 0xff) as u8);
-            my_u32 = 0;
-            significance = 0;
-        }
-    }
-    if significance == 4 {
-        results.push((my_u32 >> 16 & 0xff) as u8);
-        results.push((my_u32 >> 8 & 0xff) as u8);
-        results.push((my_u32 & 0xff) as u8);
-    } else if significance == 3 {
-        results.push((my_u32 >> 8 & 0xff) as u8);
-        results.push((my_u32 & 0xff) as u8);
-    } else if significance == 2 {
-        results.push((my_u32 & 0xff) as u8);
-    } else if significance == 1 {
-        panic!("Invalid number of diactrics: {} mod 5 == 1", points.len());
-    }
-    results
-}
-
-pub struct CursedConfig {
-    diatrics_break: Option<String>,
-    max_diactrics_per_letter: Option<usize>,
-    max_diatrics: Option<usize>,
-}
-
-impl Default for CursedConfig {
-    fn default() -> Self {
-        Self {
-            diatrics_break: None,
-            max_diactrics_per_letter: None,
-            max_diatrics: None,
-        }
-    }
-}
-
-impl CursedConfig {
-    pub fn new() -> Self {
-        Self::default()
-    }
-
-    pub fn discord() -> Self {
-        Self::new()
-            .max_diactrics_per_letter(3)
-            .with_zwj_break()
-            .max_diactrics(20)
-    }
-
-    pub fn with_zwsp_break(mut self) -> Self {
-        self.diatrics_break = Some(ZWSP.to_string());
-        self
-    }
-
-    pub fn with_zwnj_break(mut self) -> Self {
-        self.diatrics_break = Some(ZWNJ.to_string());
-        self
-    }
-
-    pub fn with_zwj_break(mut self) -> Self {
-        self.diatrics_break = Some(ZWJ.to_string());
-        self
-    }
-
-    pub fn with_mvs_break(mut self) -> Self {
-        self.diatrics_break = Some(MVS.to_string());
-        self
-    }
-
-    pub fn with_no_break(mut self) -> Self {
-        self.diatrics_break = None;
-        self
-    }
-
-    pub fn max_diactrics_per_letter(mut self, max_diactrics_per_letter: usize) -> Self {
-        self.max_diactrics_per_letter = Some(max_diactrics_per_letter);
-        self
-    }
-
-    pub fn max_diactrics(mut self, max_diactrics: usize) -> Self {
-        self.max_diatrics = Some(max_diactrics);
-        self
-    }
-
-    pub fn with_no_max_diactrics(mut self) -> Self {
-        self.max_diatrics = None;
-        self
-    }
-
-    pub fn can_curse(&self, text_length: usize, data_length: usize) -> bool {
-        let diatrics_for_data = (data_length / 4) * 5;
-        if !self
-            .max_diatrics
-            .map_or(true, |max| diatrics_for_data <= max)
-        {
-            false
-        } else if self.diatrics_break.is_some() {
-            true
-        } else if let Some(max_diactrics_per_letter) = self.max_diactrics_per_letter {
-            text_length > usize::div_ceil(diatrics_for_data, max_diactrics_per_letter)
-        } else {
-            true
-        }
-    }
-
-    pub fn generate_curse(&self, text: &str, data: &[u8]) -> String {
-        if !self.can_curse(text.len(), data.len()) {
-            panic!("Cannot curse text with given data");
-        }
-        let points = bytes_to_diactrics_points(data);
-        let mut cursed_text = String::new();
-        let mut point_index = 0;
-        let mut characters_left = text.chars().count();
-        for c in text.chars() {
-            cursed_text.push(c);
-            let points_left = points.len() - point_index;
-            let diatrics_per_letter = usize::div_ceil(points_left, characters_left);
-            for dia_idx in 0..diatrics_per_letter {
-                if point_index < points.len() {
-                    cursed_text.push(
-                        std::char::from_u32(BASE_DIACTRICS_START + points[point_index] as u32)
-                            .unwrap(),
-                    );
-                    point_index += 1;
-                }
-                if let Some(max_diactrics_per_letter) = self.max_diactrics_per_letter {
-                    if (dia_idx + 1) % max_diactrics_per_letter == 0 {
-                        if let Some(diatrics_break) = &self.diatrics_break {
-                            cursed_text.push_str(diatrics_break);
-                        } else {
-                            panic!("Too many diactrics for no break");
-                        }
-                    }
-                }
-            }
-            characters_left -= 1;
-        }
-        cursed_text
-    }
-}
-
-pub fn create_curse<T: Serialize>(t: &T, config: &CursedConfig, text: &str) -> String {
-    let data = postcard::to_allocvec(t).unwrap();
-    config.generate_curse(text, data.as_slice())
-}
-
-pub fn parse_curse_to_points(text: &str) -> Vec<u8> {
-    let mut points = Vec::new();
-    for c in text.chars() {
-        if is_diactric(c) {
-            let point = c as u32 - BASE_DIACTRICS_START;
-            points.push(point as u8);
-        }
-    }
-    points
-}
-
-pub fn bytes_from_curse(text: &str) -> Vec<u8> {
-    let points = parse_curse_to_points(text);
-    diatric_points_to_bytes(points)
-}
-
-pub fn read_from_curse<T: serde::de::DeserializeOwned>(text: &str) -> Option<T> {
-    let points = parse_curse_to_points(text);
-    let bytes = diatric_points_to_bytes(points);
-    postcard::from_bytes(&bytes).ok()
-}
-
-#[cfg(test)]
-mod cursed_tests {
-    use rand::random;
-
-    use super::*;
-
-    #[test]
-    fn test_is_diatric() {
-        assert!(is_diactric('̀'));
-        assert!(is_diactric('ͅ'));
-        assert!(!is_diactric('a'));
-    }
-
-    #[test]
-    fn copy_and_display_test() {
-        let s = "T\u{0300}\u{0300}\u{0300}\u{200D}\u{0301}\u{0301}\u{0301}\u{200D}\u{0302}\u{0302}\u{0302}\u{200D}\u{0300}\u{0300}\u{0300}e\u{0300}s\u{200D}t";
-        println!("{}", s);
-        // From terminal
-        let s_2 = "T̀̀̀‍́́́‍̂̂̂‍̀̀̀ès‍t";
-        assert_eq!(s, s_2);
-        // From Discord
-        let s_3 = "T̀̀̀‍́́́‍̂̂̂‍̀̀̀ès‍t";
-        assert_eq!(s, s_3);
-    }
-
-    #[test]
-    fn test_bytes_to_diactrics_points() {
-        let points = bytes_to_diactrics_points(&[0, 0, 0, 0]);
-        assert_eq!(points, vec![0, 0, 0, 0, 0]);
-        let points = bytes_to_diactrics_points(&[0, 0, 0, 0, 0]);
-        assert_eq!(points, vec![0, 0, 0, 0, 0, 0, 0]);
-    }
-
-    #[test]
-    fn test_diatric_points_to_bytes() {
-        let bytes = diatric_points_to_bytes(vec![0, 0, 0, 0, 0]);
-        assert_eq!(bytes, vec![0, 0, 0, 0]);
-        let bytes = diatric_points_to_bytes(vec![0, 0, 0, 0, 0, 0, 0]);
-        assert_eq!(bytes, vec![0, 0, 0, 0, 0, 0]);
-    }
-
-    #[test]
-    fn test_bytes_to_bytes() {
-        let bytes = [166];
-        let points = bytes_to_diactrics_points(&bytes);
-        let bytes_2 = diatric_points_to_bytes(points);
-        assert_eq!(bytes.to_vec(), bytes_2);
-
-        let bytes = [98, 205, 238];
-        let points = bytes_to_diactrics_points(&bytes);
-        let bytes_2 = diatric_points_to_bytes(points);
-        assert_eq!(bytes.to_vec(), bytes_2);
-
-        let bytes = [62, 10, 105, 133, 98, 205, 238];
-        let points = bytes_to_diactrics_points(&bytes);
-        let bytes_2 = diatric_points_to_bytes(points);
-        assert_eq!(bytes.to_vec(), bytes_2);
-
-        let bytes = [
-            62, 10, 105, 133, 98, 205, 238, 62, 10, 105, 133, 98, 205, 238, 123, 23,
-        ];
-        let points = bytes_to_diactrics_points(&bytes);
-        let bytes_2 = diatric_points_to_bytes(points);
-        assert_eq!(bytes.to_vec(), bytes_2);
-    }
-
-    #[test]
-    fn discord_cursed() {
-        let curse_config = CursedConfig::discord();
-        let text = "Curse";
-        let bytes = (0..16).collect::<Vec<_>>();
-        let curse = curse_config.generate_curse(text, &bytes);
-        let points = parse_curse_to_points(&curse);
-        let bytes_2 = diatric_points_to_bytes(points);
-        assert_eq!(bytes, bytes_2);
-        // Copied to Discord, then copy+pasted back.
-        let pasted = "C͓̝̅‍̀ù͗̍‍̀r̰̀͛‍ͭsͪ̀͟‍͟e̟ͥ͝‍́";
-        assert_eq!(curse, pasted);
-    }
-
-    #[test]
-    fn overly_cursed() {
-        let curse_config = CursedConfig::new();
-        let text = "Comments & code";
-        let bytes = (0..100).collect::<Vec<_>>();
-        let curse = curse_config.generate_curse(text, &bytes);
-        let points = parse_curse_to_points(&curse);
-        let bytes_2 = diatric_points_to_bytes(points);
-        assert_eq!(bytes, bytes_2);
-    }
-
-    #[test]
-    fn stress_test() {
-        let curse_config = CursedConfig::new();
-        for _ in 0..1000 {
-            let bytes: Vec<u8> = (0..random::<u8>()).map(|_| random::<u8>()).collect();
-            let points = bytes_to_diactrics_points(&bytes);
-            let bytes_2 = diatric_points_to_bytes(points);
-            assert_eq!(bytes, bytes_2);
-            let text = "Comments & code";
-            let curse = curse_config.generate_curse(text, &bytes);
-            let points_2 = parse_curse_to_points(&curse);
-            let bytes_3 = diatric_points_to_bytes(points_2);
-            assert_eq!(bytes, bytes_3);
-        }
-    }
-}
+use alloc::borrow::Cow;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+
+use serde::Serialize;
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::{crc16, decode_postcard, push_length_bytes, take_length_bytes, DecodeError};
+
+const BASE_DIACTRICS_START: u32 = 0x0300;
+const BASE_DIACTRICS_END: u32 = 0x036F;
+const ZWSP: char = '\u{200B}';
+const ZWNJ: char = '\u{200C}';
+const ZWJ: char = '\u{200D}';
+const MVS: char = '\u{180E}';
+
+// The combining blocks of the extended set, which add up to exactly 256 diactrics, or one per byte.
+const EXTENDED_DIACTRICS: [(u32, u32); 5] = [
+    (BASE_DIACTRICS_START, BASE_DIACTRICS_END),
+    (0x1AB0, 0x1ACE),
+    (0x1DC0, 0x1DFF),
+    (0x20D0, 0x20F0),
+    (0xFE20, 0xFE2F),
+];
+
+// The diactrics of the basic block which are drawn above the letter, with a canonical combining class of 230, leaving
+// out those that normalization replaces with another diactric, and those NFC composes with a letter, like the grave
+// accent in "à".
+const NORMALIZATION_SAFE_DIACTRICS: [(u32, u32); 11] = [
+    (0x0305, 0x0305),
+    (0x030D, 0x030E),
+    (0x0310, 0x0310),
+    (0x0312, 0x0312),
+    (0x033D, 0x033F),
+    (0x0346, 0x0346),
+    (0x034A, 0x034C),
+    (0x0350, 0x0352),
+    (0x0357, 0x0357),
+    (0x035B, 0x035B),
+    (0x0363, 0x036F),
+];
+
+// The diactrics of the basic block which are drawn below the letter, with a canonical combining class of 220, leaving
+// out those NFC composes with a letter, like the dot below in "ạ". The last safe one, U+035A, is also left out, so
+// every point has a twin in the normalization safe set.
+const BELOW_DIACTRICS: [(u32, u32); 10] = [
+    (0x0316, 0x0319),
+    (0x031C, 0x0320),
+    (0x0329, 0x032C),
+    (0x032F, 0x032F),
+    (0x0332, 0x0333),
+    (0x0339, 0x033C),
+    (0x0347, 0x0349),
+    (0x034D, 0x034E),
+    (0x0353, 0x0356),
+    (0x0359, 0x0359),
+];
+
+/**
+The combining characters used to carry data, as a list of inclusive ranges. A point is the index of its diactric
+across all of the ranges, so the number of diactrics is the base the data is written in.
+*/
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DiacriticSet {
+    ranges: Vec<(u32, u32)>,
+}
+
+impl Default for DiacriticSet {
+    fn default() -> Self {
+        Self::basic()
+    }
+}
+
+impl DiacriticSet {
+    /**
+    Creates a set from inclusive ranges of combining characters. Returns None if the set has fewer than 2 or more than
+    256 characters, or if the ranges overlap.
+    */
+    pub fn new(ranges: &[(char, char)]) -> Option<Self> {
+        let ranges: Vec<(u32, u32)> = ranges
+            .iter()
+            .map(|(start, end)| (*start as u32, *end as u32))
+            .collect();
+        let mut sorted = ranges.clone();
+        sorted.sort();
+        if sorted.iter().any(|(start, end)| start > end)
+            || sorted.windows(2).any(|pair| pair[0].1 >= pair[1].0)
+        {
+            return None;
+        }
+        let set = Self { ranges };
+        (2..=256).contains(&set.base()).then_some(set)
+    }
+
+    /**
+    The Combining Diacritical Marks block, 112 diactrics. Every 4 bytes take 5 diactrics.
+    */
+    pub fn basic() -> Self {
+        Self {
+            ranges: vec![(BASE_DIACTRICS_START, BASE_DIACTRICS_END)],
+        }
+    }
+
+    /**
+    The Combining Diacritical Marks block, plus its Extended and Supplement blocks and the combining marks for symbols
+    and half marks. This is 256 diactrics, so every byte takes a single diactric.
+    */
+    pub fn extended() -> Self {
+        Self {
+            ranges: EXTENDED_DIACTRICS.to_vec(),
+        }
+    }
+
+    /**
+    The 30 diactrics of the basic block which share a canonical combining class, and which normalization neither
+    replaces nor composes into the letter before them. Unicode normalization sorts runs of diactrics by their combining
+    class, which scrambles the order of the points when the classes differ, but can't reorder these. This costs
+    density: every 4 bytes take 7 diactrics, and every 3 take 5.
+    */
+    pub fn normalization_safe() -> Self {
+        Self {
+            ranges: NORMALIZATION_SAFE_DIACTRICS.to_vec(),
+        }
+    }
+
+    /**
+    The built-in sets, indexed by the id a self-describing header records for them.
+    */
+    fn described() -> [DiacriticSet; 3] {
+        [
+            DiacriticSet::basic(),
+            DiacriticSet::extended(),
+            DiacriticSet::normalization_safe(),
+        ]
+    }
+
+    /**
+    The 30 diactrics of the basic block which are drawn below the letter and share a canonical combining class. This
+    is the set CursedConfig::two_channel uses for its points, drawing half of them as the same point above the letter.
+    */
+    pub fn below() -> Self {
+        Self {
+            ranges: BELOW_DIACTRICS.to_vec(),
+        }
+    }
+
+    pub fn base(&self) -> u32 {
+        self.ranges.iter().map(|(start, end)| end - start + 1).sum()
+    }
+
+    pub fn contains(&self, c: char) -> bool {
+        self.point_from_char(c).is_some()
+    }
+
+    pub fn point_from_char(&self, c: char) -> Option<u8> {
+        let c = c as u32;
+        let mut offset = 0;
+        for (start, end) in self.ranges.iter() {
+            if (*start..=*end).contains(&c) {
+                return Some((offset + c - start) as u8);
+            }
+            offset += end - start + 1;
+        }
+        None
+    }
+
+    pub fn char_from_point(&self, point: u8) -> char {
+        let mut point = point as u32;
+        for (start, end) in self.ranges.iter() {
+            if point <= end - start {
+                return core::char::from_u32(start + point).unwrap();
+            }
+            point -= end - start + 1;
+        }
+        panic!("Point {} is outside of the diactric set", point);
+    }
+
+    /**
+    The number of points needed for a group of up to 4 bytes.
+    */
+    fn points_for_group(&self, group_length: usize) -> usize {
+        let capacity = 1u64 << (8 * group_length);
+        let mut points = 0;
+        let mut values = 1u64;
+        while values < capacity {
+            values *= self.base() as u64;
+            points += 1;
+        }
+        points
+    }
+
+    /**
+    The number of points bytes_to_points produces for the given number of bytes.
+    */
+    pub fn points_for_bytes(&self, data_length: usize) -> usize {
+        (data_length / 4) * self.points_for_group(4) + self.points_for_group(data_length % 4)
+    }
+
+    /**
+    Whether some number of bytes converts to exactly this many points, so points_to_bytes can read them.
+
+    Every full group of 4 bytes takes the same number of points, so this only depends on what's left over after the
+    full groups. A last group of fewer than 4 bytes still takes at least one point per byte, so with fewer than 256
+    diactrics, no number of bytes leaves a single trailing point; for the basic set, a count is valid unless it's 1
+    more than a multiple of 5. Every count is valid for a set of 256, which takes one point per byte.
+    */
+    pub fn is_valid_point_count(&self, count: usize) -> bool {
+        let remainder = count % self.points_for_group(4);
+        (0..4).any(|length| self.points_for_group(length) == remainder)
+    }
+
+    /**
+    Converts bytes to points, in groups of 4 bytes. Each group is read as a big-endian number, then written out in the
+    set's base, least significant point first.
+    */
+    pub fn bytes_to_points(&self, bytes: &[u8]) -> Vec<u8> {
+        let base = self.base() as u64;
+        let mut results = Vec::new();
+        for group in bytes.chunks(4) {
+            let mut value = group
+                .iter()
+                .fold(0u64, |value, byte| (value << 8) | *byte as u64);
+            for _ in 0..self.points_for_group(group.len()) {
+                results.push((value % base) as u8);
+                value /= base;
+            }
+        }
+        results
+    }
+
+    /**
+    Converts points back to bytes, returning an error for truncated or corrupted points rather than panicking. Only
+    counts accepted by is_valid_point_count can be read; any other count is an InvalidPointCount.
+    */
+    pub fn points_to_bytes(&self, points: &[u8]) -> Result<Vec<u8>, CurseError> {
+        if !self.is_valid_point_count(points.len()) {
+            return Err(CurseError::InvalidPointCount {
+                count: points.len(),
+            });
+        }
+        let base = self.base() as u64;
+        let mut results = Vec::new();
+        for group in points.chunks(self.points_for_group(4)) {
+            let group_length = (1..=4)
+                .find(|length| self.points_for_group(*length) == group.len())
+                .ok_or(CurseError::InvalidPointCount {
+                    count: points.len(),
+                })?;
+            let value = group
+                .iter()
+                .rev()
+                .fold(0u64, |value, point| value * base + *point as u64);
+            if value >> (8 * group_length) != 0 {
+                return Err(CurseError::PointsOutOfRange);
+            }
+            for i in (0..group_length).rev() {
+                results.push((value >> (8 * i) & 0xff) as u8);
+            }
+        }
+        Ok(results)
+    }
+}
+
+/**
+The number of points bytes_to_diactrics_points produces for the given number of bytes.
+*/
+pub fn diactrics_for_bytes(data_length: usize) -> usize {
+    DiacriticSet::basic().points_for_bytes(data_length)
+}
+
+/**
+Whether bytes_to_diactrics_points could have produced this many points. Any count which is 1 more than a multiple of
+5 can't be read back, since a lone trailing point is never written.
+*/
+pub fn is_valid_point_count(count: usize) -> bool {
+    DiacriticSet::basic().is_valid_point_count(count)
+}
+
+pub fn bytes_to_diactrics_points(bytes: &[u8]) -> Vec<u8> {
+    DiacriticSet::basic().bytes_to_points(bytes)
+}
+
+/**
+Reasons cursed text could not be read back.
+*/
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CurseError {
+    /// The diactrics don't divide into groups of a length that some number of bytes would produce.
+    InvalidPointCount { count: usize },
+    /// A group of points adds up to more than its bytes can hold.
+    PointsOutOfRange,
+    /// The data needs more diactrics than the config's max_diactrics.
+    TooManyDiacritics { needed: usize, available: usize },
+    /// The text has too few letters to hold the data within the config's max_diactrics_per_letter.
+    InsufficientCarrierText { needed: usize, available: usize },
+    /// Some letters would need more than break_every diactrics, but the config has no break.
+    BreakRequired,
+    /// The diactrics were read, but the bytes didn't deserialize to the requested type.
+    Decode(DecodeError),
+    /// The break contains a diactric, which would be read back as data.
+    BreakContainsDiacritic { diacritic: char },
+    /// The curse should have a checksum, but its length prefix or checksum is missing.
+    MissingChecksum,
+    /// The checksum did not match the payload, so diactrics were changed, reordered, or dropped along the way.
+    ChecksumMismatch { expected: u16, found: u16 },
+    /// The config's diactric set isn't one of the built-in sets, so a header can't name it.
+    UnknownDiacriticSet,
+    /// The text doesn't start with a self-describing header.
+    MissingHeader,
+}
+
+impl core::fmt::Display for CurseError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            CurseError::InvalidPointCount { count } => {
+                write!(f, "invalid number of diactrics: {}", count)
+            }
+            CurseError::PointsOutOfRange => write!(f, "diactrics do not encode valid bytes"),
+            CurseError::TooManyDiacritics { needed, available } => write!(
+                f,
+                "data needs {} diactrics, but only {} are allowed",
+                needed, available
+            ),
+            CurseError::InsufficientCarrierText { needed, available } => write!(
+                f,
+                "data needs {} diactrics, but the text can only carry {}",
+                needed, available
+            ),
+            CurseError::BreakRequired => write!(f, "data needs a break between diactrics"),
+            CurseError::Decode(err) => write!(f, "could not deserialize curse: {}", err),
+            CurseError::BreakContainsDiacritic { diacritic } => {
+                write!(f, "break contains the diactric U+{:04X}", *diacritic as u32)
+            }
+            CurseError::MissingChecksum => write!(f, "curse is missing its length or checksum"),
+            CurseError::ChecksumMismatch { expected, found } => write!(
+                f,
+                "checksum mismatch: expected {:#06x}, found {:#06x}",
+                expected, found
+            ),
+            CurseError::UnknownDiacriticSet => {
+                write!(f, "diactric set has no id for a self-describing header")
+            }
+            CurseError::MissingHeader => write!(f, "curse has no self-describing header"),
+        }
+    }
+}
+
+impl core::error::Error for CurseError {}
+
+/**
+Converts points back to bytes. This panics on points that bytes_to_diactrics_points could not have produced, so it
+should only be used on trusted input; see diatric_points_to_bytes_checked otherwise.
+*/
+pub fn diatric_points_to_bytes(points: Vec<u8>) -> Vec<u8> {
+    match diatric_points_to_bytes_checked(points) {
+        Ok(bytes) => bytes,
+        Err(err) => panic!("{}", err),
+    }
+}
+
+/**
+Converts points back to bytes, returning an error for truncated or corrupted points rather than panicking.
+*/
+pub fn diatric_points_to_bytes_checked(points: Vec<u8>) -> Result<Vec<u8>, CurseError> {
+    DiacriticSet::basic().points_to_bytes(&points)
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct CursedConfig {
+    diatrics_break: Option<String>,
+    max_diactrics_per_letter: Option<usize>,
+    break_every: Option<usize>,
+    max_diatrics: Option<usize>,
+    diacritic_set: DiacriticSet,
+    even_distribution: bool,
+    checksum: bool,
+    two_channel: bool,
+}
+
+impl CursedConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn discord() -> Self {
+        Self::new()
+            .break_every(3)
+            .with_zwj_break()
+            .max_diactrics(20)
+    }
+
+    pub fn with_zwsp_break(mut self) -> Self {
+        self.diatrics_break = Some(ZWSP.to_string());
+        self
+    }
+
+    pub fn with_zwnj_break(mut self) -> Self {
+        self.diatrics_break = Some(ZWNJ.to_string());
+        self
+    }
+
+    pub fn with_zwj_break(mut self) -> Self {
+        self.diatrics_break = Some(ZWJ.to_string());
+        self
+    }
+
+    pub fn with_mvs_break(mut self) -> Self {
+        self.diatrics_break = Some(MVS.to_string());
+        self
+    }
+
+    /**
+    Uses any string as the break, such as a visible separator for debugging. The break must not contain any of the
+    diactrics used for data, or generate_curse will refuse it.
+    */
+    pub fn with_custom_break(mut self, diatrics_break: impl Into<String>) -> Self {
+        self.diatrics_break = Some(diatrics_break.into());
+        self
+    }
+
+    pub fn with_no_break(mut self) -> Self {
+        self.diatrics_break = None;
+        self
+    }
+
+    /**
+    Limits how many diactrics go on each letter of the text. This is a hard cap, whether or not there's a break.
+    */
+    pub fn max_diactrics_per_letter(mut self, max_diactrics_per_letter: usize) -> Self {
+        self.max_diactrics_per_letter = Some(max_diactrics_per_letter);
+        self
+    }
+
+    /**
+    Inserts the break after every n diactrics on a letter, so renderers which limit stacking start a new stack. This
+    is independent of max_diactrics_per_letter. Without a break, any letter needing more than n diactrics is an error.
+    */
+    pub fn break_every(mut self, n: usize) -> Self {
+        self.break_every = Some(n);
+        self
+    }
+
+    pub fn max_diactrics(mut self, max_diactrics: usize) -> Self {
+        self.max_diatrics = Some(max_diactrics);
+        self
+    }
+
+    pub fn with_no_max_diactrics(mut self) -> Self {
+        self.max_diatrics = None;
+        self
+    }
+
+    /**
+    Uses a different set of diactrics. The curse must then be read back with the same set.
+    */
+    pub fn with_diacritic_set(mut self, diacritic_set: DiacriticSet) -> Self {
+        self.diacritic_set = diacritic_set;
+        self.two_channel = false;
+        self
+    }
+
+    /**
+    Writes the curse with DiacriticSet::normalization_safe, so a platform which normalizes the text can't reorder the
+    diactrics. The curse takes about two fifths more diactrics, and must be read back with the same set. Turning this off
+    goes back to the basic set.
+    */
+    pub fn normalization_safe(self, normalization_safe: bool) -> Self {
+        self.with_diacritic_set(if normalization_safe {
+            DiacriticSet::normalization_safe()
+        } else {
+            DiacriticSet::basic()
+        })
+    }
+
+    /**
+    Draws the diactrics in two channels, alternating between marks above the letter and marks below it, so each stack
+    is half as tall. The points are written with DiacriticSet::below, whose 30 marks each have a twin above the letter
+    from DiacriticSet::normalization_safe, so every 4 bytes take 7 diactrics.
+
+    Each channel keeps its own order under normalization, and reading the curse takes the channels apart and
+    interleaves them again, so the curse also survives platforms which reorder diactrics. max_diactrics_per_letter
+    limits each channel rather than the whole letter, so a letter can take twice as many diactrics, while break_every
+    still counts the diactrics of both channels together.
+
+    This replaces the diactric set, and choosing another set turns it off again. Turning it off goes back to the
+    basic set.
+    */
+    pub fn two_channel(mut self, two_channel: bool) -> Self {
+        self.diacritic_set = if two_channel {
+            DiacriticSet::below()
+        } else {
+            DiacriticSet::basic()
+        };
+        self.two_channel = two_channel;
+        self
+    }
+
+    /**
+    The most diactrics each letter can take, counting both channels when there are two.
+    */
+    fn letter_limit(&self) -> Option<usize> {
+        self.max_diactrics_per_letter
+            .map(|max_diactrics_per_letter| {
+                max_diactrics_per_letter * if self.two_channel { 2 } else { 1 }
+            })
+    }
+
+    /**
+    The set of diactrics the curse is written with, and so must be read back with.
+    */
+    pub fn diacritic_set(&self) -> &DiacriticSet {
+        &self.diacritic_set
+    }
+
+    /**
+    The break inserted between stacks of diactrics, if any.
+    */
+    pub fn break_char(&self) -> Option<&str> {
+        self.diatrics_break.as_deref()
+    }
+
+    /**
+    The most diactrics any one letter takes, if limited.
+    */
+    pub fn max_per_letter(&self) -> Option<usize> {
+        self.max_diactrics_per_letter
+    }
+
+    /**
+    The most diactrics the whole curse takes, if limited.
+    */
+    pub fn max_total(&self) -> Option<usize> {
+        self.max_diatrics
+    }
+
+    /**
+    Spreads the diactrics as evenly as possible across the whole text. Otherwise, letters near the start of the text
+    take any extra diactrics.
+    */
+    pub fn even_distribution(mut self, even_distribution: bool) -> Self {
+        self.even_distribution = even_distribution;
+        self
+    }
+
+    /**
+    Follows the data with a CRC-16 checksum, framed by the data's length, so a curse mangled by a client which
+    normalizes or drops diactrics is reported rather than read as data. The curse must then be read with read_curse,
+    since nothing in the curse itself says whether it has a checksum.
+    */
+    pub fn with_checksum(mut self) -> Self {
+        self.checksum = true;
+        self
+    }
+
+    /**
+    The bytes which are actually cursed for the data, with the length and checksum if the config asks for them.
+    */
+    fn frame<'a>(&self, data: &'a [u8]) -> Cow<'a, [u8]> {
+        if !self.checksum {
+            return Cow::Borrowed(data);
+        }
+        let mut framed = Vec::with_capacity(data.len() + 4);
+        push_length_bytes(data.len(), &mut framed);
+        framed.extend_from_slice(data);
+        framed.extend_from_slice(&crc16(data).to_be_bytes());
+        Cow::Owned(framed)
+    }
+
+    /**
+    The number of points cursed for data of the given length, including any framing.
+    */
+    fn points_for_data(&self, data_length: usize) -> usize {
+        let framed_length = if self.checksum {
+            let mut prefix = Vec::new();
+            push_length_bytes(data_length, &mut prefix);
+            prefix.len() + data_length + 2
+        } else {
+            data_length
+        };
+        self.diacritic_set.points_for_bytes(framed_length)
+    }
+
+    /**
+    Checks whether data of the given length can be cursed onto text with the given number of graphemes.
+    */
+    pub fn can_curse(&self, text_length: usize, data_length: usize) -> bool {
+        self.check_capacity(text_length, self.points_for_data(data_length))
+            .is_ok()
+    }
+
+    /**
+    The fewest graphemes of cover text that can hold data of the given length, for padding out a cover text which
+    can_curse rejects. Without a limit per letter, and with a break when break_every is set, one grapheme is enough.
+
+    A cover text can't make up for max_diactrics, so can_curse may still fail if the data needs more than that.
+    */
+    pub fn min_carrier_len(&self, data_len: usize) -> usize {
+        let needed = self.points_for_data(data_len);
+        if needed == 0 {
+            return 0;
+        }
+        let per_letter = self.letter_limit().map_or(1, |max_diactrics_per_letter| {
+            needed.div_ceil(max_diactrics_per_letter)
+        });
+        let per_break = match (self.break_every, &self.diatrics_break) {
+            (Some(break_every), None) => needed.div_ceil(break_every),
+            _ => 1,
+        };
+        per_letter.max(per_break)
+    }
+
+    fn check_capacity(&self, text_length: usize, needed: usize) -> Result<(), CurseError> {
+        if let Some(max_diatrics) = self.max_diatrics {
+            if needed > max_diatrics {
+                return Err(CurseError::TooManyDiacritics {
+                    needed,
+                    available: max_diatrics,
+                });
+            }
+        }
+        // Letters take the diactrics as evenly as possible, so each letter's limit applies to the whole text.
+        let available = match self.letter_limit() {
+            Some(max_diactrics_per_letter) => text_length * max_diactrics_per_letter,
+            None if text_length == 0 => 0,
+            None => usize::MAX,
+        };
+        if needed > available {
+            return Err(CurseError::InsufficientCarrierText { needed, available });
+        }
+        if let (Some(break_every), None) = (self.break_every, &self.diatrics_break) {
+            if needed > text_length * break_every {
+                return Err(CurseError::BreakRequired);
+            }
+        }
+        Ok(())
+    }
+
+    /**
+    The most diactrics text with the given number of graphemes can take on its own, within every limit of the config.
+    */
+    fn capacity(&self, text_length: usize) -> usize {
+        let mut capacity = if text_length == 0 { 0 } else { usize::MAX };
+        if let Some(max_diatrics) = self.max_diatrics {
+            capacity = capacity.min(max_diatrics);
+        }
+        if let Some(max_diactrics_per_letter) = self.letter_limit() {
+            capacity = capacity.min(text_length.saturating_mul(max_diactrics_per_letter));
+        }
+        if let (Some(break_every), None) = (self.break_every, &self.diatrics_break) {
+            capacity = capacity.min(text_length.saturating_mul(break_every));
+        }
+        capacity
+    }
+
+    /**
+    Curses the text with the data, for callers who have already checked can_curse. Panics if the data doesn't fit.
+    */
+    pub fn generate_curse_unchecked(&self, text: &str, data: &[u8]) -> String {
+        match self.generate_curse(text, data) {
+            Ok(cursed_text) => cursed_text,
+            Err(err) => panic!("Cannot curse text with given data: {}", err),
+        }
+    }
+
+    /**
+    Curses the text with the data, or explains why the data doesn't fit within the config's limits.
+    */
+    pub fn generate_curse(&self, text: &str, data: &[u8]) -> Result<String, CurseError> {
+        points_to_curse(
+            text,
+            &self.diacritic_set.bytes_to_points(&self.frame(data)),
+            self,
+        )
+    }
+
+    /**
+    How many diactrics generate_curse would put on each grapheme of a text with text_len graphemes, for data of the
+    given length, without generating the curse. This is meant for previews which highlight heavily stacked letters.
+
+    The plan is only meaningful if the data fits; see can_curse.
+    */
+    pub fn diacritic_plan(&self, text_len: usize, data_len: usize) -> Vec<usize> {
+        self.diactrics_per_letter(text_len, self.points_for_data(data_len))
+    }
+
+    /**
+    How many diactrics each letter of the text takes, in order.
+    */
+    fn diactrics_per_letter(&self, characters: usize, points: usize) -> Vec<usize> {
+        let mut points_left = points;
+        (0..characters)
+            .map(|i| {
+                let share = self.letter_share(i, characters, points, points_left);
+                points_left -= share;
+                share
+            })
+            .collect()
+    }
+
+    /**
+    How many diactrics the letter at the index takes, given how many points the letters before it left.
+    */
+    fn letter_share(
+        &self,
+        index: usize,
+        characters: usize,
+        points: usize,
+        points_left: usize,
+    ) -> usize {
+        if self.even_distribution {
+            // Each letter takes its share of the points, rounded so the extras land evenly.
+            (index + 1) * points / characters - index * points / characters
+        } else {
+            usize::div_ceil(points_left, characters - index)
+        }
+    }
+
+    /**
+    The length, in chars, of the curse generate_curse would produce for data of the given length on a text of the given
+    number of graphemes, without producing it. This is meant for checking against character limits, like a chat
+    message's.
+
+    Like can_curse, text_len counts graphemes, and each is taken to be a single char, as in plain text. A text with
+    graphemes of several chars, like emoji, adds its extra chars on top. The length is only meaningful if the data
+    fits; see can_curse.
+    */
+    pub fn estimated_len(&self, text_len: usize, data_len: usize) -> usize {
+        let points = self.points_for_data(data_len);
+        let breaks = match (self.break_every, &self.diatrics_break) {
+            (Some(break_every), Some(diatrics_break)) => {
+                let breaks: usize = self
+                    .diactrics_per_letter(text_len, points)
+                    .iter()
+                    .map(|share| share / break_every)
+                    .sum();
+                breaks * diatrics_break.chars().count()
+            }
+            _ => 0,
+        };
+        text_len + points + breaks
+    }
+
+    /**
+    Reads the bytes back from text cursed with this config, with its diactric set, verifying the checksum if the config
+    has one.
+    */
+    pub fn read_curse_bytes(&self, text: &str) -> Result<Vec<u8>, CurseError> {
+        if self.two_channel {
+            let points = parse_curse_to_points_two_channel(text);
+            return self.unframe(self.diacritic_set.points_to_bytes(&points)?);
+        }
+        self.unframe(bytes_from_curse_with_set(text, &self.diacritic_set)?)
+    }
+
+    /**
+    Takes the data back out of the bytes frame produced, checking the checksum if the config has one.
+    */
+    fn unframe(&self, bytes: Vec<u8>) -> Result<Vec<u8>, CurseError> {
+        if !self.checksum {
+            return Ok(bytes);
+        }
+        let (length, consumed) = take_length_bytes(&bytes).ok_or(CurseError::MissingChecksum)?;
+        let body = &bytes[consumed..];
+        if body.len() < length + 2 {
+            return Err(CurseError::MissingChecksum);
+        }
+        let (payload, checksum) = body.split_at(length);
+        let expected = u16::from_be_bytes([checksum[0], checksum[1]]);
+        let found = crc16(payload);
+        if expected != found {
+            return Err(CurseError::ChecksumMismatch { expected, found });
+        }
+        Ok(payload.to_vec())
+    }
+
+    /**
+    Curses the text like generate_curse, but starts with a header diactric recording the diactric set and whether
+    there's a checksum, so read_curse_self_describing can read the curse without the config.
+
+    The header counts towards the config's limits like any other diactric. Fails with UnknownDiacriticSet unless the
+    set is basic, extended, or normalization_safe.
+    */
+    pub fn generate_curse_self_describing(
+        &self,
+        text: &str,
+        data: &[u8],
+    ) -> Result<String, CurseError> {
+        let id = DiacriticSet::described()
+            .iter()
+            .position(|set| *set == self.diacritic_set)
+            .ok_or(CurseError::UnknownDiacriticSet)?;
+        // The header is drawn from the normalization safe set, whose diactrics every built-in set has, so it reads the
+        // same whichever set it's from.
+        let header = DiacriticSet::normalization_safe()
+            .char_from_point((id + self.checksum as usize * 3) as u8);
+        let mut points = vec![self.diacritic_set.point_from_char(header).unwrap()];
+        points.extend(self.diacritic_set.bytes_to_points(&self.frame(data)));
+        points_to_curse(text, &points, self)
+    }
+
+    /**
+    Reads a value from text cursed with this config, like read_from_curse_with_set, verifying the checksum if the
+    config has one.
+    */
+    pub fn read_curse<T: serde::de::DeserializeOwned>(&self, text: &str) -> Result<T, CurseError> {
+        decode_postcard(&self.read_curse_bytes(text)?).map_err(CurseError::Decode)
+    }
+
+    /**
+    Recovers the cover text from text cursed with this config, like strip_curse_with_set, also dropping the config's
+    break, even a custom one. A break only ever follows every break_every diactrics, so the same text elsewhere in the
+    cover is kept.
+    */
+    pub fn strip_curse(&self, text: &str) -> String {
+        let above = self.two_channel.then(DiacriticSet::normalization_safe);
+        let is_diacritic = |c: char| {
+            self.diacritic_set.contains(c) || above.as_ref().is_some_and(|above| above.contains(c))
+        };
+        let mut stripped = String::with_capacity(text.len());
+        let mut rest = text;
+        // The diactrics in the current stack, since the last letter or break.
+        let mut stack = 0;
+        while let Some(c) = rest.chars().next() {
+            if let (Some(break_every), Some(diatrics_break)) =
+                (self.break_every, &self.diatrics_break)
+            {
+                if stack == break_every {
+                    if let Some(after_break) = rest.strip_prefix(diatrics_break.as_str()) {
+                        rest = after_break;
+                        stack = 0;
+                        continue;
+                    }
+                }
+            }
+            if is_diacritic(c) {
+                stack += 1;
+            } else {
+                stack = 0;
+                if ![ZWSP, ZWNJ, ZWJ, MVS].contains(&c) {
+                    stripped.push(c);
+                }
+            }
+            rest = &rest[c.len_utf8()..];
+        }
+        stripped
+    }
+}
+
+/**
+Curses the text with points which are already in the config's diacritic set, like those from
+DiacriticSet::bytes_to_points, or the output of an earlier step which works on points rather than bytes.
+
+Fails with PointsOutOfRange if a point has no diactric in the set, or for the same reasons as generate_curse.
+*/
+pub fn points_to_curse(
+    text: &str,
+    points: &[u8],
+    config: &CursedConfig,
+) -> Result<String, CurseError> {
+    // Diactrics go after whole graphemes, so they don't split an emoji or land inside a ZWJ sequence.
+    let characters = text.graphemes(true).count();
+    let above = check_curse(characters, points, config)?;
+    let mut cursed_text = String::new();
+    let mut point_index = 0;
+    let shares = config.diactrics_per_letter(characters, points.len());
+    for (grapheme, diatrics_per_letter) in text.graphemes(true).zip(shares) {
+        cursed_text.push_str(grapheme);
+        let letter_points = &points[point_index..point_index + diatrics_per_letter];
+        curse_letter(letter_points, above.as_ref(), config, &mut cursed_text);
+        point_index += diatrics_per_letter;
+    }
+    Ok(cursed_text)
+}
+
+/**
+Checks that the points can be cursed onto a text with the given number of graphemes, returning the set drawn above the
+letter in two channels.
+*/
+fn check_curse(
+    characters: usize,
+    points: &[u8],
+    config: &CursedConfig,
+) -> Result<Option<DiacriticSet>, CurseError> {
+    // In two channels, the points are also drawn above the letter.
+    let above = config.two_channel.then(DiacriticSet::normalization_safe);
+    if let Some(diacritic) = config
+        .diatrics_break
+        .iter()
+        .flat_map(|diatrics_break| diatrics_break.chars())
+        .find(|c| {
+            config.diacritic_set.contains(*c)
+                || above.as_ref().is_some_and(|above| above.contains(*c))
+        })
+    {
+        return Err(CurseError::BreakContainsDiacritic { diacritic });
+    }
+    if points
+        .iter()
+        .any(|point| *point as u32 >= config.diacritic_set.base())
+    {
+        return Err(CurseError::PointsOutOfRange);
+    }
+    config.check_capacity(characters, points.len())?;
+    Ok(above)
+}
+
+/**
+Writes the diactrics for one letter's points, with breaks between stacks as the config asks.
+*/
+fn curse_letter(
+    points: &[u8],
+    above: Option<&DiacriticSet>,
+    config: &CursedConfig,
+    cursed_text: &mut String,
+) {
+    for (dia_idx, point) in points.iter().enumerate() {
+        // Each stack between breaks starts above the letter, then alternates.
+        let stack_idx = match (config.break_every, &config.diatrics_break) {
+            (Some(break_every), Some(_)) => dia_idx % break_every,
+            _ => dia_idx,
+        };
+        let set = match above {
+            Some(above) if stack_idx % 2 == 0 => above,
+            _ => &config.diacritic_set,
+        };
+        cursed_text.push(set.char_from_point(*point));
+        if let (Some(break_every), Some(diatrics_break)) =
+            (config.break_every, &config.diatrics_break)
+        {
+            if (dia_idx + 1) % break_every == 0 {
+                cursed_text.push_str(diatrics_break);
+            }
+        }
+    }
+}
+
+/**
+Curses a carrier text one grapheme at a time, for documents too long to hold in memory at once. Only the data's
+diactrics are kept, and the output is identical to generate_curse on the whole carrier.
+
+The diactrics are shared out by the carrier's length, so it must be known up front, counted in graphemes as with
+unicode-segmentation. Graphemes past that length, or after the data runs out, pass through unchanged.
+*/
+pub struct CurseEncoder<'a> {
+    config: &'a CursedConfig,
+    above: Option<DiacriticSet>,
+    points: Vec<u8>,
+    point_index: usize,
+    characters: usize,
+    letter_index: usize,
+}
+
+impl<'a> CurseEncoder<'a> {
+    /**
+    Prepares to curse a carrier of the given number of graphemes with the data, or explains why the data won't fit.
+    */
+    pub fn new(
+        config: &'a CursedConfig,
+        characters: usize,
+        data: &[u8],
+    ) -> Result<Self, CurseError> {
+        let points = config.diacritic_set.bytes_to_points(&config.frame(data));
+        let above = check_curse(characters, &points, config)?;
+        Ok(CurseEncoder {
+            config,
+            above,
+            points,
+            point_index: 0,
+            characters,
+            letter_index: 0,
+        })
+    }
+
+    /**
+    Writes a grapheme of the carrier, followed by its diactrics.
+    */
+    pub fn push_grapheme(&mut self, grapheme: &str, cursed_text: &mut String) {
+        cursed_text.push_str(grapheme);
+        if self.letter_index >= self.characters {
+            return;
+        }
+        let share = self.config.letter_share(
+            self.letter_index,
+            self.characters,
+            self.points.len(),
+            self.points.len() - self.point_index,
+        );
+        let letter_points = &self.points[self.point_index..self.point_index + share];
+        curse_letter(letter_points, self.above.as_ref(), self.config, cursed_text);
+        self.point_index += share;
+        self.letter_index += 1;
+    }
+
+    /**
+    Writes a chunk of the carrier. Chunks should end on grapheme boundaries, or a grapheme split across two chunks is
+    counted, and cursed, as two.
+    */
+    pub fn push_str(&mut self, text: &str, cursed_text: &mut String) {
+        for grapheme in text.graphemes(true) {
+            self.push_grapheme(grapheme, cursed_text);
+        }
+    }
+
+    /**
+    Whether every diactric has been written, so the rest of the carrier passes through unchanged.
+    */
+    pub fn is_done(&self) -> bool {
+        self.point_index == self.points.len()
+    }
+
+    /**
+    Checks that the carrier was long enough to take every diactric, failing with InsufficientCarrierText otherwise.
+    */
+    pub fn finish(self) -> Result<(), CurseError> {
+        if !self.is_done() {
+            return Err(CurseError::InsufficientCarrierText {
+                needed: self.points.len(),
+                available: self.point_index,
+            });
+        }
+        Ok(())
+    }
+}
+
+/**
+Curses several lines of text with the data, joining them with newlines, for data too long for a single line to carry
+within the config's limits. Each line is cursed on its own, so max_diactrics and the other limits apply per line.
+
+The points are shared out across the lines by their length, the same way generate_curse shares them across letters,
+except that a line which hits its limits passes the rest on to later lines. Since newlines aren't diactrics, the
+joined text reads back like any other curse, with read_curse_bytes or bytes_from_curse. Fails with
+InsufficientCarrierText if all of the lines together can't hold the data.
+*/
+pub fn generate_curse_multiline(
+    lines: &[&str],
+    data: &[u8],
+    config: &CursedConfig,
+) -> Result<String, CurseError> {
+    let points = config.diacritic_set.bytes_to_points(&config.frame(data));
+    let lengths: Vec<usize> = lines
+        .iter()
+        .map(|line| line.graphemes(true).count())
+        .collect();
+    let capacities: Vec<usize> = lengths
+        .iter()
+        .map(|length| config.capacity(*length))
+        .collect();
+    let available = capacities
+        .iter()
+        .fold(0usize, |sum, capacity| sum.saturating_add(*capacity));
+    if points.len() > available {
+        return Err(CurseError::InsufficientCarrierText {
+            needed: points.len(),
+            available,
+        });
+    }
+    let mut cursed_lines = Vec::with_capacity(lines.len());
+    let mut point_index = 0;
+    let mut length_left: usize = lengths.iter().sum();
+    for (i, line) in lines.iter().enumerate() {
+        let points_left = points.len() - point_index;
+        let later_capacity = capacities[i + 1..]
+            .iter()
+            .fold(0usize, |sum, capacity| sum.saturating_add(*capacity));
+        let share = if length_left == 0 {
+            0
+        } else {
+            (points_left * lengths[i]).div_ceil(length_left)
+        };
+        // Take more than the line's share if the lines after it can't hold the rest.
+        let share = share
+            .max(points_left.saturating_sub(later_capacity))
+            .min(capacities[i])
+            .min(points_left);
+        cursed_lines.push(points_to_curse(
+            line,
+            &points[point_index..point_index + share],
+            config,
+        )?);
+        point_index += share;
+        length_left -= lengths[i];
+    }
+    Ok(cursed_lines.join("\n"))
+}
+
+/**
+Reads the bytes back from a curse made by CursedConfig::generate_curse_self_describing, with the diactric set and
+checksum its header names.
+*/
+pub fn read_curse_self_describing(text: &str) -> Result<Vec<u8>, CurseError> {
+    let header = text
+        .chars()
+        .find(|c| DiacriticSet::basic().contains(*c))
+        .and_then(|c| DiacriticSet::normalization_safe().point_from_char(c))
+        .ok_or(CurseError::MissingHeader)?;
+    let sets = DiacriticSet::described();
+    if header as usize >= sets.len() * 2 {
+        return Err(CurseError::MissingHeader);
+    }
+    let set = sets[header as usize % sets.len()].clone();
+    let mut config = CursedConfig::new().with_diacritic_set(set);
+    if header as usize >= sets.len() {
+        config = config.with_checksum();
+    }
+    let points = parse_curse_to_points_with_set(text, &config.diacritic_set);
+    config.unframe(config.diacritic_set.points_to_bytes(&points[1..])?)
+}
+
+pub fn create_curse<T: Serialize>(
+    t: &T,
+    config: &CursedConfig,
+    text: &str,
+) -> Result<String, CurseError> {
+    let data = postcard::to_allocvec(t).unwrap();
+    config.generate_curse(text, data.as_slice())
+}
+
+/**
+Reads the points from text cursed with the basic diactric set. See parse_curse_to_points_with_set for other sets, and
+parse_curse_to_points_two_channel for curses written in two channels.
+*/
+pub fn parse_curse_to_points(text: &str) -> Vec<u8> {
+    parse_curse_to_points_with_set(text, &DiacriticSet::basic())
+}
+
+/**
+Reads the points from text cursed with CursedConfig::two_channel. Each run of diactrics has its marks above and below
+the letter taken apart, then interleaved again starting from above, so the order the channels were drawn in doesn't
+matter.
+*/
+pub fn parse_curse_to_points_two_channel(text: &str) -> Vec<u8> {
+    let above = DiacriticSet::normalization_safe();
+    let below = DiacriticSet::below();
+    let mut points = Vec::new();
+    let mut run_above = Vec::new();
+    let mut run_below = Vec::new();
+    let mut flush = |run_above: &mut Vec<u8>, run_below: &mut Vec<u8>| {
+        for idx in 0..run_above.len().max(run_below.len()) {
+            points.extend(run_above.get(idx));
+            points.extend(run_below.get(idx));
+        }
+        run_above.clear();
+        run_below.clear();
+    };
+    for c in text.chars() {
+        if let Some(point) = above.point_from_char(c) {
+            run_above.push(point);
+        } else if let Some(point) = below.point_from_char(c) {
+            run_below.push(point);
+        } else {
+            flush(&mut run_above, &mut run_below);
+        }
+    }
+    flush(&mut run_above, &mut run_below);
+    points
+}
+
+/**
+Reads the points from cursed text, recognizing exactly the diactrics in the set the text was cursed with.
+
+Combining marks outside the set, like accents already in the cover text, are treated as part of the cover and
+skipped. Marks inside the set can't be told apart from data, so a cover text with its own marks should be cursed with
+a set that doesn't include them.
+*/
+pub fn parse_curse_to_points_with_set(text: &str, diacritic_set: &DiacriticSet) -> Vec<u8> {
+    text.chars()
+        .filter_map(|c| diacritic_set.point_from_char(c))
+        .collect()
+}
+
+/**
+Recovers the cover text from cursed text, dropping the diactrics and any of the preset break characters.
+*/
+pub fn strip_curse(text: &str) -> String {
+    strip_curse_with_set(text, &DiacriticSet::basic())
+}
+
+pub fn strip_curse_with_set(text: &str, diacritic_set: &DiacriticSet) -> String {
+    text.chars()
+        .filter(|c| !diacritic_set.contains(*c) && ![ZWSP, ZWNJ, ZWJ, MVS].contains(c))
+        .collect()
+}
+
+/**
+Reads the raw bytes back out of cursed text, mirroring CursedConfig::generate_curse. Fails with InvalidPointCount or
+PointsOutOfRange rather than panicking if the curse was truncated or corrupted.
+*/
+pub fn bytes_from_curse(text: &str) -> Result<Vec<u8>, CurseError> {
+    bytes_from_curse_with_set(text, &DiacriticSet::basic())
+}
+
+pub fn read_from_curse<T: serde::de::DeserializeOwned>(text: &str) -> Option<T> {
+    read_from_curse_checked(text).ok()
+}
+
+/**
+Reads a value from cursed text, like read_from_curse, but explains whether the diactrics or the payload were at fault.
+
+"Checked" here means the read reports errors instead of returning None. It can't verify a checksum. A plain curse
+carries no header, and a checksummed frame can't be told apart from data that happens to look like one. Read curses
+from a config with_checksum with CursedConfig::read_curse, or write them with generate_curse_self_describing and read
+them with read_curse_self_describing. Either way, a mismatch is a ChecksumMismatch.
+*/
+pub fn read_from_curse_checked<T: serde::de::DeserializeOwned>(
+    text: &str,
+) -> Result<T, CurseError> {
+    read_from_curse_with_set(text, &DiacriticSet::basic())
+}
+
+/**
+Reads a value from text cursed with a config using the given diactric set.
+*/
+pub fn read_from_curse_with_set<T: serde::de::DeserializeOwned>(
+    text: &str,
+    diacritic_set: &DiacriticSet,
+) -> Result<T, CurseError> {
+    let bytes = bytes_from_curse_with_set(text, diacritic_set)?;
+    decode_postcard(&bytes).map_err(CurseError::Decode)
+}
+
+pub fn bytes_from_curse_with_set(
+    text: &str,
+    diacritic_set: &DiacriticSet,
+) -> Result<Vec<u8>, CurseError> {
+    diacritic_set.points_to_bytes(&parse_curse_to_points_with_set(text, diacritic_set))
+}
+
+#[cfg(test)]
+mod cursed_tests {
+    use rand::random;
+
+    use super::*;
+
+    #[test]
+    fn test_is_diatric() {
+        assert!(DiacriticSet::basic().contains('̀'));
+        assert!(DiacriticSet::basic().contains('ͅ'));
+        assert!(!DiacriticSet::basic().contains('a'));
+    }
+
+    #[test]
+    fn copy_and_display_test() {
+        let s = "T\u{0300}\u{0300}\u{0300}\u{200D}\u{0301}\u{0301}\u{0301}\u{200D}\u{0302}\u{0302}\u{0302}\u{200D}\u{0300}\u{0300}\u{0300}e\u{0300}s\u{200D}t";
+        println!("{}", s);
+        // From terminal
+        let s_2 = "T̀̀̀‍́́́‍̂̂̂‍̀̀̀ès‍t";
+        assert_eq!(s, s_2);
+        // From Discord
+        let s_3 = "T̀̀̀‍́́́‍̂̂̂‍̀̀̀ès‍t";
+        assert_eq!(s, s_3);
+    }
+
+    #[test]
+    fn test_bytes_to_diactrics_points() {
+        let points = bytes_to_diactrics_points(&[0, 0, 0, 0]);
+        assert_eq!(points, vec![0, 0, 0, 0, 0]);
+        let points = bytes_to_diactrics_points(&[0, 0, 0, 0, 0]);
+        assert_eq!(points, vec![0, 0, 0, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_diatric_points_to_bytes() {
+        let bytes = diatric_points_to_bytes(vec![0, 0, 0, 0, 0]);
+        assert_eq!(bytes, vec![0, 0, 0, 0]);
+        let bytes = diatric_points_to_bytes(vec![0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(bytes, vec![0, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_invalid_points() {
+        assert_eq!(
+            diatric_points_to_bytes_checked(vec![0, 0, 0, 0, 0, 0]),
+            Err(CurseError::InvalidPointCount { count: 6 })
+        );
+        assert_eq!(
+            diatric_points_to_bytes_checked(vec![0x6f; 5]),
+            Err(CurseError::PointsOutOfRange)
+        );
+        // A curse with its last letter cut off shouldn't unwind.
+        let curse = CursedConfig::new()
+            .generate_curse("Curse", &[1, 2, 3, 4, 5])
+            .unwrap();
+        let truncated = curse
+            .chars()
+            .take(curse.chars().count() - 1)
+            .collect::<String>();
+        assert_eq!(read_from_curse::<[u8; 5]>(&truncated), None);
+        assert_eq!(
+            read_from_curse_checked::<[u8; 5]>(&truncated),
+            Err(CurseError::InvalidPointCount { count: 6 })
+        );
+        assert_eq!(
+            read_from_curse_checked::<[u8; 5]>(&curse),
+            Ok([1, 2, 3, 4, 5])
+        );
+        assert_eq!(
+            read_from_curse_checked::<[u8; 6]>(&curse),
+            Err(CurseError::Decode(DecodeError::Truncated(
+                postcard::Error::DeserializeUnexpectedEnd
+            )))
+        );
+    }
+
+    #[test]
+    fn test_valid_point_counts() {
+        for count in 0..40 {
+            assert_eq!(is_valid_point_count(count), count % 5 != 1);
+        }
+        // Every length the encoder produces is one the decoder accepts.
+        for length in 0..40 {
+            for set in [DiacriticSet::basic(), DiacriticSet::extended()] {
+                let points = set.bytes_to_points(&vec![0xff; length]);
+                assert!(set.is_valid_point_count(points.len()));
+                assert_eq!(set.points_to_bytes(&points).unwrap(), vec![0xff; length]);
+            }
+        }
+        assert!((0..40).all(|count| DiacriticSet::extended().is_valid_point_count(count)));
+    }
+
+    #[test]
+    fn test_diacritic_limits() {
+        for length in 0..64 {
+            assert_eq!(
+                diactrics_for_bytes(length),
+                bytes_to_diactrics_points(&vec![0; length]).len()
+            );
+        }
+        let configs = [
+            CursedConfig::new(),
+            CursedConfig::discord(),
+            CursedConfig::new().max_diactrics(13),
+            CursedConfig::new()
+                .max_diactrics_per_letter(3)
+                .with_no_break(),
+            CursedConfig::new()
+                .max_diactrics_per_letter(2)
+                .with_zwsp_break()
+                .max_diactrics(7),
+        ];
+        let text = "Curse";
+        for config in configs.iter() {
+            for length in 0..32 {
+                let bytes = (0..length as u8).collect::<Vec<_>>();
+                let result = config.generate_curse(text, &bytes);
+                assert_eq!(config.can_curse(text.len(), length), result.is_ok());
+                if let Ok(curse) = result {
+                    let diactrics = curse
+                        .chars()
+                        .filter(|c| DiacriticSet::basic().contains(*c))
+                        .count();
+                    assert!(config.max_diatrics.is_none_or(|max| diactrics <= max));
+                    assert_eq!(bytes_from_curse(&curse), Ok(bytes.clone()));
+                }
+            }
+        }
+        assert_eq!(
+            CursedConfig::discord().generate_curse("Curse", &[0; 17]),
+            Err(CurseError::TooManyDiacritics {
+                needed: 22,
+                available: 20
+            })
+        );
+        assert_eq!(
+            CursedConfig::new().generate_curse("", &[0]),
+            Err(CurseError::InsufficientCarrierText {
+                needed: 2,
+                available: 0
+            })
+        );
+    }
+
+    #[test]
+    fn test_diacritic_sets() {
+        let extended = DiacriticSet::extended();
+        assert_eq!(DiacriticSet::basic().base(), 112);
+        assert_eq!(extended.base(), 256);
+        for point in 0..=255 {
+            let c = extended.char_from_point(point);
+            assert_eq!(extended.point_from_char(c), Some(point));
+        }
+        assert_eq!(extended.point_from_char('a'), None);
+        assert_eq!(extended.points_for_bytes(100), 100);
+        assert_eq!(DiacriticSet::basic().points_for_bytes(100), 125);
+        assert_eq!(DiacriticSet::new(&[('\u{0300}', '\u{0300}')]), None);
+        assert_eq!(
+            DiacriticSet::new(&[('\u{0300}', '\u{036F}'), ('\u{0360}', '\u{0370}')]),
+            None
+        );
+        let custom =
+            DiacriticSet::new(&[('\u{0300}', '\u{036F}'), ('\u{1DC0}', '\u{1DFF}')]).unwrap();
+        assert_eq!(custom.base(), 176);
+
+        for diacritic_set in [DiacriticSet::basic(), extended, custom] {
+            let config = CursedConfig::new().with_diacritic_set(diacritic_set.clone());
+            for _ in 0..100 {
+                let bytes: Vec<u8> = (0..random::<u8>()).map(|_| random::<u8>()).collect();
+                let points = diacritic_set.bytes_to_points(&bytes);
+                assert_eq!(points.len(), diacritic_set.points_for_bytes(bytes.len()));
+                assert_eq!(diacritic_set.points_to_bytes(&points), Ok(bytes.clone()));
+                let curse = config.generate_curse("Comments & code", &bytes).unwrap();
+                assert_eq!(bytes_from_curse_with_set(&curse, &diacritic_set), Ok(bytes));
+            }
+        }
+    }
+
+    #[test]
+    fn test_normalization_safe() {
+        let set = DiacriticSet::normalization_safe();
+        assert_eq!(set.base(), 30);
+        assert_eq!(set.points_for_bytes(4), 7);
+        assert_eq!(set.points_for_bytes(3), 5);
+        // Below the letter, overlaid, decomposed by normalization, and composed with a letter.
+        for c in [
+            '\u{0316}', '\u{0334}', '\u{0340}', '\u{0344}', '\u{0345}', '\u{0300}', '\u{0308}',
+        ] {
+            assert!(!set.contains(c));
+        }
+        let config = CursedConfig::new().normalization_safe(true);
+        assert_eq!(config.diacritic_set(), &set);
+        let bytes: Vec<u8> = (0..=255).collect();
+        let curse = config
+            .generate_curse("Normalize this, if you must", &bytes)
+            .unwrap();
+        assert_eq!(config.read_curse_bytes(&curse), Ok(bytes));
+        assert_eq!(
+            CursedConfig::new()
+                .normalization_safe(true)
+                .normalization_safe(false)
+                .diacritic_set(),
+            &DiacriticSet::basic()
+        );
+    }
+
+    #[test]
+    fn test_generate_curse_multiline() {
+        let data: Vec<u8> = (0..40).collect();
+        let config = CursedConfig::new().max_diactrics(20);
+        let lines = ["First line", "Second", "And a third, longer line"];
+        assert!(config.generate_curse(lines[2], &data).is_err());
+        let curse = generate_curse_multiline(&lines, &data, &config).unwrap();
+        assert_eq!(curse.lines().count(), 3);
+        assert_eq!(strip_curse(&curse), lines.join("\n"));
+        for line in curse.lines() {
+            assert!(parse_curse_to_points(line).len() <= 20);
+        }
+        assert_eq!(bytes_from_curse(&curse), Ok(data.clone()));
+        assert_eq!(config.read_curse_bytes(&curse), Ok(data.clone()));
+        // A short line which fills up passes the rest on.
+        let config = CursedConfig::new().max_diactrics_per_letter(2);
+        let lines = ["A long line to carry most of it", "Hi"];
+        let curse = generate_curse_multiline(&lines, &data, &config).unwrap();
+        assert_eq!(bytes_from_curse(&curse), Ok(data.clone()));
+        assert_eq!(
+            generate_curse_multiline(&["Hi", "there"], &data, &config),
+            Err(CurseError::InsufficientCarrierText {
+                needed: 50,
+                available: 14
+            })
+        );
+        assert_eq!(
+            generate_curse_multiline(&[], &[], &CursedConfig::new()),
+            Ok(String::new())
+        );
+    }
+
+    #[test]
+    fn test_curse_encoder() {
+        let text = "A long document, fed to the encoder a piece at a time. 👨‍👩‍👧 Emoji stay whole.";
+        let characters = text.graphemes(true).count();
+        let data = b"Streamed data";
+        let configs = [
+            CursedConfig::new(),
+            CursedConfig::new().even_distribution(true),
+            CursedConfig::discord().max_diactrics(100),
+            CursedConfig::new()
+                .max_diactrics_per_letter(4)
+                .two_channel(true)
+                .with_checksum(),
+        ];
+        for config in configs {
+            let mut encoder = CurseEncoder::new(&config, characters, data).unwrap();
+            let mut cursed = String::new();
+            for piece in text.split_inclusive(' ') {
+                encoder.push_str(piece, &mut cursed);
+            }
+            assert!(encoder.is_done());
+            encoder.finish().unwrap();
+            assert_eq!(cursed, config.generate_curse(text, data).unwrap());
+        }
+
+        // Carrier past the promised length passes through unchanged.
+        let config = CursedConfig::new();
+        let mut encoder = CurseEncoder::new(&config, 5, b"Hi").unwrap();
+        let mut cursed = String::new();
+        encoder.push_str("Hello", &mut cursed);
+        encoder.push_str(", world", &mut cursed);
+        assert_eq!(
+            cursed,
+            config.generate_curse("Hello", b"Hi").unwrap() + ", world"
+        );
+
+        // A carrier shorter than promised leaves diactrics unwritten.
+        let mut encoder = CurseEncoder::new(&config, 5, b"Hello there").unwrap();
+        encoder.push_str("Hel", &mut String::new());
+        assert!(matches!(
+            encoder.finish(),
+            Err(CurseError::InsufficientCarrierText { .. })
+        ));
+        assert!(CurseEncoder::new(&config, 0, b"Hi").is_err());
+    }
+
+    #[test]
+    fn test_diacritic_plan() {
+        let text = "Preview each letter 👍🏽";
+        let data = b"Some planned data";
+        let configs = [
+            CursedConfig::new(),
+            CursedConfig::new().even_distribution(true),
+            CursedConfig::discord().max_diactrics(100),
+            CursedConfig::new().with_checksum(),
+        ];
+        for config in configs {
+            let plan = config.diacritic_plan(text.graphemes(true).count(), data.len());
+            let curse = config.generate_curse(text, data).unwrap();
+            // Walk the curse letter by letter, counting the diactrics which follow each one.
+            let mut rest = curse.as_str();
+            let mut placed = Vec::new();
+            for grapheme in text.graphemes(true) {
+                rest = rest.strip_prefix(grapheme).unwrap();
+                let mut count = 0;
+                while let Some(c) = rest.chars().next() {
+                    if config.diacritic_set().contains(c) {
+                        count += 1;
+                    } else if config.break_char().is_none_or(|b| !b.contains(c)) {
+                        break;
+                    }
+                    rest = &rest[c.len_utf8()..];
+                }
+                placed.push(count);
+            }
+            assert_eq!(plan, placed);
+        }
+    }
+
+    #[test]
+    fn test_two_channel() {
+        let bytes: Vec<u8> = (0..=255).collect();
+        let carrier = "Two channels, above and below";
+        for config in [
+            CursedConfig::new().two_channel(true),
+            CursedConfig::new()
+                .two_channel(true)
+                .max_diactrics_per_letter(8),
+            CursedConfig::discord()
+                .with_no_max_diactrics()
+                .two_channel(true),
+            CursedConfig::new().two_channel(true).with_checksum(),
+        ] {
+            let curse = config.generate_curse(carrier, &bytes).unwrap();
+            assert_eq!(strip_curse(&curse), carrier);
+            assert_eq!(config.read_curse_bytes(&curse), Ok(bytes.clone()));
+        }
+        assert_eq!(DiacriticSet::below().base(), 30);
+        assert_eq!(DiacriticSet::below().points_for_bytes(4), 7);
+        // Each channel stays within the per-letter limit, so a letter takes twice as many diactrics.
+        let config = CursedConfig::new()
+            .two_channel(true)
+            .max_diactrics_per_letter(2);
+        let curse = config.generate_curse("ab", &[1, 2, 3, 4]).unwrap();
+        assert_eq!(parse_curse_to_points(&curse).len(), 7);
+        let above = DiacriticSet::normalization_safe();
+        let below = DiacriticSet::below();
+        for letter in curse.split(['a', 'b']).skip(1) {
+            assert!(letter.chars().filter(|c| above.contains(*c)).count() <= 2);
+            assert!(letter.chars().filter(|c| below.contains(*c)).count() <= 2);
+        }
+        assert_eq!(config.min_carrier_len(4), 2);
+        // Normalization would move the marks below ahead of those above, which changes nothing.
+        let mut reordered = String::new();
+        let mut run_above = String::new();
+        for c in curse.chars() {
+            if above.contains(c) {
+                run_above.push(c);
+            } else if below.contains(c) {
+                reordered.push(c);
+            } else {
+                reordered.push_str(&run_above);
+                run_above.clear();
+                reordered.push(c);
+            }
+        }
+        reordered.push_str(&run_above);
+        assert_ne!(reordered, curse);
+        assert_eq!(config.read_curse_bytes(&reordered), Ok(vec![1, 2, 3, 4]));
+        assert_eq!(
+            CursedConfig::new()
+                .two_channel(true)
+                .normalization_safe(true)
+                .diacritic_set(),
+            &DiacriticSet::normalization_safe()
+        );
+    }
+
+    #[test]
+    fn test_normalization_safe_nfc() {
+        use unicode_normalization::UnicodeNormalization;
+
+        // Letters which NFC composes with many marks, above and below.
+        let carrier = "aeiouAEIOU nyczswgkhr";
+        for config in [
+            CursedConfig::new().normalization_safe(true),
+            CursedConfig::new().two_channel(true),
+        ] {
+            let points: Vec<u8> = (0..config.diacritic_set().base() as u8).collect();
+            let curse = points_to_curse(carrier, &points, &config).unwrap();
+            let normalized = curse.nfc().collect::<String>();
+            let read = if config.two_channel {
+                parse_curse_to_points_two_channel(&normalized)
+            } else {
+                parse_curse_to_points_with_set(&normalized, config.diacritic_set())
+            };
+            assert_eq!(read, points);
+            let bytes: Vec<u8> = (0..=255).collect();
+            let curse = config.generate_curse(carrier, &bytes).unwrap();
+            let normalized = curse.nfc().collect::<String>();
+            assert_eq!(config.read_curse_bytes(&normalized), Ok(bytes.clone()));
+        }
+        let config = CursedConfig::new().normalization_safe(true).with_checksum();
+        let curse = config
+            .generate_curse_self_describing(carrier, b"Described")
+            .unwrap();
+        assert_eq!(
+            read_curse_self_describing(&curse.nfc().collect::<String>()),
+            Ok(b"Described".to_vec())
+        );
+    }
+
+    #[test]
+    fn test_carrier_marks_outside_set() {
+        let bytes = b"Decorated";
+        // The enclosing circle isn't in the basic set, so it stays part of the cover.
+        let carrier = "Circled \u{20DD} cover";
+        let curse = CursedConfig::new().generate_curse(carrier, bytes).unwrap();
+        assert_eq!(
+            bytes_from_curse_with_set(&curse, &DiacriticSet::basic()),
+            Ok(bytes.to_vec())
+        );
+        assert_eq!(strip_curse(&curse), carrier);
+        // An accent from the basic block is only safe when the data uses other blocks.
+        let carrier = "Cafe\u{0301} au lait";
+        let supplement =
+            DiacriticSet::new(&[('\u{1AB0}', '\u{1ACE}'), ('\u{1DC0}', '\u{1DFF}')]).unwrap();
+        let curse = CursedConfig::new()
+            .with_diacritic_set(supplement.clone())
+            .generate_curse(carrier, bytes)
+            .unwrap();
+        assert_eq!(
+            bytes_from_curse_with_set(&curse, &supplement),
+            Ok(bytes.to_vec())
+        );
+        assert_eq!(strip_curse_with_set(&curse, &supplement), carrier);
+        assert_ne!(
+            bytes_from_curse_with_set(&curse, &DiacriticSet::extended()),
+            Ok(bytes.to_vec())
+        );
+    }
+
+    #[test]
+    fn test_even_distribution() {
+        let marks_per_letter = |curse: &str| {
+            curse.chars().fold(Vec::new(), |mut counts, c| {
+                if DiacriticSet::basic().contains(c) {
+                    *counts.last_mut().unwrap() += 1;
+                } else {
+                    counts.push(0);
+                }
+                counts
+            })
+        };
+        let bytes = [1, 2, 3, 4, 5];
+        let curse = CursedConfig::new().generate_curse("Curse", &bytes).unwrap();
+        assert_eq!(marks_per_letter(&curse), vec![2, 2, 1, 1, 1]);
+        let curse = CursedConfig::new()
+            .even_distribution(true)
+            .generate_curse("Curse", &bytes)
+            .unwrap();
+        assert_eq!(marks_per_letter(&curse), vec![1, 1, 2, 1, 2]);
+        assert_eq!(bytes_from_curse(&curse), Ok(bytes.to_vec()));
+
+        let config = CursedConfig::discord().even_distribution(true);
+        for length in 0..16 {
+            let bytes = (0..length).collect::<Vec<_>>();
+            let curse = config.generate_curse("Comments & code", &bytes).unwrap();
+            assert_eq!(bytes_from_curse(&curse), Ok(bytes.clone()));
+        }
+    }
+
+    #[test]
+    fn test_strip_curse() {
+        let text = "Comments & code";
+        let bytes = (0..16).collect::<Vec<_>>();
+        for config in [
+            CursedConfig::new(),
+            CursedConfig::discord(),
+            CursedConfig::discord().with_mvs_break(),
+        ] {
+            assert_eq!(
+                strip_curse(&config.generate_curse(text, &bytes).unwrap()),
+                text
+            );
+        }
+        let curse = CursedConfig::new()
+            .with_diacritic_set(DiacriticSet::extended())
+            .generate_curse(text, &bytes)
+            .unwrap();
+        assert_eq!(
+            strip_curse_with_set(&curse, &DiacriticSet::extended()),
+            text
+        );
+        // Breaks are removed even without any diactrics.
+        assert_eq!(
+            strip_curse("C\u{200B}u\u{200C}r\u{200D}s\u{180E}e"),
+            "Curse"
+        );
+
+        // A custom break is removed after diactrics, but kept where it's part of the cover text.
+        let text = "Comments | code";
+        for config in [
+            CursedConfig::discord().with_custom_break("|"),
+            CursedConfig::discord().with_custom_break("<br>"),
+            CursedConfig::new().with_checksum(),
+            CursedConfig::new()
+                .max_diactrics_per_letter(4)
+                .two_channel(true),
+        ] {
+            let curse = config.generate_curse(text, &bytes).unwrap();
+            assert_eq!(config.strip_curse(&curse), text);
+        }
+        let config = CursedConfig::new().break_every(3).with_custom_break("|");
+        assert_eq!(
+            config.strip_curse("a\u{300}\u{301}\u{302}||b\u{300}|"),
+            "a|b|"
+        );
+    }
+
+    #[test]
+    fn test_custom_break() {
+        let bytes = (0..16).collect::<Vec<_>>();
+        let config = CursedConfig::discord().with_custom_break("|");
+        let curse = config.generate_curse("Curse", &bytes).unwrap();
+        assert_eq!(curse.matches('|').count(), 5);
+        assert_eq!(bytes_from_curse(&curse), Ok(bytes.clone()));
+
+        let config = CursedConfig::discord().with_custom_break("a\u{0301}");
+        assert_eq!(
+            config.generate_curse("Curse", &bytes),
+            Err(CurseError::BreakContainsDiacritic {
+                diacritic: '\u{0301}'
+            })
+        );
+    }
+
+    #[test]
+    fn test_break_every() {
+        let bytes = (0..12).collect::<Vec<_>>();
+        let config = CursedConfig::discord().break_every(2);
+        let curse = config.generate_curse("Curse", &bytes).unwrap();
+        // 15 diactrics over 5 letters, with a break after every 2nd on each letter.
+        assert_eq!(curse.matches(ZWJ).count(), 5);
+        assert_eq!(bytes_from_curse(&curse), Ok(bytes.clone()));
+
+        // The per-letter cap doesn't add breaks, and holds even with a break.
+        let config = CursedConfig::discord().max_diactrics_per_letter(3);
+        let curse = config.generate_curse("Curse", &bytes).unwrap();
+        assert_eq!(curse.matches(ZWJ).count(), 5);
+        assert_eq!(bytes_from_curse(&curse), Ok(bytes.clone()));
+        assert_eq!(
+            config.generate_curse("Curse", &(0..13).collect::<Vec<_>>()),
+            Err(CurseError::InsufficientCarrierText {
+                needed: 17,
+                available: 15
+            })
+        );
+        // Without a break, letters can't go past break_every.
+        let config = CursedConfig::new().break_every(3);
+        let curse = config.generate_curse("Curse", &bytes).unwrap();
+        assert_eq!(strip_curse(&curse), "Curse");
+        assert_eq!(
+            config.generate_curse("Curse", &(0..13).collect::<Vec<_>>()),
+            Err(CurseError::BreakRequired)
+        );
+        assert!(!config.can_curse(5, 13));
+    }
+
+    #[test]
+    fn test_estimated_len() {
+        let configs = [
+            CursedConfig::new(),
+            CursedConfig::discord(),
+            CursedConfig::new().even_distribution(true),
+            CursedConfig::new().with_zwsp_break().break_every(2),
+            CursedConfig::new()
+                .with_custom_break("--")
+                .break_every(3)
+                .even_distribution(true),
+            CursedConfig::new().with_diacritic_set(DiacriticSet::extended()),
+        ];
+        let texts = [
+            "Curse",
+            "A longer cover text",
+            "\u{1F469}\u{200D}\u{1F4BB} ok",
+        ];
+        for config in configs.iter() {
+            for text in texts {
+                for length in 0..40 {
+                    let bytes: Vec<u8> = (0..length).map(|_| random::<u8>()).collect();
+                    if let Ok(curse) = config.generate_curse(text, &bytes) {
+                        let graphemes = text.graphemes(true).count();
+                        assert_eq!(
+                            config.estimated_len(graphemes, length) + text.chars().count()
+                                - graphemes,
+                            curse.chars().count()
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_config_getters() {
+        let config = CursedConfig::discord();
+        assert_eq!(config.break_char(), Some("\u{200D}"));
+        assert_eq!(config.max_per_letter(), None);
+        assert_eq!(config.max_total(), Some(20));
+        let variation = config.clone().max_diactrics_per_letter(2).with_no_break();
+        assert_eq!(variation.break_char(), None);
+        assert_eq!(variation.max_per_letter(), Some(2));
+        assert_eq!(variation.max_total(), Some(20));
+        assert_eq!(config.max_per_letter(), None);
+        assert!(format!("{:?}", config).contains("max_diatrics: Some(20)"));
+    }
+
+    #[test]
+    fn test_min_carrier_len() {
+        let config = CursedConfig::new();
+        assert_eq!(config.min_carrier_len(0), 0);
+        assert_eq!(config.min_carrier_len(100), 1);
+        let config = CursedConfig::new().max_diactrics_per_letter(4);
+        // 10 bytes take 13 diactrics.
+        assert_eq!(config.min_carrier_len(10), 4);
+        assert!(config.can_curse(4, 10));
+        assert!(!config.can_curse(3, 10));
+        let config = CursedConfig::new().with_no_break().break_every(5);
+        assert_eq!(config.min_carrier_len(10), 3);
+        assert!(config.can_curse(3, 10));
+        assert!(!config.can_curse(2, 10));
+        let config = CursedConfig::new().with_zwsp_break().break_every(5);
+        assert_eq!(config.min_carrier_len(10), 1);
+        assert!(config.can_curse(1, 10));
+    }
+
+    #[test]
+    fn test_checksum() {
+        let config = CursedConfig::new().with_checksum();
+        let value = ("Normalize this".to_string(), 1234u32);
+        let curse = create_curse(&value, &config, "Survives the trip").unwrap();
+        assert_eq!(config.read_curse(&curse), Ok(value.clone()));
+        assert_eq!(
+            curse.chars().count(),
+            config.estimated_len(
+                "Survives the trip".len(),
+                postcard::to_allocvec(&value).unwrap().len()
+            )
+        );
+        // Swapping the first diactric for another keeps the point count, so only the checksum notices.
+        let tampered: String = curse
+            .chars()
+            .enumerate()
+            .map(|(i, c)| match DiacriticSet::basic().point_from_char(c) {
+                Some(point) if i == 1 => DiacriticSet::basic().char_from_point((point + 1) % 112),
+                _ => c,
+            })
+            .collect();
+        assert!(matches!(
+            config.read_curse::<(String, u32)>(&tampered),
+            Err(CurseError::ChecksumMismatch { .. })
+        ));
+        // Without the checksum, the curse is read as the framed bytes.
+        assert!(read_from_curse_checked::<(String, u32)>(&curse) != Ok(value.clone()));
+        let short = config.generate_curse("Short", &[]).unwrap();
+        assert_eq!(config.read_curse_bytes(&short), Ok(vec![]));
+        assert_eq!(
+            config.read_curse_bytes("Plain"),
+            Err(CurseError::MissingChecksum)
+        );
+        assert!(CursedConfig::new().can_curse(1, 3));
+        assert!(!CursedConfig::new()
+            .max_diactrics(4)
+            .with_checksum()
+            .can_curse(1, 3));
+    }
+
+    #[test]
+    fn test_can_curse_tails() {
+        for (length, diactrics) in [(1, 2), (2, 3), (3, 4), (5, 7), (6, 8), (7, 9)] {
+            assert_eq!(diactrics_for_bytes(length), diactrics);
+            let config = CursedConfig::new().max_diactrics(diactrics);
+            assert!(config.can_curse(5, length));
+            let config = CursedConfig::new().max_diactrics(diactrics - 1);
+            assert!(!config.can_curse(5, length));
+            assert!(config.generate_curse("Curse", &vec![0xff; length]).is_err());
+        }
+    }
+
+    #[test]
+    fn test_points_to_curse() {
+        let config = CursedConfig::new();
+        let data = [1, 2, 3, 4, 5, 6];
+        let points = bytes_to_diactrics_points(&data);
+        let curse = points_to_curse("Points", &points, &config).unwrap();
+        assert_eq!(curse, config.generate_curse("Points", &data).unwrap());
+        assert_eq!(parse_curse_to_points(&curse), points);
+        assert_eq!(
+            points_to_curse("Points", &[0, 112], &config),
+            Err(CurseError::PointsOutOfRange)
+        );
+    }
+
+    #[test]
+    fn test_bytes_to_bytes() {
+        let bytes = [166];
+        let points = bytes_to_diactrics_points(&bytes);
+        let bytes_2 = diatric_points_to_bytes(points);
+        assert_eq!(bytes.to_vec(), bytes_2);
+
+        let bytes = [98, 205, 238];
+        let points = bytes_to_diactrics_points(&bytes);
+        let bytes_2 = diatric_points_to_bytes(points);
+        assert_eq!(bytes.to_vec(), bytes_2);
+
+        let bytes = [62, 10, 105, 133, 98, 205, 238];
+        let points = bytes_to_diactrics_points(&bytes);
+        let bytes_2 = diatric_points_to_bytes(points);
+        assert_eq!(bytes.to_vec(), bytes_2);
+
+        let bytes = [
+            62, 10, 105, 133, 98, 205, 238, 62, 10, 105, 133, 98, 205, 238, 123, 23,
+        ];
+        let points = bytes_to_diactrics_points(&bytes);
+        let bytes_2 = diatric_points_to_bytes(points);
+        assert_eq!(bytes.to_vec(), bytes_2);
+    }
+
+    #[test]
+    fn discord_cursed() {
+        let curse_config = CursedConfig::discord();
+        let text = "Curse";
+        let bytes = (0..16).collect::<Vec<_>>();
+        let curse = curse_config.generate_curse(text, &bytes).unwrap();
+        let points = parse_curse_to_points(&curse);
+        let bytes_2 = diatric_points_to_bytes(points);
+        assert_eq!(bytes, bytes_2);
+        // Copied to Discord, then copy+pasted back.
+        let pasted = "C͓̝̅‍̀ù͗̍‍̀r̰̀͛‍ͭsͪ̀͟‍͟e̟ͥ͝‍́";
+        assert_eq!(curse, pasted);
+    }
+
+    #[test]
+    fn overly_cursed() {
+        let curse_config = CursedConfig::new();
+        let text = "Comments & code";
+        let bytes = (0..100).collect::<Vec<_>>();
+        let curse = curse_config.generate_curse(text, &bytes).unwrap();
+        let points = parse_curse_to_points(&curse);
+        let bytes_2 = diatric_points_to_bytes(points);
+        assert_eq!(bytes, bytes_2);
+    }
+
+    #[test]
+    fn test_bytes_from_curse() {
+        // The same path store_cursed_bytes and retrieve_cursed_bytes take through the clipboard.
+        let curse_config = CursedConfig::discord();
+        let bytes = [62, 10, 105, 133, 98, 205, 238];
+        let curse = curse_config.generate_curse("Curse", &bytes).unwrap();
+        assert_eq!(bytes_from_curse(&curse), Ok(bytes.to_vec()));
+        assert_eq!(bytes_from_curse("No curse here"), Ok(Vec::<u8>::new()));
+        // A curse cut off after its first diactric can't be read, but doesn't unwind.
+        assert_eq!(
+            bytes_from_curse("a\u{0300}"),
+            Err(CurseError::InvalidPointCount { count: 1 })
+        );
+    }
+
+    #[test]
+    fn test_emoji_cover() {
+        // A ZWJ family, a flag with a variation selector, and a skin tone modifier.
+        let text = "\u{1F469}\u{200D}\u{1F4BB}\u{1F3F3}\u{FE0F}\u{200D}\u{1F308}\u{1F44D}\u{1F3FD}";
+        let bytes = [1, 2, 3, 4, 5, 6];
+        let curse = CursedConfig::new().generate_curse(text, &bytes).unwrap();
+        assert_eq!(bytes_from_curse(&curse), Ok(bytes.to_vec()));
+        let cover: Vec<&str> = text.graphemes(true).collect();
+        let cursed: Vec<&str> = curse.graphemes(true).collect();
+        assert_eq!(cursed.len(), cover.len());
+        for (cursed, cover) in cursed.iter().zip(cover) {
+            assert!(cursed.starts_with(cover));
+            assert!(cursed[cover.len()..]
+                .chars()
+                .all(|c| DiacriticSet::basic().contains(c)));
+        }
+    }
+
+    #[test]
+    fn stress_test() {
+        let curse_config = CursedConfig::new();
+        for _ in 0..1000 {
+            let bytes: Vec<u8> = (0..random::<u8>()).map(|_| random::<u8>()).collect();
+            let points = bytes_to_diactrics_points(&bytes);
+            let bytes_2 = diatric_points_to_bytes(points);
+            assert_eq!(bytes, bytes_2);
+            let text = "Comments & code";
+            let curse = curse_config.generate_curse(text, &bytes).unwrap();
+            let points_2 = parse_curse_to_points(&curse);
+            let bytes_3 = diatric_points_to_bytes(points_2);
+            assert_eq!(bytes, bytes_3);
+        }
+    }
+}
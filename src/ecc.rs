@@ -0,0 +1,355 @@
+#[cfg(feature = "futhark")]
+use serde::{de::DeserializeOwned, Serialize};
+
+/// The most bytes a single Reed-Solomon block can hold, parity included.
+const BLOCK_LENGTH: usize = 255;
+
+/**
+Reasons bytes protected by encode_with_ecc could not be recovered.
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EccError {
+    /// A block is too short to hold its parity bytes, so bytes were lost rather than changed.
+    Truncated,
+    /// A block has more corrupted bytes than its parity can correct.
+    TooManyErrors,
+}
+
+impl std::fmt::Display for EccError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EccError::Truncated => write!(f, "block is shorter than its parity"),
+            EccError::TooManyErrors => write!(f, "too many errors to correct"),
+        }
+    }
+}
+
+impl std::error::Error for EccError {}
+
+/// Exponent and logarithm tables for GF(2^8), with the primitive polynomial x^8 + x^4 + x^3 + x^2 + 1.
+static TABLES: ([u8; 512], [u8; 256]) = build_tables();
+
+const fn build_tables() -> ([u8; 512], [u8; 256]) {
+    let mut exp = [0; 512];
+    let mut log = [0; 256];
+    let mut x: u16 = 1;
+    let mut i = 0;
+    while i < 255 {
+        exp[i] = x as u8;
+        log[x as usize] = i as u8;
+        x <<= 1;
+        if x & 0x100 != 0 {
+            x ^= 0x11d;
+        }
+        i += 1;
+    }
+    // Doubling the exponents saves a modulo when multiplying.
+    while i < 512 {
+        exp[i] = exp[i - 255];
+        i += 1;
+    }
+    (exp, log)
+}
+
+fn gf_exp(power: usize) -> u8 {
+    TABLES.0[power % 255]
+}
+
+fn gf_log(x: u8) -> usize {
+    TABLES.1[x as usize] as usize
+}
+
+fn gf_mul(a: u8, b: u8) -> u8 {
+    if a == 0 || b == 0 {
+        0
+    } else {
+        TABLES.0[gf_log(a) + gf_log(b)]
+    }
+}
+
+fn gf_div(a: u8, b: u8) -> u8 {
+    if a == 0 {
+        0
+    } else {
+        TABLES.0[gf_log(a) + 255 - gf_log(b)]
+    }
+}
+
+fn gf_inverse(x: u8) -> u8 {
+    TABLES.0[255 - gf_log(x)]
+}
+
+// Polynomials are stored with the highest degree coefficient first.
+
+fn poly_scale(p: &[u8], x: u8) -> Vec<u8> {
+    p.iter().map(|c| gf_mul(*c, x)).collect()
+}
+
+fn poly_add(p: &[u8], q: &[u8]) -> Vec<u8> {
+    let length = p.len().max(q.len());
+    let mut result = vec![0; length];
+    for (i, c) in p.iter().enumerate() {
+        result[i + length - p.len()] = *c;
+    }
+    for (i, c) in q.iter().enumerate() {
+        result[i + length - q.len()] ^= *c;
+    }
+    result
+}
+
+fn poly_mul(p: &[u8], q: &[u8]) -> Vec<u8> {
+    let mut result = vec![0; p.len() + q.len() - 1];
+    for (j, qc) in q.iter().enumerate() {
+        for (i, pc) in p.iter().enumerate() {
+            result[i + j] ^= gf_mul(*pc, *qc);
+        }
+    }
+    result
+}
+
+fn poly_eval(p: &[u8], x: u8) -> u8 {
+    p.iter().skip(1).fold(p[0], |y, c| gf_mul(y, x) ^ c)
+}
+
+fn generator_poly(parity: usize) -> Vec<u8> {
+    (0..parity).fold(vec![1], |g, i| poly_mul(&g, &[1, gf_exp(i)]))
+}
+
+fn encode_block(data: &[u8], generator: &[u8]) -> Vec<u8> {
+    let parity = generator.len() - 1;
+    let mut block = data.to_vec();
+    block.resize(data.len() + parity, 0);
+    for i in 0..data.len() {
+        let coefficient = block[i];
+        if coefficient != 0 {
+            for (j, g) in generator.iter().enumerate().skip(1) {
+                block[i + j] ^= gf_mul(*g, coefficient);
+            }
+        }
+    }
+    // The division overwrote the data with its quotient, so put it back in front of the remainder.
+    block[..data.len()].copy_from_slice(data);
+    block
+}
+
+/// The syndromes of a block, after a leading zero. They're all zero when the block is intact.
+fn syndromes(block: &[u8], parity: usize) -> Vec<u8> {
+    std::iter::once(0)
+        .chain((0..parity).map(|i| poly_eval(block, gf_exp(i))))
+        .collect()
+}
+
+/// Finds the error locator polynomial from the syndromes, with Berlekamp-Massey.
+fn error_locator(syndromes: &[u8], parity: usize) -> Result<Vec<u8>, EccError> {
+    let mut locator = vec![1];
+    let mut old_locator = vec![1];
+    for k in 1..=parity {
+        let mut delta = syndromes[k];
+        for j in 1..locator.len() {
+            delta ^= gf_mul(locator[locator.len() - 1 - j], syndromes[k - j]);
+        }
+        old_locator.push(0);
+        if delta != 0 {
+            if old_locator.len() > locator.len() {
+                let new_locator = poly_scale(&old_locator, delta);
+                old_locator = poly_scale(&locator, gf_inverse(delta));
+                locator = new_locator;
+            }
+            locator = poly_add(&locator, &poly_scale(&old_locator, delta));
+        }
+    }
+    let leading_zeros = locator.iter().take_while(|c| **c == 0).count();
+    let locator = locator[leading_zeros..].to_vec();
+    if (locator.len() - 1) * 2 > parity {
+        return Err(EccError::TooManyErrors);
+    }
+    Ok(locator)
+}
+
+/// Finds the positions of the errors in a block, as the roots of the error locator.
+fn error_positions(locator: &[u8], block_length: usize) -> Result<Vec<usize>, EccError> {
+    let reversed: Vec<u8> = locator.iter().rev().copied().collect();
+    let positions: Vec<usize> = (0..block_length)
+        .filter(|i| poly_eval(&reversed, gf_exp(*i)) == 0)
+        .map(|i| block_length - 1 - i)
+        .collect();
+    if positions.len() != locator.len() - 1 {
+        return Err(EccError::TooManyErrors);
+    }
+    Ok(positions)
+}
+
+/// Fixes the errors at the given positions in place, with the Forney algorithm.
+fn correct_errors(block: &mut [u8], syndromes: &[u8], positions: &[usize]) {
+    let powers: Vec<usize> = positions.iter().map(|p| block.len() - 1 - p).collect();
+    let locator = powers.iter().fold(vec![1], |l, power| {
+        poly_mul(&l, &poly_add(&[1], &[gf_exp(*power), 0]))
+    });
+    let reversed_syndromes: Vec<u8> = syndromes.iter().rev().copied().collect();
+    let product = poly_mul(&reversed_syndromes, &locator);
+    let evaluator: Vec<u8> = product[product.len() - locator.len()..].to_vec();
+    let xs: Vec<u8> = powers.iter().map(|power| gf_exp(*power)).collect();
+    for (i, x) in xs.iter().enumerate() {
+        let x_inverse = gf_inverse(*x);
+        let locator_prime = xs
+            .iter()
+            .enumerate()
+            .filter(|(j, _)| *j != i)
+            .fold(1, |product, (_, other)| {
+                gf_mul(product, 1 ^ gf_mul(x_inverse, *other))
+            });
+        let y = gf_mul(*x, poly_eval(&evaluator, x_inverse));
+        block[positions[i]] ^= gf_div(y, locator_prime);
+    }
+}
+
+fn decode_block(block: &[u8], parity: usize) -> Result<Vec<u8>, EccError> {
+    if block.len() <= parity {
+        return Err(EccError::Truncated);
+    }
+    let mut block = block.to_vec();
+    let syndromes = syndromes(&block, parity);
+    if syndromes.iter().any(|s| *s != 0) {
+        let locator = error_locator(&syndromes, parity)?;
+        let positions = error_positions(&locator, block.len())?;
+        correct_errors(&mut block, &syndromes, &positions);
+        if self::syndromes(&block, parity).iter().any(|s| *s != 0) {
+            return Err(EccError::TooManyErrors);
+        }
+    }
+    block.truncate(block.len() - parity);
+    Ok(block)
+}
+
+/**
+Protects bytes with Reed-Solomon parity, so that decode_with_ecc can correct some of them if they're changed.
+
+The bytes are split into blocks of up to 255 - parity bytes, and each block is followed by its own parity bytes. So
+the overhead is parity bytes per block, and up to parity / 2 changed bytes can be corrected in each block. Bytes
+which are lost, rather than changed, can't be corrected, since every byte after them moves.
+
+Panics if parity is 255 or more, which would leave no room for data in a block.
+*/
+pub fn encode_with_ecc(bytes: &[u8], parity: usize) -> Vec<u8> {
+    assert!(parity < BLOCK_LENGTH, "parity must be less than 255");
+    let generator = generator_poly(parity);
+    bytes
+        .chunks(BLOCK_LENGTH - parity)
+        .flat_map(|data| encode_block(data, &generator))
+        .collect()
+}
+
+/**
+Recovers the bytes protected by encode_with_ecc, correcting any changed bytes. The parity must match the encoder's.
+*/
+pub fn decode_with_ecc(bytes: &[u8], parity: usize) -> Result<Vec<u8>, EccError> {
+    assert!(parity < BLOCK_LENGTH, "parity must be less than 255");
+    let mut result = Vec::new();
+    for block in bytes.chunks(BLOCK_LENGTH) {
+        result.extend(decode_block(block, parity)?);
+    }
+    Ok(result)
+}
+
+/**
+Like create_runes, but protects the serialized value with parity bytes from encode_with_ecc.
+
+Each substituted rune changes at most two bytes, so every block can survive parity / 4 bad runes.
+*/
+#[cfg(feature = "futhark")]
+pub fn create_runes_with_ecc<T: Serialize>(
+    t: &T,
+    alphabet: &crate::Alphabet,
+    parity: usize,
+) -> String {
+    let data = postcard::to_allocvec(t).unwrap();
+    crate::generate_runes(&encode_with_ecc(&data, parity), alphabet)
+}
+
+/**
+Reads a value written by create_runes_with_ecc with the same parity, correcting any substituted runes it can.
+*/
+#[cfg(feature = "futhark")]
+pub fn read_from_runes_with_ecc<T: DeserializeOwned>(
+    runes: &str,
+    alphabet: &crate::Alphabet,
+    parity: usize,
+) -> Option<T> {
+    let bytes = decode_with_ecc(&crate::bytes_from_runes(runes, alphabet), parity).ok()?;
+    postcard::from_bytes(&bytes).ok()
+}
+
+#[cfg(test)]
+mod ecc_tests {
+    use rand::random;
+    #[cfg(feature = "futhark")]
+    use unicode_segmentation::UnicodeSegmentation;
+
+    use super::*;
+
+    fn random_bytes(length: usize) -> Vec<u8> {
+        (0..length).map(|_| random()).collect()
+    }
+
+    #[test]
+    fn test_overhead() {
+        assert_eq!(encode_with_ecc(&[1, 2, 3], 8).len(), 11);
+        assert_eq!(encode_with_ecc(&random_bytes(500), 10).len(), 500 + 3 * 10);
+        assert_eq!(encode_with_ecc(&[1, 2, 3], 0), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_intact_round_trip() {
+        let bytes = random_bytes(600);
+        assert_eq!(decode_with_ecc(&encode_with_ecc(&bytes, 16), 16), Ok(bytes));
+    }
+
+    #[test]
+    fn test_corrects_errors() {
+        for _ in 0..100 {
+            let bytes = random_bytes(random::<usize>() % 400 + 1);
+            let mut encoded = encode_with_ecc(&bytes, 16);
+            for block in encoded.chunks_mut(BLOCK_LENGTH) {
+                for _ in 0..8 {
+                    let index = random::<usize>() % block.len();
+                    block[index] ^= random::<u8>() | 1;
+                }
+            }
+            assert_eq!(decode_with_ecc(&encoded, 16), Ok(bytes));
+        }
+    }
+
+    #[test]
+    fn test_too_many_errors() {
+        let bytes: Vec<u8> = (0..32).collect();
+        let mut encoded = encode_with_ecc(&bytes, 4);
+        for byte in encoded.iter_mut().take(10) {
+            *byte ^= 0x55;
+        }
+        assert_ne!(decode_with_ecc(&encoded, 4), Ok(bytes));
+    }
+
+    #[test]
+    fn test_truncated() {
+        let encoded = encode_with_ecc(&[1, 2, 3], 8);
+        assert_eq!(decode_with_ecc(&encoded[..8], 8), Err(EccError::Truncated));
+    }
+
+    #[cfg(feature = "futhark")]
+    #[test]
+    fn test_runes_with_ecc() {
+        let value = ("Mangled by a chat client".to_string(), 1234u32);
+        let runes = create_runes_with_ecc(&value, &crate::FUTHARK, 8);
+        let substitute = crate::FUTHARK.graphemes().next().unwrap();
+        let mangled: String = runes
+            .graphemes(true)
+            .enumerate()
+            .map(|(i, rune)| if i == 3 || i == 20 { substitute } else { rune })
+            .collect();
+        assert_ne!(mangled, runes);
+        assert_eq!(
+            read_from_runes_with_ecc(&mangled, &crate::FUTHARK, 8),
+            Some(value)
+        );
+    }
+}
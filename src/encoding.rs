@@ -0,0 +1,406 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use serde::{de::DeserializeOwned, Serialize};
+
+/**
+Reasons a value could not be encoded or decoded through an Encoding.
+*/
+#[derive(Debug)]
+pub enum EncodeError {
+    /// The bytes don't fit in a box layout allowed by the config.
+    #[cfg(feature = "boxes")]
+    Layout(crate::LayoutError),
+    /// The bytes couldn't be spread over, or read back from, the cover text.
+    #[cfg(feature = "cursed")]
+    Curse(crate::CurseError),
+    /// The bytes were decoded, but didn't deserialize to the requested type.
+    Postcard(postcard::Error),
+    /// The text has no self-describing header, or the encoding has no way to write one.
+    Header,
+}
+
+impl core::fmt::Display for EncodeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            #[cfg(feature = "boxes")]
+            EncodeError::Layout(err) => write!(f, "{}", err),
+            #[cfg(feature = "cursed")]
+            EncodeError::Curse(err) => write!(f, "{}", err),
+            EncodeError::Postcard(err) => write!(f, "could not deserialize: {}", err),
+            EncodeError::Header => write!(f, "missing or unknown self-describing header"),
+        }
+    }
+}
+
+impl core::error::Error for EncodeError {}
+
+#[cfg(feature = "boxes")]
+impl From<crate::LayoutError> for EncodeError {
+    fn from(err: crate::LayoutError) -> Self {
+        EncodeError::Layout(err)
+    }
+}
+
+#[cfg(feature = "cursed")]
+impl From<crate::CurseError> for EncodeError {
+    fn from(err: crate::CurseError) -> Self {
+        EncodeError::Curse(err)
+    }
+}
+
+/**
+A way of turning bytes into text and back, shared by each of the encodings in this crate.
+
+Encoding can fail for the encodings with limited room, like boxes with a maximum size or cursed cover text.
+*/
+pub trait Encoding {
+    fn encode_bytes(&self, bytes: &[u8]) -> Result<String, EncodeError>;
+
+    fn decode_bytes(&self, text: &str) -> Result<Vec<u8>, EncodeError>;
+
+    /**
+    Encodes the bytes with a short header recording what decode_self_describing needs to read them back. Encodings
+    which can't record their parameters, like those using a custom alphabet or diactric set, fail with Header.
+    */
+    fn encode_self_describing(&self, bytes: &[u8]) -> Result<String, EncodeError> {
+        let _ = bytes;
+        Err(EncodeError::Header)
+    }
+}
+
+/**
+Encodes the runes of an alphabet, like create_runes and read_from_runes.
+*/
+#[cfg(feature = "futhark")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FutharkEncoding(pub crate::Alphabet);
+
+#[cfg(feature = "futhark")]
+impl Encoding for FutharkEncoding {
+    fn encode_bytes(&self, bytes: &[u8]) -> Result<String, EncodeError> {
+        Ok(crate::generate_runes(bytes, &self.0))
+    }
+
+    fn decode_bytes(&self, text: &str) -> Result<Vec<u8>, EncodeError> {
+        Ok(crate::bytes_from_runes(text, &self.0))
+    }
+
+    fn encode_self_describing(&self, bytes: &[u8]) -> Result<String, EncodeError> {
+        crate::generate_runes_self_describing(bytes, &self.0).ok_or(EncodeError::Header)
+    }
+}
+
+/**
+Encodes box diagrams laid out by a config, like create_boxes and parse_boxes.
+
+Decoding doesn't need the config, and may leave zero bytes after the data; see bytes_from_boxes.
+*/
+#[cfg(feature = "boxes")]
+#[derive(Debug, Clone, Default)]
+pub struct BoxEncoding(pub crate::BoxLayoutConfig);
+
+#[cfg(feature = "boxes")]
+impl Encoding for BoxEncoding {
+    fn encode_bytes(&self, bytes: &[u8]) -> Result<String, EncodeError> {
+        Ok(crate::generate_boxes_checked(bytes, Some(self.0.clone()))?)
+    }
+
+    fn decode_bytes(&self, text: &str) -> Result<Vec<u8>, EncodeError> {
+        Ok(crate::bytes_from_boxes(text))
+    }
+
+    fn encode_self_describing(&self, bytes: &[u8]) -> Result<String, EncodeError> {
+        Ok(crate::generate_boxes_self_describing(
+            bytes,
+            Some(self.0.clone()),
+        )?)
+    }
+}
+
+/**
+Encodes data as diactrics over the given cover text, like create_curse and CursedConfig::read_curse.
+*/
+#[cfg(feature = "cursed")]
+pub struct CursedEncoding(pub crate::CursedConfig, pub String);
+
+#[cfg(feature = "cursed")]
+impl Encoding for CursedEncoding {
+    fn encode_bytes(&self, bytes: &[u8]) -> Result<String, EncodeError> {
+        Ok(self.0.generate_curse(&self.1, bytes)?)
+    }
+
+    fn decode_bytes(&self, text: &str) -> Result<Vec<u8>, EncodeError> {
+        Ok(self.0.read_curse_bytes(text)?)
+    }
+
+    fn encode_self_describing(&self, bytes: &[u8]) -> Result<String, EncodeError> {
+        Ok(self.0.generate_curse_self_describing(&self.1, bytes)?)
+    }
+}
+
+/**
+Serializes a value with postcard, then encodes the bytes.
+*/
+pub fn encode_value<T: Serialize, E: Encoding>(t: &T, encoding: &E) -> Result<String, EncodeError> {
+    let data = postcard::to_allocvec(t).unwrap();
+    encoding.encode_bytes(data.as_slice())
+}
+
+/**
+Decodes the bytes from some text, then deserializes them with postcard.
+*/
+pub fn decode_value<T: DeserializeOwned, E: Encoding>(
+    text: &str,
+    encoding: &E,
+) -> Result<T, EncodeError> {
+    let bytes = encoding.decode_bytes(text)?;
+    postcard::from_bytes(&bytes).map_err(EncodeError::Postcard)
+}
+
+/**
+Decodes bytes written by Encoding::encode_self_describing, without being told the encoding or its parameters.
+
+The encoding is recognized by its symbols: any diactrics make the text a curse, and otherwise any box-drawing
+graphemes make it boxes, leaving runes. From there, the header says how to read the rest.
+*/
+#[cfg_attr(
+    not(any(feature = "futhark", feature = "boxes", feature = "cursed")),
+    allow(unused_variables)
+)]
+pub fn decode_self_describing(text: &str) -> Result<(Format, Vec<u8>), EncodeError> {
+    #[cfg(feature = "cursed")]
+    if !crate::parse_curse_to_points(text).is_empty() {
+        return Ok((Format::Cursed, crate::read_curse_self_describing(text)?));
+    }
+    #[cfg(feature = "boxes")]
+    if text
+        .chars()
+        .any(|c| crate::Connections::from_grapheme(c.encode_utf8(&mut [0; 4])).is_some())
+    {
+        let (_, bytes) =
+            crate::bytes_from_boxes_self_describing(text).ok_or(EncodeError::Header)?;
+        return Ok((Format::Boxes, bytes));
+    }
+    #[cfg(feature = "futhark")]
+    if let Some(bytes) = crate::parse_runes_self_describing(text) {
+        return Ok((Format::Runes, bytes));
+    }
+    Err(EncodeError::Header)
+}
+
+/**
+The encodings choose_encoding picks between.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Format {
+    /// Diactrics over the carrier text, with the default CursedConfig.
+    #[cfg(feature = "cursed")]
+    Cursed,
+    /// Runes from the futhark alphabet.
+    #[cfg(feature = "futhark")]
+    Runes,
+    /// Box diagrams, with the default BoxLayoutConfig.
+    #[cfg(feature = "boxes")]
+    Boxes,
+}
+
+/**
+Encodes the data in whichever enabled encoding comes out shortest, as long as that's no more than budget chars. This
+is meant for fitting data into a message with a character limit, when it doesn't matter how the data is encoded.
+
+Each encoding's length is estimated first, so encodings which clearly can't fit aren't generated. Cursed text is
+preferred on a tie, then runes, then boxes. Returns None if nothing fits.
+*/
+// Only cursed text needs the carrier, and with no encodings at all, nothing is considered.
+#[cfg_attr(not(feature = "cursed"), allow(unused_variables))]
+#[cfg_attr(
+    not(any(feature = "futhark", feature = "boxes", feature = "cursed")),
+    allow(unused_mut)
+)]
+pub fn choose_encoding(data: &[u8], budget: usize, carrier: &str) -> Option<(Format, String)> {
+    let mut best: Option<(Format, String)> = None;
+    let mut consider = |format: Format, text: String| {
+        let length = text.chars().count();
+        if length <= budget
+            && best
+                .as_ref()
+                .is_none_or(|(_, b)| length < b.chars().count())
+        {
+            best = Some((format, text));
+        }
+    };
+    #[cfg(feature = "cursed")]
+    {
+        use unicode_segmentation::UnicodeSegmentation;
+
+        let config = crate::CursedConfig::new();
+        // The estimate counts each grapheme as one char, so emoji and the like add the rest of their chars.
+        let graphemes = carrier.graphemes(true).count();
+        let extra_chars = carrier.chars().count() - graphemes;
+        if config.estimated_len(graphemes, data.len()) + extra_chars <= budget {
+            if let Ok(curse) = config.generate_curse(carrier, data) {
+                consider(Format::Cursed, curse);
+            }
+        }
+    }
+    #[cfg(feature = "futhark")]
+    {
+        // Every futhark rune is a single char, and each one carries 5 bits.
+        if (data.len() * 8).div_ceil(5) <= budget {
+            consider(Format::Runes, crate::generate_runes(data, &crate::FUTHARK));
+        }
+    }
+    #[cfg(feature = "boxes")]
+    {
+        // Every cell of the layout takes at least one char.
+        if let Some(layout) = crate::layout_byte_length(data.len(), None) {
+            if layout.width() * layout.height() <= budget {
+                consider(Format::Boxes, layout.display_bytes(data));
+            }
+        }
+    }
+    best
+}
+
+#[cfg(all(test, any(feature = "futhark", feature = "boxes", feature = "cursed")))]
+mod encoding_tests {
+    use super::*;
+
+    fn round_trip<E: Encoding>(encoding: &E) {
+        let value = ("Encoded".to_string(), 42u32, vec![1u8, 2, 3]);
+        let encoded = encode_value(&value, encoding).unwrap();
+        assert_eq!(
+            decode_value::<(String, u32, Vec<u8>), E>(&encoded, encoding).unwrap(),
+            value
+        );
+    }
+
+    #[cfg(feature = "futhark")]
+    #[test]
+    fn test_futhark_round_trip() {
+        round_trip(&FutharkEncoding(crate::FUTHARK.clone()));
+        round_trip(&FutharkEncoding(crate::ALPHA_NUM.clone()));
+    }
+
+    #[cfg(feature = "boxes")]
+    #[test]
+    fn test_boxes_round_trip() {
+        round_trip(&BoxEncoding::default());
+    }
+
+    #[cfg(feature = "cursed")]
+    #[test]
+    fn test_cursed_round_trip() {
+        round_trip(&CursedEncoding(
+            crate::CursedConfig::new(),
+            "Some perfectly ordinary cover text".to_string(),
+        ));
+    }
+
+    #[test]
+    fn test_self_describing() {
+        let data = b"Described by its header".to_vec();
+        let mut encoded = Vec::new();
+        #[cfg(feature = "futhark")]
+        {
+            for alphabet in [&crate::FUTHARK, &crate::ALPHA_NUM] {
+                let encoding = FutharkEncoding(alphabet.clone());
+                encoded.push((
+                    Format::Runes,
+                    encoding.encode_self_describing(&data).unwrap(),
+                ));
+            }
+            let custom =
+                FutharkEncoding(crate::Alphabet::new("0123456789abcdefghijklmnopqrstuv").unwrap());
+            assert!(matches!(
+                custom.encode_self_describing(&data),
+                Err(EncodeError::Header)
+            ));
+        }
+        #[cfg(feature = "boxes")]
+        for style in crate::BoxStyle::ALL {
+            let encoding = BoxEncoding(crate::BoxLayoutConfig {
+                style,
+                ..Default::default()
+            });
+            let boxes = encoding.encode_self_describing(&data).unwrap();
+            assert_eq!(
+                crate::bytes_from_boxes_self_describing(&boxes),
+                Some((style, data.clone()))
+            );
+            encoded.push((Format::Boxes, boxes));
+        }
+        #[cfg(feature = "cursed")]
+        {
+            let carrier = "Some perfectly ordinary cover text".to_string();
+            for config in [
+                crate::CursedConfig::new(),
+                crate::CursedConfig::new().with_checksum(),
+                crate::CursedConfig::new().with_diacritic_set(crate::DiacriticSet::extended()),
+                crate::CursedConfig::new()
+                    .normalization_safe(true)
+                    .with_checksum(),
+            ] {
+                let encoding = CursedEncoding(config, carrier.clone());
+                encoded.push((
+                    Format::Cursed,
+                    encoding.encode_self_describing(&data).unwrap(),
+                ));
+            }
+        }
+        for (format, text) in encoded {
+            assert_eq!(
+                decode_self_describing(&text).unwrap(),
+                (format, data.clone())
+            );
+        }
+        assert!(matches!(
+            decode_self_describing("   "),
+            Err(EncodeError::Header)
+        ));
+    }
+
+    #[cfg(feature = "cursed")]
+    #[test]
+    fn test_cursed_short_cover() {
+        let encoding = CursedEncoding(
+            crate::CursedConfig::new().max_diactrics_per_letter(1),
+            "a".to_string(),
+        );
+        assert!(matches!(
+            encode_value(&"Too long for the cover", &encoding),
+            Err(EncodeError::Curse(_))
+        ));
+    }
+
+    #[cfg(all(feature = "futhark", feature = "boxes", feature = "cursed"))]
+    #[test]
+    fn test_choose_encoding() {
+        let data = b"Fit this somewhere";
+        let (format, text) = choose_encoding(data, 2000, "Hi").unwrap();
+        assert_eq!(format, Format::Cursed);
+        assert_eq!(
+            crate::bytes_from_curse_with_set(&text, &crate::DiacriticSet::basic()).unwrap(),
+            data
+        );
+        // Without a carrier there's nothing to curse, so the runes are shortest.
+        let (format, text) = choose_encoding(data, 2000, "").unwrap();
+        assert_eq!(format, Format::Runes);
+        assert_eq!(text.chars().count(), 29);
+        assert_eq!(
+            crate::bytes_from_runes(&text, &crate::FUTHARK)[..data.len()],
+            data[..]
+        );
+        assert_eq!(choose_encoding(data, 29, "").unwrap().0, Format::Runes);
+        assert_eq!(choose_encoding(data, 28, ""), None);
+        // A long carrier makes the curse longer than the runes.
+        assert_eq!(
+            choose_encoding(data, 2000, "A much longer message")
+                .unwrap()
+                .0,
+            Format::Runes
+        );
+        assert_eq!(choose_encoding(data, 28, "A much longer message"), None);
+    }
+}
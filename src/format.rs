@@ -0,0 +1,141 @@
+use alloc::vec::Vec;
+
+use serde::{de::DeserializeOwned, Serialize};
+
+/**
+The serialization format used to turn a value into bytes, before those bytes are encoded.
+
+The format is not recorded in the encoded output, so the decoder must be told which one was used.
+*/
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SerdeFormat {
+    /// Compact binary postcard encoding. This is what the default helpers use.
+    #[default]
+    Postcard,
+    /// JSON text, for interop with decoders that don't speak postcard.
+    Json,
+}
+
+impl SerdeFormat {
+    pub fn to_bytes<T: Serialize>(self, t: &T) -> Vec<u8> {
+        match self {
+            SerdeFormat::Postcard => postcard::to_allocvec(t).unwrap(),
+            SerdeFormat::Json => serde_json::to_vec(t).unwrap(),
+        }
+    }
+
+    pub fn from_bytes<T: DeserializeOwned>(self, bytes: &[u8]) -> Option<T> {
+        match self {
+            SerdeFormat::Postcard => postcard::from_bytes(bytes).ok(),
+            SerdeFormat::Json => serde_json::from_slice(bytes).ok(),
+        }
+    }
+}
+
+/**
+Reasons decoded bytes could not be deserialized with postcard, split by whether more bytes might have helped.
+*/
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The bytes ran out partway through the value, so the text may have been cut off when it was copied.
+    Truncated(postcard::Error),
+    /// The bytes don't match the requested type, so the text likely holds a different schema.
+    Mismatch(postcard::Error),
+}
+
+impl DecodeError {
+    /**
+    The underlying postcard error.
+    */
+    pub fn postcard_error(&self) -> &postcard::Error {
+        match self {
+            DecodeError::Truncated(err) | DecodeError::Mismatch(err) => err,
+        }
+    }
+
+    pub fn is_truncated(&self) -> bool {
+        matches!(self, DecodeError::Truncated(_))
+    }
+}
+
+impl From<postcard::Error> for DecodeError {
+    fn from(err: postcard::Error) -> Self {
+        match err {
+            postcard::Error::DeserializeUnexpectedEnd => DecodeError::Truncated(err),
+            _ => DecodeError::Mismatch(err),
+        }
+    }
+}
+
+impl core::fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            DecodeError::Truncated(err) => write!(f, "data ends too soon: {}", err),
+            DecodeError::Mismatch(err) => write!(f, "data does not match the type: {}", err),
+        }
+    }
+}
+
+impl core::error::Error for DecodeError {}
+
+/**
+Deserializes postcard bytes, classifying any failure as a DecodeError.
+*/
+#[cfg(any(feature = "futhark", feature = "boxes", feature = "cursed"))]
+pub(crate) fn decode_postcard<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, DecodeError> {
+    Ok(postcard::from_bytes(bytes)?)
+}
+
+/**
+Appends a length to a vector of bytes as a varint, 7 bits per byte, with the high bit set on all but the last.
+*/
+#[cfg(any(feature = "futhark", feature = "boxes", feature = "cursed"))]
+pub(crate) fn push_length_bytes(mut length: usize, bytes: &mut Vec<u8>) {
+    loop {
+        let byte = (length & 0x7f) as u8;
+        length >>= 7;
+        if length == 0 {
+            bytes.push(byte);
+            return;
+        }
+        bytes.push(byte | 0x80);
+    }
+}
+
+/**
+Reads a varint length from the front of a slice of bytes, returning the length and the number of bytes consumed.
+*/
+#[cfg(any(feature = "futhark", feature = "boxes", feature = "cursed"))]
+pub(crate) fn take_length_bytes(bytes: &[u8]) -> Option<(usize, usize)> {
+    let mut length: usize = 0;
+    for (idx, byte) in bytes.iter().enumerate() {
+        let shift = idx * 7;
+        if shift >= usize::BITS as usize {
+            return None;
+        }
+        length |= ((*byte & 0x7f) as usize) << shift;
+        if byte & 0x80 == 0 {
+            return Some((length, idx + 1));
+        }
+    }
+    None
+}
+
+/**
+CRC-16/CCITT-FALSE, which is plenty to catch a dropped or substituted rune or diactric.
+*/
+#[cfg(any(feature = "futhark", feature = "cursed"))]
+pub(crate) fn crc16(bytes: &[u8]) -> u16 {
+    let mut crc: u16 = 0xffff;
+    for byte in bytes {
+        crc ^= (*byte as u16) << 8;
+        for _ in 0..8 {
+            if crc & 0x8000 != 0 {
+                crc = (crc << 1) ^ 0x1021;
+            } else {
+                crc <<= 1;
+            }
+        }
+    }
+    crc
+}
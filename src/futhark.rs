@@ -1,233 +1,2075 @@
-use serde::{de::DeserializeOwned, Serialize};
-use unicode_segmentation::UnicodeSegmentation;
-
-pub const FUTHARK: &'static str = include_str!("../data/alphabet.txt");
-pub const ALPHA_NUM: &'static str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ123456";
-
-// DECODING!
-/**
-This function takes a string of runes and converts it to a vector of numbers between 0 and 31.
-
-The function takes two arguments:
-
-   * runes: A string of runes to convert.
-
-   * futhark: A boolean value that determines whether to use the Futhark alphabet or plain ASCII.
-*/
-pub fn parse_runes_to_points(runes: &str, alphabet: &str) -> Vec<u8> {
-    let mut results = Vec::new();
-    for rune in runes.graphemes(true) {
-        let mut alphabet = alphabet.graphemes(true);
-        if let Some(idx) = alphabet.position(|alpha| alpha == rune) {
-            results.push(idx as u8);
-            if idx == 32 {
-                break;
-            }
-        }
-    }
-    results
-}
-
-/**
-This function takes a vector of numbers between 0 and 31 and converts it to a vector of bytes.
-
-This treats the numbers as a series of 5-bit values, and packs them into bytes.
-*/
-pub fn points_to_bytes(points: Vec<u8>) -> Vec<u8> {
-    let mut results = Vec::new();
-    let mut bits: u32 = 0;
-    let mut offset = 0;
-    for point in points {
-        if offset == 0 {
-            bits = point as u32;
-            offset = 5;
-        } else {
-            bits |= (point as u32) << offset;
-            offset += 5;
-        }
-        if offset >= 8 {
-            results.push((bits & 0xff) as u8);
-            bits >>= 8;
-            offset -= 8;
-        }
-    }
-    results
-}
-
-/**
-This function takes a string of runes and converts it to a vector of bytes, for further parsing.
-*/
-pub fn parse_runes(runes: &str, alphabet: &str) -> Vec<u8> {
-    let points = parse_runes_to_points(runes, alphabet);
-    points_to_bytes(points)
-}
-
-/**
-Returns a deserialized value from a string of runes.
-
-This function takes two arguments:
-
-   * runes: A string of runes to convert.
-
-   * futhark: A boolean value that determines whether to use the Futhark alphabet or plain ASCII.
-*/
-pub fn read_from_runes<T: DeserializeOwned>(runes: &str, alphabet: &str) -> Option<T> {
-    let bytes = parse_runes(runes, alphabet);
-    postcard::from_bytes(&bytes).ok()
-}
-
-// ENCODING!
-/**
-This function takes a vector of bytes and converts it to a vector of numbers between 0 and 31.
-
-This treats the bytes as a series of 8-bit values, and repacks them into 5-bit values.
- */
-pub fn bytes_to_points(bytes: &[u8]) -> Vec<u8> {
-    let mut results = Vec::new();
-    let mut bits: u32 = 0;
-    let mut offset = 0;
-    for byte in bytes {
-        bits |= (*byte as u32) << offset;
-        offset += 8;
-        while offset >= 5 {
-            results.push((bits & 0x1f) as u8);
-            bits >>= 5;
-            offset -= 5;
-        }
-    }
-    if offset != 0 {
-        results.push(bits as u8);
-    }
-    results
-}
-
-/**
-This function takes a vector of bytes and converts it to a Unicode String of runes.
-
-This function takes two arguments:
-
-   * bytes: A vector of bytes to convert.
-
-   * futhark: A boolean value that determines whether to use the Futhark alphabet or plain ASCII.
-*/
-pub fn generate_runes(bytes: &[u8], alphabet: &str) -> String {
-    let points = bytes_to_points(bytes);
-    points
-        .iter()
-        .map(|point| alphabet.graphemes(true).nth(*point as usize).unwrap())
-        .collect()
-}
-
-fn simple_generate_runes_ascii(bytes: &[u8]) -> String {
-    let alphabet = "abcdefghijklmnopqrstuvwxyz123456";
-    let points = bytes_to_points(bytes);
-    points
-        .iter()
-        .map(|point| alphabet.bytes().nth(*point as usize).unwrap() as char)
-        .collect::<String>()
-}
-
-pub fn create_runes<T: Serialize>(t: &T, alphabet: &str) -> String {
-    let data = postcard::to_allocvec(t).unwrap();
-    generate_runes(data.as_slice(), alphabet)
-}
-
-#[cfg(test)]
-mod runes_tests {
-    use super::*;
-
-    #[test]
-    fn test_parse_runes_to_points() {
-        assert_eq!(parse_runes_to_points("ᚠᚢ", FUTHARK), vec![0, 1]);
-        assert_eq!(parse_runes_to_points("ᚦᚨ", FUTHARK), vec![2, 3]);
-        assert_eq!(parse_runes_to_points("AB", ALPHA_NUM), vec![0, 1]);
-        assert_eq!(parse_runes_to_points("CD", ALPHA_NUM), vec![2, 3]);
-    }
-
-    #[test]
-    fn test_parse_runes() {
-        assert_eq!(parse_runes("ᚠᚠ", FUTHARK), vec![0b00000]);
-        assert_eq!(parse_runes("ᚢᚠᛌ", FUTHARK), vec![0b00001]);
-        assert_eq!(parse_runes("ᚠᛁᚢᚠ", FUTHARK), vec![64, 5]);
-        assert_eq!(parse_runes("AKBA", ALPHA_NUM), vec![64, 5]);
-        assert_eq!(
-            parse_runes("AKBAAKBAAKBA", ALPHA_NUM),
-            vec![64, 5, 0, 84, 0, 64, 5]
-        );
-    }
-
-    #[test]
-    fn test_generate_runes() {
-        assert_eq!(generate_runes(&[0b00000000], FUTHARK), "ᚠᚠ");
-        assert_eq!(generate_runes(&[0b00000001], FUTHARK), "ᚢᚠ");
-        assert_eq!(generate_runes(&[0b100000], FUTHARK), "ᚠᚢ");
-        assert_eq!(generate_runes(&[64, 5], ALPHA_NUM), "AKBA");
-        assert_eq!(
-            generate_runes(&[64, 5, 0, 84, 0, 64, 5], FUTHARK),
-            "ᚠᛁᚢᚠᚠᛁᚢᚠᚠᛁᚢᚠ"
-        );
-    }
-}
-
-#[cfg(test)]
-mod cac_tests {
-    use super::*;
-    use serde::{Deserialize, Serialize};
-
-    #[derive(Serialize, Deserialize, PartialEq, Debug)]
-    struct TestStruct {
-        comments: String,
-        code: u32,
-    }
-
-    #[test]
-    fn test_bytes_and_such() {
-        let test = TestStruct {
-            comments: "Hello".to_string(),
-            code: 42,
-        };
-        assert_eq!(test.comments.as_bytes(), b"Hello");
-        assert_eq!(test.code.to_be_bytes(), [0, 0, 0, 42]);
-        assert_eq!(postcard::to_allocvec(&test).unwrap(), b"\x05Hello*");
-        assert_eq!(
-            create_runes(&serde_json::to_string(&test).unwrap().as_str(), FUTHARK),
-            "ᚪᚡᚪᚱᛖᛒᚩᛈᛈᛃᚥᛁᚷᛟᛒᛉᛗᛗᚺᛚᚨᛒᚠᚾᚲᚨᚥᚡᛞᛟᚾᚱᛇᛒᚺᚷᛞᛟᛒᛇᚲᛗᚺᛚᚨᚤᚺᚷᚩᚨ"
-        );
-    }
-
-    #[test]
-    fn test_points() {
-        let hello = b"Hello";
-        assert_eq!(bytes_to_points(hello), vec![8, 10, 25, 24, 6, 22, 29, 13]);
-        let hello_str = b"\x05Hello*";
-        assert_eq!(
-            bytes_to_points(hello_str),
-            vec![5, 0, 18, 10, 6, 22, 17, 13, 15, 19, 10, 0]
-        );
-    }
-
-    #[test]
-    fn test_base_32() {
-        let test = TestStruct {
-            comments: "Hello".to_string(),
-            code: 42,
-        };
-        assert_eq!(
-            simple_generate_runes_ascii(&postcard::to_allocvec(&test).unwrap()),
-            "FASKGWRNPTKA"
-        );
-    }
-
-    #[test]
-    fn test_create_runes() {
-        let test = TestStruct {
-            comments: "Hello".to_string(),
-            code: 42,
-        };
-        // Magic!
-        assert_eq!(create_runes(&test, FUTHARK), "ᚲᚠᛖᛁᚷᛞᛒᛈᛊᛗᛁᚠ");
-        // Faskgwarniptaka!
-        assert_eq!(create_runes(&test, ALPHA_NUM), "FASKGWRNPTKA");
-        assert_eq!(create_runes(&"C+c", FUTHARK), "ᚨᚡᛏᛞᛖᛒᚢ");
-    }
-}
+use alloc::borrow::Cow;
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::io::{Read, Write};
+
+use once_cell::race::OnceBox;
+use serde::{de::DeserializeOwned, Serialize};
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::{
+    crc16, decode_postcard, push_length_bytes, take_length_bytes, DecodeError, SerdeFormat,
+};
+
+/// The runes of data/alphabet.txt: the 32 runes of FUTHARK, followed by FUTHARK_STOP.
+const FUTHARK_RUNES: &str = include_str!("../data/alphabet.txt");
+/// The rune data/alphabet.txt ends with, which once marked the end of the runes. It isn't one of the 32 data runes, so
+/// parsing skips it like any other grapheme outside the alphabet.
+const FUTHARK_STOP: &str = "\u{16CC}";
+pub static FUTHARK: Alphabet = Alphabet(Cow::Borrowed(
+    FUTHARK_RUNES
+        .split_at(FUTHARK_RUNES.len() - FUTHARK_STOP.len())
+        .0,
+));
+pub static ALPHA_NUM: Alphabet = Alphabet(Cow::Borrowed("ABCDEFGHIJKLMNOPQRSTUVWXYZ123456"));
+
+/// The lookup table for FUTHARK, built the first time runes are parsed with it.
+static FUTHARK_DECODER: OnceBox<RuneDecoder<'static>> = OnceBox::new();
+
+/**
+Errors produced by the checked rune functions.
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RuneError {
+    /// A grapheme was found which is not part of the alphabet. The index is a byte offset into the runes.
+    UnknownGrapheme { index: usize, grapheme: String },
+    /// A length prefix promised more bytes than the runes hold.
+    Truncated,
+    /// The bytes of an item could not be deserialized.
+    Deserialize(postcard::Error),
+    /// The alphabet does not have enough graphemes to represent every point.
+    AlphabetTooSmall { needed: usize, got: usize },
+}
+
+impl core::fmt::Display for RuneError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            RuneError::UnknownGrapheme { index, grapheme } => {
+                write!(f, "unknown grapheme {:?} at byte {}", grapheme, index)
+            }
+            RuneError::Truncated => write!(f, "runes are shorter than their length prefix"),
+            RuneError::Deserialize(e) => write!(f, "could not deserialize runes: {}", e),
+            RuneError::AlphabetTooSmall { needed, got } => {
+                write!(f, "alphabet needs {} graphemes, but has {}", needed, got)
+            }
+        }
+    }
+}
+
+impl core::error::Error for RuneError {}
+
+/**
+Errors produced when validating an alphabet.
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AlphabetError {
+    /// The alphabet does not have exactly 32 graphemes.
+    WrongLength { expected: usize, got: usize },
+    /// The same grapheme appears more than once, so decoding would be ambiguous.
+    DuplicateGrapheme { grapheme: String },
+}
+
+impl core::fmt::Display for AlphabetError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            AlphabetError::WrongLength { expected, got } => {
+                write!(f, "alphabet needs {} graphemes, but has {}", expected, got)
+            }
+            AlphabetError::DuplicateGrapheme { grapheme } => {
+                write!(f, "alphabet contains {:?} more than once", grapheme)
+            }
+        }
+    }
+}
+
+impl core::error::Error for AlphabetError {}
+
+/**
+Errors produced when reading runes with an integrity check.
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IntegrityError {
+    /// The length prefix or checksum is missing, usually because the runes were truncated.
+    Malformed,
+    /// The checksum did not match the payload, so at least one rune was changed.
+    ChecksumMismatch { expected: u16, found: u16 },
+    /// The payload passed the checksum, but could not be deserialized.
+    Deserialize(postcard::Error),
+}
+
+impl core::fmt::Display for IntegrityError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            IntegrityError::Malformed => write!(f, "runes are missing their length or checksum"),
+            IntegrityError::ChecksumMismatch { expected, found } => write!(
+                f,
+                "checksum mismatch: expected {:#06x}, found {:#06x}",
+                expected, found
+            ),
+            IntegrityError::Deserialize(e) => write!(f, "could not deserialize runes: {}", e),
+        }
+    }
+}
+
+impl core::error::Error for IntegrityError {}
+
+/**
+A validated alphabet of exactly 32 distinct graphemes, one for each 5-bit point.
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Alphabet(Cow<'static, str>);
+
+impl Alphabet {
+    /**
+    Checks that the given string has exactly 32 graphemes, with no duplicates.
+    */
+    pub fn new(alphabet: &str) -> Result<Self, AlphabetError> {
+        let mut seen = Vec::new();
+        for grapheme in alphabet.graphemes(true) {
+            if seen.contains(&grapheme) {
+                return Err(AlphabetError::DuplicateGrapheme {
+                    grapheme: grapheme.to_string(),
+                });
+            }
+            seen.push(grapheme);
+        }
+        if seen.len() != 32 {
+            return Err(AlphabetError::WrongLength {
+                expected: 32,
+                got: seen.len(),
+            });
+        }
+        Ok(Alphabet(Cow::Owned(alphabet.to_string())))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    pub fn graphemes(&self) -> unicode_segmentation::Graphemes<'_> {
+        self.0.graphemes(true)
+    }
+
+    /**
+    Returns the point for the given grapheme, if it is part of the alphabet.
+    */
+    pub fn position(&self, grapheme: &str) -> Option<u8> {
+        self.graphemes()
+            .position(|alpha| alpha == grapheme)
+            .map(|idx| idx as u8)
+    }
+}
+
+/**
+Shuffles the graphemes of an alphabet by a seed, so the same bytes become different runes for each seed. Runes made
+with the permuted alphabet can only be read with the same permutation, so keep the seed alongside the base alphabet.
+
+This is a scrambled alphabet for casual obfuscation, not encryption. With only 32 graphemes and a simple shuffle,
+anyone with a few samples of known data can recover the permutation, so don't use it to protect secrets.
+*/
+pub fn permuted_alphabet(base: &Alphabet, seed: u64) -> Alphabet {
+    let mut graphemes: Vec<&str> = base.graphemes().collect();
+    // A Fisher-Yates shuffle driven by SplitMix64, which is fixed here so permutations never change between versions.
+    let mut state = seed;
+    for idx in (1..graphemes.len()).rev() {
+        state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^= z >> 31;
+        graphemes.swap(idx, (z % (idx as u64 + 1)) as usize);
+    }
+    Alphabet(Cow::Owned(graphemes.concat()))
+}
+
+// DECODING!
+/**
+This function takes a string of runes and converts it to a vector of numbers between 0 and 31.
+
+The function takes two arguments:
+
+   * runes: A string of runes to convert.
+
+   * alphabet: The alphabet to use, such as FUTHARK or ALPHA_NUM.
+
+Any grapheme which isn't in the alphabet is skipped, so runes can be broken up with spaces, newlines, or other
+separators, like those from create_runes_grouped, without changing the result.
+*/
+pub fn parse_runes_to_points(runes: &str, alphabet: &Alphabet) -> Vec<u8> {
+    if *alphabet == FUTHARK {
+        // The built-in alphabet is common enough to keep its lookup table around between calls.
+        return FUTHARK_DECODER
+            .get_or_init(|| Box::new(RuneDecoder::new(&FUTHARK)))
+            .decode_points(runes);
+    }
+    runes
+        .graphemes(true)
+        .filter_map(|rune| alphabet.position(rune))
+        .collect()
+}
+
+/**
+Options for parse_runes_to_points_with_options. The defaults match parse_runes_to_points.
+*/
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DecodeOptions<'a> {
+    /// A grapheme which ends the runes, like a delimiter after runes embedded in other text. Nothing from it onwards
+    /// is read.
+    pub stop_at: Option<&'a str>,
+    /// Graphemes which are easily mistaken for runes, and the runes they should be read as, like
+    /// futhark_confusions. Anything else is looked up as it is.
+    pub confusions: Option<&'a BTreeMap<&'a str, &'a str>>,
+}
+
+/**
+Lookalikes for the runes of FUTHARK, for reading runes which were copied by hand or through OCR. These are other runic
+variants and Latin letters which are drawn much like a FUTHARK rune.
+
+Letters are read as runes with this table, so it's best kept to text which is only meant to hold runes.
+*/
+pub fn futhark_confusions() -> BTreeMap<&'static str, &'static str> {
+    [
+        // Runic variants from other rows, which aren't part of FUTHARK.
+        ("ᚻ", "ᚺ"),
+        ("ᛐ", "ᛏ"),
+        ("ᛓ", "ᛒ"),
+        ("ᛔ", "ᛒ"),
+        ("ᛕ", "ᛈ"),
+        ("ᛘ", "ᛉ"),
+        ("ᛂ", "ᛁ"),
+        ("ᚭ", "ᚩ"),
+        ("ᚮ", "ᚩ"),
+        // Latin letters and symbols.
+        ("þ", "ᚦ"),
+        ("Þ", "ᚦ"),
+        ("F", "ᚠ"),
+        ("R", "ᚱ"),
+        ("P", "ᚹ"),
+        ("X", "ᚷ"),
+        ("B", "ᛒ"),
+        ("M", "ᛖ"),
+        ("I", "ᛁ"),
+        ("|", "ᛁ"),
+        ("<", "ᚲ"),
+        ("◇", "ᛜ"),
+    ]
+    .into_iter()
+    .collect()
+}
+
+/**
+Like parse_runes_to_points, but stops and repairs runes where the options say to.
+*/
+pub fn parse_runes_to_points_with_options(
+    runes: &str,
+    alphabet: &Alphabet,
+    options: &DecodeOptions,
+) -> Vec<u8> {
+    let runes = match options.stop_at {
+        Some(stop_at) => runes
+            .grapheme_indices(true)
+            .find(|(_, grapheme)| *grapheme == stop_at)
+            .map_or(runes, |(idx, _)| &runes[..idx]),
+        None => runes,
+    };
+    match options.confusions {
+        Some(confusions) => runes
+            .graphemes(true)
+            .map(|rune| confusions.get(rune).copied().unwrap_or(rune))
+            .filter_map(|rune| alphabet.position(rune))
+            .collect(),
+        None => parse_runes_to_points(runes, alphabet),
+    }
+}
+
+/**
+This function takes a vector of numbers between 0 and 31 and converts it to a vector of bytes.
+
+This treats the numbers as a series of 5-bit values, and packs them into bytes.
+*/
+pub fn points_to_bytes(points: Vec<u8>) -> Vec<u8> {
+    points_to_bytes_n(&points, 5)
+}
+
+/**
+This function takes a vector of points with the given bit width and converts it to a vector of bytes.
+
+The bit width must be between 1 and 8. Any trailing bits which do not fill a byte are dropped.
+*/
+pub fn points_to_bytes_n(points: &[u8], bits: usize) -> Vec<u8> {
+    assert!((1..=8).contains(&bits), "Invalid bit width: {}", bits);
+    let mut results = Vec::new();
+    let mut accumulator: u32 = 0;
+    let mut offset = 0;
+    for point in points {
+        accumulator |= ((*point as u32) & ((1 << bits) - 1)) << offset;
+        offset += bits;
+        while offset >= 8 {
+            results.push((accumulator & 0xff) as u8);
+            accumulator >>= 8;
+            offset -= 8;
+        }
+    }
+    results
+}
+
+/**
+Reads the raw bytes back out of a string of runes, mirroring generate_runes.
+
+The last rune may be padded with up to 4 zero bits, which are dropped, so the result is exactly the original data.
+*/
+pub fn bytes_from_runes(runes: &str, alphabet: &Alphabet) -> Vec<u8> {
+    let points = parse_runes_to_points(runes, alphabet);
+    points_to_bytes(points)
+}
+
+/**
+Reads at most max_bytes bytes from the start of the runes, the same as the start of bytes_from_runes, without looking
+at the runes after them. This is for peeking at a header, like a length or a type tag, before decoding everything.
+*/
+pub fn parse_runes_prefix(runes: &str, alphabet: &Alphabet, max_bytes: usize) -> Vec<u8> {
+    let mut results = Vec::with_capacity(max_bytes.min(runes.len()));
+    if max_bytes == 0 {
+        return results;
+    }
+    let mut accumulator: u32 = 0;
+    let mut offset = 0;
+    for point in runes
+        .graphemes(true)
+        .filter_map(|rune| alphabet.position(rune))
+    {
+        accumulator |= (point as u32) << offset;
+        offset += 5;
+        if offset >= 8 {
+            results.push((accumulator & 0xff) as u8);
+            if results.len() == max_bytes {
+                break;
+            }
+            accumulator >>= 8;
+            offset -= 8;
+        }
+    }
+    results
+}
+
+/**
+The old name of bytes_from_runes.
+*/
+#[deprecated(note = "renamed to bytes_from_runes, to match bytes_from_boxes and bytes_from_curse")]
+pub fn parse_runes(runes: &str, alphabet: &Alphabet) -> Vec<u8> {
+    bytes_from_runes(runes, alphabet)
+}
+
+/**
+Guesses which of the built-in alphabets a string of runes was written with.
+
+The alphabet which accounts for the most graphemes in the string wins. If no grapheme belongs to either alphabet, or
+both account for the same number, the result is ambiguous and None is returned.
+*/
+pub fn detect_alphabet(runes: &str) -> Option<&'static Alphabet> {
+    let mut futhark = 0;
+    let mut alpha_num = 0;
+    for rune in runes.graphemes(true) {
+        if FUTHARK.position(rune).is_some() {
+            futhark += 1;
+        }
+        if ALPHA_NUM.position(rune).is_some() {
+            alpha_num += 1;
+        }
+    }
+    match futhark.cmp(&alpha_num) {
+        core::cmp::Ordering::Greater => Some(&FUTHARK),
+        core::cmp::Ordering::Less => Some(&ALPHA_NUM),
+        core::cmp::Ordering::Equal => None,
+    }
+}
+
+/**
+This function takes a string of runes and converts it to a vector of bytes, like bytes_from_runes.
+
+Unlike bytes_from_runes, any grapheme which is not in the alphabet is reported as an error, rather than skipped.
+*/
+pub fn parse_runes_checked(runes: &str, alphabet: &Alphabet) -> Result<Vec<u8>, RuneError> {
+    let mut points = Vec::new();
+    for (index, rune) in runes.grapheme_indices(true) {
+        match alphabet.position(rune) {
+            Some(idx) => points.push(idx),
+            None => {
+                return Err(RuneError::UnknownGrapheme {
+                    index,
+                    grapheme: rune.to_string(),
+                })
+            }
+        }
+    }
+    Ok(points_to_bytes(points))
+}
+
+/**
+A decoder which looks up each rune in a precomputed table, rather than scanning the alphabet.
+
+This is worthwhile when decoding long strings of runes, or many strings with the same alphabet.
+*/
+pub struct RuneDecoder<'a> {
+    lookup: BTreeMap<&'a str, u8>,
+}
+
+impl<'a> RuneDecoder<'a> {
+    pub fn new(alphabet: &'a Alphabet) -> Self {
+        let lookup = alphabet
+            .graphemes()
+            .enumerate()
+            .map(|(idx, grapheme)| (grapheme, idx as u8))
+            .collect();
+        RuneDecoder { lookup }
+    }
+
+    /**
+    Builds a decoder for an alphabet which hasn't been validated, which must have exactly 32 distinct graphemes.
+    */
+    pub fn try_new(alphabet: &'a str) -> Result<Self, AlphabetError> {
+        let mut lookup = BTreeMap::new();
+        for (idx, grapheme) in alphabet.graphemes(true).enumerate() {
+            if lookup.insert(grapheme, idx as u8).is_some() {
+                return Err(AlphabetError::DuplicateGrapheme {
+                    grapheme: grapheme.to_string(),
+                });
+            }
+        }
+        if lookup.len() != 32 {
+            return Err(AlphabetError::WrongLength {
+                expected: 32,
+                got: lookup.len(),
+            });
+        }
+        Ok(RuneDecoder { lookup })
+    }
+
+    /**
+    Converts a string of runes to a vector of numbers between 0 and 31, skipping unknown graphemes.
+    */
+    pub fn decode_points(&self, runes: &str) -> Vec<u8> {
+        runes
+            .graphemes(true)
+            .filter_map(|rune| self.lookup.get(rune).copied())
+            .collect()
+    }
+
+    /**
+    Converts a string of runes to a vector of bytes, skipping unknown graphemes.
+    */
+    pub fn decode(&self, runes: &str) -> Vec<u8> {
+        points_to_bytes(self.decode_points(runes))
+    }
+}
+
+/**
+Returns a deserialized value from a string of runes.
+
+This function takes two arguments:
+
+   * runes: A string of runes to convert.
+
+   * alphabet: The alphabet to use, such as FUTHARK or ALPHA_NUM.
+*/
+pub fn read_from_runes<T: DeserializeOwned>(runes: &str, alphabet: &Alphabet) -> Option<T> {
+    try_read_from_runes(runes, alphabet).ok()
+}
+
+/**
+Reads a value from runes like read_from_runes, but explains why it couldn't be deserialized.
+
+A DecodeError::Truncated suggests the runes were cut off, while DecodeError::Mismatch suggests they hold another type.
+*/
+pub fn try_read_from_runes<T: DeserializeOwned>(
+    runes: &str,
+    alphabet: &Alphabet,
+) -> Result<T, DecodeError> {
+    decode_postcard(&bytes_from_runes(runes, alphabet))
+}
+
+// ENCODING!
+/**
+This function takes a vector of bytes and converts it to a vector of numbers between 0 and 31.
+
+This treats the bytes as a series of 8-bit values, and repacks them into 5-bit values.
+
+Every 5 bytes make exactly 8 points, so whole groups are unpacked with fixed shifts, and only the tail goes through
+bytes_to_points_n. The result is the same as bytes_to_points_n(bytes, 5), just faster on long inputs.
+ */
+pub fn bytes_to_points(bytes: &[u8]) -> Vec<u8> {
+    let mut results = Vec::with_capacity((bytes.len() * 8).div_ceil(5));
+    let mut groups = bytes.chunks_exact(5);
+    for group in &mut groups {
+        let bits = group
+            .iter()
+            .rev()
+            .fold(0u64, |bits, byte| (bits << 8) | *byte as u64);
+        results.extend((0..8).map(|point| ((bits >> (point * 5)) & 0x1f) as u8));
+    }
+    results.extend(bytes_to_points_n(groups.remainder(), 5));
+    results
+}
+
+/**
+This function takes a vector of bytes and converts it to a vector of points with the given bit width.
+
+The bit width must be between 1 and 8. With 4 bits, each byte becomes two hex nibbles, and with 6 bits every 3 bytes become 4 Base64-style points.
+ */
+pub fn bytes_to_points_n(bytes: &[u8], bits: usize) -> Vec<u8> {
+    assert!((1..=8).contains(&bits), "Invalid bit width: {}", bits);
+    let mask = (1 << bits) - 1;
+    let mut results = Vec::new();
+    let mut accumulator: u32 = 0;
+    let mut offset = 0;
+    for byte in bytes {
+        accumulator |= (*byte as u32) << offset;
+        offset += 8;
+        while offset >= bits {
+            results.push((accumulator & mask) as u8);
+            accumulator >>= bits;
+            offset -= bits;
+        }
+    }
+    if offset != 0 {
+        results.push(accumulator as u8);
+    }
+    results
+}
+
+/**
+This function takes a vector of bytes and converts it to a Unicode String of runes.
+
+This function takes two arguments:
+
+   * bytes: A vector of bytes to convert.
+
+   * alphabet: The alphabet to use, such as FUTHARK or ALPHA_NUM.
+*/
+pub fn generate_runes(bytes: &[u8], alphabet: &Alphabet) -> String {
+    let points = bytes_to_points(bytes);
+    points_to_runes(&points, alphabet)
+}
+
+/**
+Like generate_runes, for an alphabet which hasn't been validated. Only the first 32 graphemes are used, and if the
+alphabet has fewer than 32 graphemes, an AlphabetTooSmall error is returned.
+*/
+pub fn generate_runes_checked(bytes: &[u8], alphabet: &str) -> Result<String, RuneError> {
+    let graphemes = alphabet.graphemes(true).collect::<Vec<_>>();
+    if graphemes.len() < 32 {
+        return Err(RuneError::AlphabetTooSmall {
+            needed: 32,
+            got: graphemes.len(),
+        });
+    }
+    Ok(bytes_to_points(bytes)
+        .iter()
+        .map(|point| graphemes[*point as usize])
+        .collect())
+}
+
+/**
+How the unused bits at the end of the final rune are handled.
+*/
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PadMode {
+    /// The unused bits are zero, and nothing marks them as padding. This is what generate_runes does.
+    #[default]
+    Zero,
+    /// The unused bits are zero, and one more rune follows with the number of them, from 0 to 4. Decoding then checks
+    /// the runes end on a byte boundary, so a missing or extra rune is caught rather than read as data.
+    Recorded,
+}
+
+/**
+Options for generate_runes_with_options and parse_runes_with_options. The defaults match generate_runes.
+*/
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EncodeOptions<'a> {
+    pub pad: PadMode,
+    /// A grapheme from outside the alphabet which follows the runes, marking where they end, so several codes can
+    /// be written one after another.
+    pub sentinel: Option<&'a str>,
+    /// The number of runes on each line, so the runes form a rectangular block for scanning. The last line may be
+    /// shorter, and any sentinel follows it.
+    pub rows: Option<usize>,
+}
+
+impl EncodeOptions<'_> {
+    /**
+    Breaks the runes into lines of n runes each. Decoding skips the newlines, like any grapheme outside the alphabet.
+    */
+    pub fn rows_of(mut self, n: usize) -> Self {
+        self.rows = Some(n);
+        self
+    }
+}
+
+/**
+Like generate_runes, with the final rune padded, broken into rows, and followed by a sentinel as the options ask.
+
+Panics if the sentinel is a grapheme of the alphabet, since it would be read back as data.
+*/
+pub fn generate_runes_with_options(
+    bytes: &[u8],
+    alphabet: &Alphabet,
+    options: &EncodeOptions,
+) -> String {
+    let mut points = bytes_to_points(bytes);
+    if options.pad == PadMode::Recorded {
+        points.push((points.len() * 5 - bytes.len() * 8) as u8);
+    }
+    let mut runes = points_to_runes(&points, alphabet);
+    if let Some(rows) = options.rows {
+        runes = group_runes(runes, rows, "\n");
+    }
+    if let Some(sentinel) = options.sentinel {
+        assert!(
+            alphabet.position(sentinel).is_none(),
+            "Sentinel {} is part of the alphabet",
+            sentinel
+        );
+        runes.push_str(sentinel);
+    }
+    runes
+}
+
+/**
+Reads runes made by generate_runes_with_options with the same options.
+
+With PadMode::Recorded, returns None if the pad rune is missing, or doesn't leave a whole number of bytes. With a
+sentinel, only the runes before the first sentinel are read, and returns None if there is no sentinel.
+*/
+pub fn parse_runes_with_options(
+    runes: &str,
+    alphabet: &Alphabet,
+    options: &EncodeOptions,
+) -> Option<Vec<u8>> {
+    let runes = match options.sentinel {
+        Some(sentinel) => split_at_sentinel(runes, sentinel)?.0,
+        None => runes,
+    };
+    let mut points = parse_runes_to_points(runes, alphabet);
+    if options.pad == PadMode::Recorded {
+        let pad = points.pop()? as usize;
+        let bits = points.len() * 5;
+        if pad >= 5 || bits < pad || !(bits - pad).is_multiple_of(8) {
+            return None;
+        }
+    }
+    Some(points_to_bytes(points))
+}
+
+/**
+Reads every code from runes made by generate_runes_with_options with a sentinel, written one after another.
+
+Returns None if the options have no sentinel, if any code can't be read, or if runes follow the last sentinel, since
+that code was cut short.
+*/
+pub fn parse_runes_stream(
+    runes: &str,
+    alphabet: &Alphabet,
+    options: &EncodeOptions,
+) -> Option<Vec<Vec<u8>>> {
+    let sentinel = options.sentinel?;
+    let mut codes = Vec::new();
+    let mut rest = runes;
+    while let Some((code, after)) = split_at_sentinel(rest, sentinel) {
+        codes.push(parse_runes_with_options(
+            code,
+            alphabet,
+            &EncodeOptions {
+                sentinel: None,
+                ..*options
+            },
+        )?);
+        rest = after;
+    }
+    if !parse_runes_to_points(rest, alphabet).is_empty() {
+        return None;
+    }
+    Some(codes)
+}
+
+/**
+Splits runes around the first grapheme which is the sentinel, if there is one.
+*/
+fn split_at_sentinel<'a>(runes: &'a str, sentinel: &str) -> Option<(&'a str, &'a str)> {
+    let (idx, _) = runes
+        .grapheme_indices(true)
+        .find(|(_, grapheme)| *grapheme == sentinel)?;
+    Some((&runes[..idx], &runes[idx + sentinel.len()..]))
+}
+
+/**
+The alphabets which can be named in a self-describing header, indexed by their id.
+*/
+fn described_alphabets() -> [&'static Alphabet; 2] {
+    [&FUTHARK, &ALPHA_NUM]
+}
+
+/**
+Like generate_runes, but starts with a header rune naming the alphabet, so parse_runes_self_describing can read the
+runes without being told the alphabet. The header is the alphabet's rune for its id: 0 for FUTHARK, 1 for
+ALPHA_NUM.
+
+Returns None for any other alphabet, since it has no id.
+*/
+pub fn generate_runes_self_describing(bytes: &[u8], alphabet: &Alphabet) -> Option<String> {
+    let id = described_alphabets()
+        .iter()
+        .position(|described| *described == alphabet)?;
+    let mut points = vec![id as u8];
+    points.extend(bytes_to_points(bytes));
+    Some(points_to_runes(&points, alphabet))
+}
+
+/**
+Reads runes made by generate_runes_self_describing, with the alphabet named by the header.
+
+The first grapheme from any known alphabet must be a header; returns None if it isn't, or if there are no runes.
+*/
+pub fn parse_runes_self_describing(runes: &str) -> Option<Vec<u8>> {
+    let (idx, header) = runes.grapheme_indices(true).find(|(_, grapheme)| {
+        described_alphabets()
+            .iter()
+            .any(|alphabet| alphabet.position(grapheme).is_some())
+    })?;
+    let alphabet = described_alphabets()
+        .into_iter()
+        .enumerate()
+        .find(|(id, alphabet)| alphabet.position(header) == Some(*id as u8))?
+        .1;
+    Some(bytes_from_runes(&runes[idx + header.len()..], alphabet))
+}
+
+/**
+This function takes a vector of numbers between 0 and 31 and converts it to a Unicode String of runes.
+*/
+pub fn points_to_runes(points: &[u8], alphabet: &Alphabet) -> String {
+    let graphemes = alphabet.graphemes().collect::<Vec<_>>();
+    points
+        .iter()
+        .map(|point| graphemes[*point as usize])
+        .collect()
+}
+
+#[cfg(test)]
+fn simple_generate_runes_ascii(bytes: &[u8]) -> String {
+    let alphabet = ALPHA_NUM.as_str().as_bytes();
+    let points = bytes_to_points(bytes);
+    points
+        .iter()
+        .map(|point| alphabet[*point as usize] as char)
+        .collect::<String>()
+}
+
+pub fn create_runes<T: Serialize>(t: &T, alphabet: &Alphabet) -> String {
+    let data = postcard::to_allocvec(t).unwrap();
+    generate_runes(data.as_slice(), alphabet)
+}
+
+/**
+Serializes a value and appends its runes to out, producing the same runes as create_runes.
+
+The runes are written as postcard produces each byte, so no byte buffer or separate string is allocated. Reusing
+one string for many values in a loop avoids allocating per value.
+*/
+pub fn create_runes_into<T: Serialize>(t: &T, alphabet: &Alphabet, out: &mut String) {
+    let flavor = RuneFlavor {
+        graphemes: alphabet.graphemes().collect(),
+        out,
+        accumulator: 0,
+        offset: 0,
+    };
+    postcard::serialize_with_flavor(t, flavor).unwrap()
+}
+
+/**
+A postcard flavor which packs bytes into 5-bit points as they arrive, like bytes_to_points, and writes out each rune.
+*/
+struct RuneFlavor<'a, 'b> {
+    graphemes: Vec<&'a str>,
+    out: &'b mut String,
+    accumulator: u32,
+    offset: usize,
+}
+
+impl postcard::ser_flavors::Flavor for RuneFlavor<'_, '_> {
+    type Output = ();
+
+    fn try_push(&mut self, data: u8) -> postcard::Result<()> {
+        self.accumulator |= (data as u32) << self.offset;
+        self.offset += 8;
+        while self.offset >= 5 {
+            self.out
+                .push_str(self.graphemes[(self.accumulator & 0x1f) as usize]);
+            self.accumulator >>= 5;
+            self.offset -= 5;
+        }
+        Ok(())
+    }
+
+    fn finalize(self) -> postcard::Result<()> {
+        if self.offset != 0 {
+            self.out.push_str(self.graphemes[self.accumulator as usize]);
+        }
+        Ok(())
+    }
+}
+
+/**
+Like create_runes, but puts the separator between every group of runes, to make long strings easier for people to copy.
+
+A group of 0 leaves out separators entirely. The separator must not contain any graphemes from the alphabet, since
+bytes_from_runes and read_from_runes skip only the graphemes they don't recognize.
+*/
+pub fn create_runes_grouped<T: Serialize>(
+    t: &T,
+    alphabet: &Alphabet,
+    group: usize,
+    sep: &str,
+) -> String {
+    group_runes(create_runes(t, alphabet), group, sep)
+}
+
+/**
+Puts the separator between every group of runes, or returns them unchanged for a group of 0.
+*/
+fn group_runes(runes: String, group: usize, sep: &str) -> String {
+    if group == 0 {
+        return runes;
+    }
+    let mut grouped = String::with_capacity(runes.len() + runes.len() / group * sep.len());
+    for (idx, rune) in runes.graphemes(true).enumerate() {
+        if idx > 0 && idx % group == 0 {
+            grouped.push_str(sep);
+        }
+        grouped.push_str(rune);
+    }
+    grouped
+}
+
+/**
+Splices runes into a template in place of the first appearance of the token, for embedding runes in decorative text.
+Returns None if the template doesn't contain the token.
+
+The runes can be read back out exactly with extract_from_template. If the rest of the template has no graphemes from
+the alphabet, the filled template can also be read directly with bytes_from_runes or read_from_runes.
+*/
+pub fn fill_template(template: &str, token: &str, runes: &str) -> Option<String> {
+    let (prefix, suffix) = template.split_once(token)?;
+    Some([prefix, runes, suffix].concat())
+}
+
+/**
+Takes the runes back out of a template filled by fill_template, by removing the text of the template around the token.
+Returns None if the filled text doesn't start and end like the template.
+*/
+pub fn extract_from_template<'a>(filled: &'a str, template: &str, token: &str) -> Option<&'a str> {
+    let (prefix, suffix) = template.split_once(token)?;
+    filled.strip_prefix(prefix)?.strip_suffix(suffix)
+}
+
+/**
+An iterator which encodes bytes from another iterator as runes, one char at a time, like generate_runes.
+
+Runes are produced as soon as enough bits have been read, so bytes are only pulled from the inner iterator as needed.
+Every rune of FUTHARK and ALPHA_NUM is a single char, but a custom alphabet's graphemes can have several, which are
+yielded in turn, so collecting the chars always gives the same string as generate_runes.
+*/
+pub struct RuneIter<'a, I: Iterator<Item = u8>> {
+    bytes: I,
+    graphemes: Vec<&'a str>,
+    bits: u32,
+    offset: usize,
+    /// The chars left of the rune being yielded.
+    rune: core::str::Chars<'a>,
+}
+
+impl<'a, I: Iterator<Item = u8>> RuneIter<'a, I> {
+    fn next_rune(&mut self) -> Option<&'a str> {
+        while self.offset < 5 {
+            match self.bytes.next() {
+                Some(byte) => {
+                    self.bits |= (byte as u32) << self.offset;
+                    self.offset += 8;
+                }
+                // The final, partial rune takes whatever bits are left.
+                None if self.offset != 0 => {
+                    let rune = self.graphemes[self.bits as usize];
+                    self.bits = 0;
+                    self.offset = 0;
+                    return Some(rune);
+                }
+                None => return None,
+            }
+        }
+        let rune = self.graphemes[(self.bits & 0x1f) as usize];
+        self.bits >>= 5;
+        self.offset -= 5;
+        Some(rune)
+    }
+}
+
+impl<'a, I: Iterator<Item = u8>> Iterator for RuneIter<'a, I> {
+    type Item = char;
+
+    fn next(&mut self) -> Option<char> {
+        loop {
+            if let Some(c) = self.rune.next() {
+                return Some(c);
+            }
+            self.rune = self.next_rune()?.chars();
+        }
+    }
+}
+
+/**
+Adds encode_runes to iterators of bytes, e.g. bytes.into_iter().encode_runes(&FUTHARK).
+*/
+pub trait EncodeRunes: Iterator<Item = u8> + Sized {
+    fn encode_runes(self, alphabet: &Alphabet) -> RuneIter<'_, Self> {
+        RuneIter {
+            bytes: self,
+            graphemes: alphabet.graphemes().collect(),
+            bits: 0,
+            offset: 0,
+            rune: "".chars(),
+        }
+    }
+}
+
+impl<I: Iterator<Item = u8>> EncodeRunes for I {}
+
+/**
+A writer which encodes every byte written to it as runes, and writes those runes to the underlying writer.
+
+Runes are written as soon as enough bits are available, so the full output is never held in memory. The final,
+partial rune is written by flush, finish, or when the writer is dropped. Flushing ends the runes like finish does, so
+bytes written after a flush start a new run of runes, as though encoded by a separate generate_runes call.
+
+Runes the underlying writer hasn't taken yet are kept, and written before anything else. A failed write takes none
+of its bytes unless they were already encoded, and no rune is ever written twice.
+*/
+#[cfg(feature = "std")]
+pub struct RuneWriter<'a, W: Write> {
+    inner: Option<W>,
+    graphemes: Vec<&'a str>,
+    bits: u32,
+    offset: usize,
+    /// Encoded runes the underlying writer hasn't taken yet.
+    pending: Vec<u8>,
+}
+
+#[cfg(feature = "std")]
+impl<'a, W: Write> RuneWriter<'a, W> {
+    pub fn new(inner: W, alphabet: &'a Alphabet) -> Self {
+        RuneWriter {
+            inner: Some(inner),
+            graphemes: alphabet.graphemes().collect(),
+            bits: 0,
+            offset: 0,
+            pending: Vec::new(),
+        }
+    }
+
+    /**
+    Writes the trailing partial rune, if any, and returns the underlying writer.
+    */
+    pub fn finish(mut self) -> std::io::Result<W> {
+        self.write_tail()?;
+        let mut inner = self.inner.take().unwrap();
+        inner.flush()?;
+        Ok(inner)
+    }
+
+    fn write_tail(&mut self) -> std::io::Result<()> {
+        if self.offset != 0 {
+            let rune = self.graphemes[self.bits as usize];
+            self.pending.extend_from_slice(rune.as_bytes());
+            self.bits = 0;
+            self.offset = 0;
+        }
+        self.write_pending()
+    }
+
+    /**
+    Hands the pending runes to the underlying writer, keeping whatever it doesn't take.
+    */
+    fn write_pending(&mut self) -> std::io::Result<()> {
+        let Some(inner) = self.inner.as_mut() else {
+            return Ok(());
+        };
+        while !self.pending.is_empty() {
+            match inner.write(&self.pending) {
+                Ok(0) => return Err(std::io::ErrorKind::WriteZero.into()),
+                Ok(written) => {
+                    self.pending.drain(..written);
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'a, W: Write> Write for RuneWriter<'a, W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        // Runes left over from a failed write go first, and while they can't be written, no new bytes are taken.
+        self.write_pending()?;
+        for byte in buf {
+            self.bits |= (*byte as u32) << self.offset;
+            self.offset += 8;
+            while self.offset >= 5 {
+                let rune = self.graphemes[(self.bits & 0x1f) as usize];
+                self.pending.extend_from_slice(rune.as_bytes());
+                self.bits >>= 5;
+                self.offset -= 5;
+            }
+        }
+        // The bytes are taken once they're encoded. If their runes can't be written yet, they stay pending, and the
+        // next write or flush reports the error.
+        let _ = self.write_pending();
+        Ok(buf.len())
+    }
+
+    /**
+    Writes the trailing partial rune, then flushes the underlying writer. The partial rune is padded with zero bits,
+    so this ends the runes, like finish.
+    */
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.write_tail()?;
+        match self.inner.as_mut() {
+            Some(inner) => inner.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'a, W: Write> Drop for RuneWriter<'a, W> {
+    fn drop(&mut self) {
+        let _ = self.write_tail();
+    }
+}
+
+/**
+Encodes everything read from the reader as runes, like generate_runes, without reading it all into memory first.
+
+The input is read in chunks and packed into runes through a RuneWriter, so only the runes themselves are held.
+*/
+#[cfg(feature = "std")]
+pub fn create_runes_from_reader<R: Read>(
+    reader: &mut R,
+    alphabet: &Alphabet,
+) -> std::io::Result<String> {
+    let mut writer = RuneWriter::new(Vec::new(), alphabet);
+    std::io::copy(reader, &mut writer)?;
+    let runes = writer.finish()?;
+    // Only whole graphemes are ever written.
+    Ok(String::from_utf8(runes).unwrap())
+}
+
+/**
+Creates runes for a value, serialized with the given format instead of postcard.
+*/
+pub fn create_runes_with_format<T: Serialize>(
+    t: &T,
+    alphabet: &Alphabet,
+    format: SerdeFormat,
+) -> String {
+    let data = format.to_bytes(t);
+    generate_runes(data.as_slice(), alphabet)
+}
+
+/**
+Reads a value from runes made by create_runes_with_format. The format must match the one used to create the runes.
+*/
+pub fn read_from_runes_with_format<T: DeserializeOwned>(
+    runes: &str,
+    alphabet: &Alphabet,
+    format: SerdeFormat,
+) -> Option<T> {
+    let bytes = bytes_from_runes(runes, alphabet);
+    format.from_bytes(&bytes)
+}
+
+// FRAMING!
+/**
+Pushes a length as a varint of points. Each point carries 4 bits of the length, with the 5th bit set when more points follow.
+*/
+fn push_length_points(mut length: usize, points: &mut Vec<u8>) {
+    loop {
+        let point = (length & 0xf) as u8;
+        length >>= 4;
+        if length == 0 {
+            points.push(point);
+            return;
+        }
+        points.push(point | 0x10);
+    }
+}
+
+/**
+Reads a varint length from the front of a vector of points, returning the length and the number of points consumed.
+*/
+fn take_length_points(points: &[u8]) -> Option<(usize, usize)> {
+    let mut length: usize = 0;
+    for (idx, point) in points.iter().enumerate() {
+        let shift = idx * 4;
+        if shift >= usize::BITS as usize {
+            return None;
+        }
+        length |= ((*point & 0xf) as usize) << shift;
+        if point & 0x10 == 0 {
+            return Some((length, idx + 1));
+        }
+    }
+    None
+}
+
+/**
+This function takes a vector of bytes and converts it to runes, prefixed with the byte length.
+
+The length lets parse_runes_framed recover exactly the original bytes, regardless of any padding in the final rune.
+*/
+pub fn generate_runes_framed(bytes: &[u8], alphabet: &Alphabet) -> String {
+    let mut points = Vec::new();
+    push_length_points(bytes.len(), &mut points);
+    points.extend(bytes_to_points(bytes));
+    points_to_runes(&points, alphabet)
+}
+
+/**
+This function takes a string of runes made by generate_runes_framed and converts it back to the original bytes.
+
+Returns None if the length prefix is missing, or there are fewer bytes than the prefix promises.
+*/
+pub fn parse_runes_framed(runes: &str, alphabet: &Alphabet) -> Option<Vec<u8>> {
+    let points = parse_runes_to_points(runes, alphabet);
+    let (length, consumed) = take_length_points(&points)?;
+    let mut bytes = points_to_bytes(points[consumed..].to_vec());
+    if bytes.len() < length {
+        return None;
+    }
+    bytes.truncate(length);
+    Some(bytes)
+}
+
+pub fn create_runes_framed<T: Serialize>(t: &T, alphabet: &Alphabet) -> String {
+    let data = postcard::to_allocvec(t).unwrap();
+    generate_runes_framed(data.as_slice(), alphabet)
+}
+
+pub fn read_from_runes_framed<T: DeserializeOwned>(runes: &str, alphabet: &Alphabet) -> Option<T> {
+    let bytes = parse_runes_framed(runes, alphabet)?;
+    postcard::from_bytes(&bytes).ok()
+}
+
+// MANY!
+/**
+Creates one string of runes for several values, each prefixed with its serialized length so they can be read back
+separately.
+*/
+pub fn create_runes_many<T: Serialize>(items: &[T], alphabet: &Alphabet) -> String {
+    let mut data = Vec::new();
+    for item in items {
+        let bytes = postcard::to_allocvec(item).unwrap();
+        push_length_bytes(bytes.len(), &mut data);
+        data.extend(bytes);
+    }
+    generate_runes(&data, alphabet)
+}
+
+/**
+Reads the values written by create_runes_many, in order.
+
+Runes cut off partway through an item are reported as truncated, but runes cut off between items can't be noticed.
+*/
+pub fn read_runes_many<T: DeserializeOwned>(
+    runes: &str,
+    alphabet: &Alphabet,
+) -> Result<Vec<T>, RuneError> {
+    let bytes = parse_runes_checked(runes, alphabet)?;
+    let mut remaining = bytes.as_slice();
+    let mut items = Vec::new();
+    while !remaining.is_empty() {
+        let (length, consumed) = take_length_bytes(remaining).ok_or(RuneError::Truncated)?;
+        remaining = &remaining[consumed..];
+        if remaining.len() < length {
+            return Err(RuneError::Truncated);
+        }
+        let (item, rest) = remaining.split_at(length);
+        items.push(postcard::from_bytes(item).map_err(RuneError::Deserialize)?);
+        remaining = rest;
+    }
+    Ok(items)
+}
+
+// CHECKSUMS!
+/**
+Creates runes for a value, followed by a CRC-16 of the serialized bytes.
+
+The runes are framed with the payload length, so the decoder knows where the payload ends and the checksum begins.
+*/
+pub fn create_runes_checked<T: Serialize>(t: &T, alphabet: &Alphabet) -> String {
+    let mut data = postcard::to_allocvec(t).unwrap();
+    let crc = crc16(&data);
+    let mut points = Vec::new();
+    push_length_points(data.len(), &mut points);
+    data.extend_from_slice(&crc.to_be_bytes());
+    points.extend(bytes_to_points(&data));
+    points_to_runes(&points, alphabet)
+}
+
+/**
+Reads a value from runes made by create_runes_checked, verifying the checksum before deserializing.
+*/
+pub fn read_from_runes_checked<T: DeserializeOwned>(
+    runes: &str,
+    alphabet: &Alphabet,
+) -> Result<T, IntegrityError> {
+    let points = parse_runes_to_points(runes, alphabet);
+    let (length, consumed) = take_length_points(&points).ok_or(IntegrityError::Malformed)?;
+    let bytes = points_to_bytes(points[consumed..].to_vec());
+    if bytes.len() < length + 2 {
+        return Err(IntegrityError::Malformed);
+    }
+    let (payload, checksum) = bytes.split_at(length);
+    let expected = u16::from_be_bytes([checksum[0], checksum[1]]);
+    let found = crc16(payload);
+    if expected != found {
+        return Err(IntegrityError::ChecksumMismatch { expected, found });
+    }
+    postcard::from_bytes(payload).map_err(IntegrityError::Deserialize)
+}
+
+#[cfg(test)]
+mod runes_tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_runes_to_points() {
+        assert_eq!(parse_runes_to_points("ᚠᚢ", &FUTHARK), vec![0, 1]);
+        assert_eq!(parse_runes_to_points("ᚦᚨ", &FUTHARK), vec![2, 3]);
+        assert_eq!(parse_runes_to_points("AB", &ALPHA_NUM), vec![0, 1]);
+        assert_eq!(parse_runes_to_points("CD", &ALPHA_NUM), vec![2, 3]);
+    }
+
+    #[test]
+    fn test_parse_runes_stop_at() {
+        let stop_at = DecodeOptions {
+            stop_at: Some("|"),
+            ..Default::default()
+        };
+        assert_eq!(
+            parse_runes_to_points_with_options("ᚠᚢ|ᚦᚨ", &FUTHARK, &stop_at),
+            vec![0, 1]
+        );
+        assert_eq!(
+            parse_runes_to_points_with_options("ABCD", &ALPHA_NUM, &stop_at),
+            vec![0, 1, 2, 3]
+        );
+        assert_eq!(
+            parse_runes_to_points_with_options("AB|CD", &ALPHA_NUM, &DecodeOptions::default()),
+            vec![0, 1, 2, 3]
+        );
+        let runes = format!("Code: {}. More text: AB", create_runes(&7u8, &ALPHA_NUM));
+        let points = parse_runes_to_points_with_options(
+            &runes[6..],
+            &ALPHA_NUM,
+            &DecodeOptions {
+                stop_at: Some("."),
+                ..Default::default()
+            },
+        );
+        assert_eq!(postcard::from_bytes(&points_to_bytes(points)), Ok(7u8));
+    }
+
+    #[test]
+    fn test_confusions() {
+        let confusions = futhark_confusions();
+        for (lookalike, rune) in &confusions {
+            assert_eq!(FUTHARK.position(lookalike), None);
+            assert!(FUTHARK.position(rune).is_some());
+        }
+        let value = ("Hand copied".to_string(), 99u8);
+        let runes = create_runes(&value, &FUTHARK);
+        // Copy the runes badly, swapping every rune which has a lookalike.
+        let copied: String = runes
+            .graphemes(true)
+            .map(|rune| {
+                confusions
+                    .iter()
+                    .find(|(_, intended)| **intended == rune)
+                    .map_or(rune, |(lookalike, _)| *lookalike)
+            })
+            .collect();
+        assert_ne!(copied, runes);
+        let options = DecodeOptions {
+            confusions: Some(&confusions),
+            ..Default::default()
+        };
+        let points = parse_runes_to_points_with_options(&copied, &FUTHARK, &options);
+        assert_eq!(points, parse_runes_to_points(&runes, &FUTHARK));
+        assert_eq!(postcard::from_bytes(&points_to_bytes(points)), Ok(value));
+        assert_ne!(
+            parse_runes_to_points(&copied, &FUTHARK),
+            parse_runes_to_points(&runes, &FUTHARK)
+        );
+    }
+
+    #[test]
+    fn test_futhark_lookup_matches_scan() {
+        let runes = format!(
+            "{} x\n{}ᚠ",
+            generate_runes(b"Cached", &FUTHARK),
+            FUTHARK.as_str()
+        );
+        let scanned: Vec<u8> = runes
+            .graphemes(true)
+            .filter_map(|rune| FUTHARK.position(rune))
+            .collect();
+        assert_eq!(parse_runes_to_points(&runes, &FUTHARK), scanned);
+        assert_eq!(parse_runes_to_points(&runes, &FUTHARK), scanned);
+    }
+
+    #[test]
+    fn test_bytes_from_runes() {
+        assert_eq!(bytes_from_runes("ᚠᚠ", &FUTHARK), vec![0b00000]);
+        assert_eq!(bytes_from_runes("ᚢᚠᛌ", &FUTHARK), vec![0b00001]);
+        assert_eq!(bytes_from_runes("ᚠᛁᚢᚠ", &FUTHARK), vec![64, 5]);
+        assert_eq!(bytes_from_runes("AKBA", &ALPHA_NUM), vec![64, 5]);
+        assert_eq!(
+            bytes_from_runes("AKBAAKBAAKBA", &ALPHA_NUM),
+            vec![64, 5, 0, 84, 0, 64, 5]
+        );
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_parse_runes() {
+        assert_eq!(parse_runes("ᚠᚠ", &FUTHARK), vec![0b00000]);
+        assert_eq!(parse_runes("ᚢᚠᛌ", &FUTHARK), vec![0b00001]);
+        assert_eq!(parse_runes("ᚠᛁᚢᚠ", &FUTHARK), vec![64, 5]);
+        assert_eq!(parse_runes("AKBA", &ALPHA_NUM), vec![64, 5]);
+        assert_eq!(
+            parse_runes("AKBAAKBAAKBA", &ALPHA_NUM),
+            vec![64, 5, 0, 84, 0, 64, 5]
+        );
+    }
+
+    #[test]
+    fn test_parse_runes_checked() {
+        assert_eq!(parse_runes_checked("ᚠᛁᚢᚠ", &FUTHARK), Ok(vec![64, 5]));
+        assert_eq!(parse_runes_checked("AKBA", &ALPHA_NUM), Ok(vec![64, 5]));
+        assert_eq!(
+            parse_runes_checked("ᚠᛁ ᚢᚠ", &FUTHARK),
+            Err(RuneError::UnknownGrapheme {
+                index: 6,
+                grapheme: " ".to_string()
+            })
+        );
+        assert_eq!(
+            parse_runes_checked("AKbA", &ALPHA_NUM),
+            Err(RuneError::UnknownGrapheme {
+                index: 2,
+                grapheme: "b".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn test_rune_decoder() {
+        let decoder = RuneDecoder::new(&FUTHARK);
+        assert_eq!(decoder.decode("ᚠᛁᚢᚠ"), vec![64, 5]);
+        assert_eq!(decoder.decode("ᚠᛁ ᚢᚠ"), bytes_from_runes("ᚠᛁ ᚢᚠ", &FUTHARK));
+        let decoder = RuneDecoder::new(&ALPHA_NUM);
+        assert_eq!(decoder.decode("AKBAAKBAAKBA"), vec![64, 5, 0, 84, 0, 64, 5]);
+        let decoder = RuneDecoder::try_new(ALPHA_NUM.as_str()).unwrap();
+        assert_eq!(decoder.decode("AKBAAKBAAKBA"), vec![64, 5, 0, 84, 0, 64, 5]);
+        assert_eq!(
+            RuneDecoder::try_new("ABC").err(),
+            Some(AlphabetError::WrongLength {
+                expected: 32,
+                got: 3
+            })
+        );
+        assert_eq!(
+            RuneDecoder::try_new("ABCDEFGHIJKLMNOPQRSTUVWXYZ12345A").err(),
+            Some(AlphabetError::DuplicateGrapheme {
+                grapheme: "A".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn test_alphabet() {
+        assert_eq!(Alphabet::new(FUTHARK.as_str()).as_ref(), Ok(&FUTHARK));
+        // The data file keeps its trailing stop rune, which isn't part of the alphabet.
+        assert_eq!(
+            FUTHARK_RUNES,
+            format!("{}{}", FUTHARK.as_str(), FUTHARK_STOP)
+        );
+        assert_eq!(FUTHARK.position(FUTHARK_STOP), None);
+        assert_eq!(Alphabet::new(ALPHA_NUM.as_str()).as_ref(), Ok(&ALPHA_NUM));
+        assert_eq!(
+            Alphabet::new("ABC").err(),
+            Some(AlphabetError::WrongLength {
+                expected: 32,
+                got: 3
+            })
+        );
+        assert_eq!(
+            Alphabet::new("ABCDEFGHIJKLMNOPQRSTUVWXYZ12345A").err(),
+            Some(AlphabetError::DuplicateGrapheme {
+                grapheme: "A".to_string()
+            })
+        );
+        assert_eq!(
+            Alphabet::new("0123456789ABCDEF").err(),
+            Some(AlphabetError::WrongLength {
+                expected: 32,
+                got: 16
+            })
+        );
+        assert_eq!(
+            Alphabet::new("ABCDEFGHIJKLMNOPQRSTUVWXYZ1234567").err(),
+            Some(AlphabetError::WrongLength {
+                expected: 32,
+                got: 33
+            })
+        );
+    }
+
+    #[test]
+    fn test_detect_alphabet() {
+        assert_eq!(detect_alphabet("ᚠᛁᚢᚠ"), Some(&FUTHARK));
+        assert_eq!(detect_alphabet("AKBA"), Some(&ALPHA_NUM));
+        assert_eq!(detect_alphabet("Code: ᚲᚠᛖᛁᚷᛞᛒᛈᛊᛗᛁᚠ"), Some(&FUTHARK));
+        assert_eq!(detect_alphabet(""), None);
+        assert_eq!(detect_alphabet("hello"), None);
+        assert_eq!(detect_alphabet("ᚠA"), None);
+    }
+
+    #[test]
+    fn test_generate_runes() {
+        assert_eq!(generate_runes(&[0b00000000], &FUTHARK), "ᚠᚠ");
+        assert_eq!(generate_runes(&[0b00000001], &FUTHARK), "ᚢᚠ");
+        assert_eq!(generate_runes(&[0b100000], &FUTHARK), "ᚠᚢ");
+        assert_eq!(generate_runes(&[64, 5], &ALPHA_NUM), "AKBA");
+        assert_eq!(
+            generate_runes(&[64, 5, 0, 84, 0, 64, 5], &FUTHARK),
+            "ᚠᛁᚢᚠᚠᛁᚢᚠᚠᛁᚢᚠ"
+        );
+    }
+
+    #[test]
+    fn test_generate_runes_small_alphabet() {
+        assert_eq!(
+            generate_runes_checked(&[255], "0123456789ABCDEF"),
+            Err(RuneError::AlphabetTooSmall {
+                needed: 32,
+                got: 16
+            })
+        );
+        assert_eq!(
+            generate_runes_checked(&[64, 5], ALPHA_NUM.as_str()),
+            Ok("AKBA".to_string())
+        );
+        // Graphemes past the first 32 are never used.
+        assert_eq!(
+            generate_runes_checked(&[64, 5, 0, 84, 0, 64, 5], FUTHARK_RUNES),
+            Ok(generate_runes(&[64, 5, 0, 84, 0, 64, 5], &FUTHARK))
+        );
+    }
+}
+
+#[cfg(test)]
+mod cac_tests {
+    use super::*;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    struct TestStruct {
+        comments: String,
+        code: u32,
+    }
+
+    #[test]
+    fn test_bytes_and_such() {
+        let test = TestStruct {
+            comments: "Hello".to_string(),
+            code: 42,
+        };
+        assert_eq!(test.comments.as_bytes(), b"Hello");
+        assert_eq!(test.code.to_be_bytes(), [0, 0, 0, 42]);
+        assert_eq!(postcard::to_allocvec(&test).unwrap(), b"\x05Hello*");
+        assert_eq!(
+            create_runes(&serde_json::to_string(&test).unwrap().as_str(), &FUTHARK),
+            "ᚪᚡᚪᚱᛖᛒᚩᛈᛈᛃᚥᛁᚷᛟᛒᛉᛗᛗᚺᛚᚨᛒᚠᚾᚲᚨᚥᚡᛞᛟᚾᚱᛇᛒᚺᚷᛞᛟᛒᛇᚲᛗᚺᛚᚨᚤᚺᚷᚩᚨ"
+        );
+    }
+
+    #[test]
+    fn test_points() {
+        let hello = b"Hello";
+        assert_eq!(bytes_to_points(hello), vec![8, 10, 25, 24, 6, 22, 29, 13]);
+        let hello_str = b"\x05Hello*";
+        assert_eq!(
+            bytes_to_points(hello_str),
+            vec![5, 0, 18, 10, 6, 22, 17, 13, 15, 19, 10, 0]
+        );
+    }
+
+    #[test]
+    fn test_grouped_points_match_scalar() {
+        for length in 0..64usize {
+            let bytes: Vec<u8> = (0..length).map(|_| rand::random::<u8>()).collect();
+            assert_eq!(bytes_to_points(&bytes), bytes_to_points_n(&bytes, 5));
+        }
+    }
+
+    #[test]
+    fn test_points_n() {
+        let hex = "0123456789abcdef";
+        let bytes = b"\x05Hello*";
+        let points = bytes_to_points_n(bytes, 4);
+        assert_eq!(points.len(), bytes.len() * 2);
+        let text = points
+            .iter()
+            .map(|point| hex.as_bytes()[*point as usize] as char)
+            .collect::<String>();
+        assert_eq!(text, "508456c6c6f6a2");
+        let points = text
+            .chars()
+            .map(|c| hex.find(c).unwrap() as u8)
+            .collect::<Vec<_>>();
+        assert_eq!(points_to_bytes_n(&points, 4), bytes);
+
+        let base64 = "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+        for length in 0..64usize {
+            let bytes: Vec<u8> = (0..length).map(|_| rand::random::<u8>()).collect();
+            let points = bytes_to_points_n(&bytes, 6);
+            assert_eq!(points.len(), (length * 8).div_ceil(6));
+            let text = points
+                .iter()
+                .map(|point| base64.graphemes(true).nth(*point as usize).unwrap())
+                .collect::<String>();
+            let points_2 = text
+                .graphemes(true)
+                .map(|rune| base64.graphemes(true).position(|g| g == rune).unwrap() as u8)
+                .collect::<Vec<_>>();
+            assert_eq!(points_to_bytes_n(&points_2, 6), bytes);
+        }
+    }
+
+    #[test]
+    fn test_base_32() {
+        let test = TestStruct {
+            comments: "Hello".to_string(),
+            code: 42,
+        };
+        assert_eq!(
+            simple_generate_runes_ascii(&postcard::to_allocvec(&test).unwrap()),
+            "FASKGWRNPTKA"
+        );
+    }
+
+    #[test]
+    fn test_create_runes() {
+        let test = TestStruct {
+            comments: "Hello".to_string(),
+            code: 42,
+        };
+        // Magic!
+        assert_eq!(create_runes(&test, &FUTHARK), "ᚲᚠᛖᛁᚷᛞᛒᛈᛊᛗᛁᚠ");
+        // Faskgwarniptaka!
+        assert_eq!(create_runes(&test, &ALPHA_NUM), "FASKGWRNPTKA");
+        assert_eq!(create_runes(&"C+c", &FUTHARK), "ᚨᚡᛏᛞᛖᛒᚢ");
+    }
+
+    #[test]
+    fn test_create_runes_into() {
+        let mut out = String::from("Prefix: ");
+        create_runes_into(&("Into", 12345u32), &FUTHARK, &mut out);
+        assert_eq!(
+            out,
+            format!("Prefix: {}", create_runes(&("Into", 12345u32), &FUTHARK))
+        );
+        for length in 0..12 {
+            let value: Vec<u8> = (0..length).map(|_| rand::random()).collect();
+            out.clear();
+            create_runes_into(&value, &ALPHA_NUM, &mut out);
+            assert_eq!(out, create_runes(&value, &ALPHA_NUM));
+        }
+    }
+
+    #[test]
+    fn test_create_runes_grouped() {
+        let value = ("Grouped for humans".to_string(), 1234567u32);
+        let grouped = create_runes_grouped(&value, &ALPHA_NUM, 5, " ");
+        assert!(grouped
+            .split(' ')
+            .all(|group| group.graphemes(true).count() <= 5));
+        assert_eq!(grouped.replace(' ', ""), create_runes(&value, &ALPHA_NUM));
+        assert_eq!(read_from_runes(&grouped, &ALPHA_NUM), Some(value.clone()));
+        let grouped = create_runes_grouped(&value, &FUTHARK, 4, "\n");
+        assert_eq!(grouped.lines().next().unwrap().graphemes(true).count(), 4);
+        assert_eq!(read_from_runes(&grouped, &FUTHARK), Some(value.clone()));
+        let grouped = create_runes_grouped(&value, &FUTHARK, 3, " - ");
+        assert_eq!(read_from_runes(&grouped, &FUTHARK), Some(value.clone()));
+        assert_eq!(
+            create_runes_grouped(&value, &FUTHARK, 0, " "),
+            create_runes(&value, &FUTHARK)
+        );
+    }
+
+    #[test]
+    fn test_runes_with_format() {
+        let test = TestStruct {
+            comments: "Hello".to_string(),
+            code: 42,
+        };
+        let runes = create_runes_with_format(&test, &ALPHA_NUM, SerdeFormat::Postcard);
+        assert_eq!(runes, create_runes(&test, &ALPHA_NUM));
+        let runes = create_runes_with_format(&test, &FUTHARK, SerdeFormat::Json);
+        assert_eq!(
+            bytes_from_runes(&runes, &FUTHARK),
+            serde_json::to_vec(&test).unwrap()
+        );
+        assert_eq!(
+            read_from_runes_with_format(&runes, &FUTHARK, SerdeFormat::Json),
+            Some(test)
+        );
+        assert_eq!(
+            read_from_runes_with_format::<TestStruct>(&runes, &FUTHARK, SerdeFormat::Postcard),
+            None
+        );
+    }
+
+    #[test]
+    fn test_rune_iter() {
+        for length in 0..64usize {
+            let bytes: Vec<u8> = (0..length).map(|_| rand::random::<u8>()).collect();
+            assert_eq!(
+                bytes
+                    .clone()
+                    .into_iter()
+                    .encode_runes(&FUTHARK)
+                    .collect::<String>(),
+                generate_runes(&bytes, &FUTHARK)
+            );
+        }
+        let runes: Vec<char> = b"\x05Hello*"
+            .iter()
+            .copied()
+            .encode_runes(&ALPHA_NUM)
+            .take(3)
+            .collect();
+        assert_eq!(runes, vec!['F', 'A', 'S']);
+        // Runes of more than one char are yielded a char at a time.
+        let accented = Alphabet::new(
+            &ALPHA_NUM
+                .graphemes()
+                .map(|grapheme| format!("{}\u{0301}", grapheme))
+                .collect::<String>(),
+        )
+        .unwrap();
+        let bytes = b"\x05Hello*";
+        assert_eq!(
+            bytes
+                .iter()
+                .copied()
+                .encode_runes(&accented)
+                .collect::<String>(),
+            generate_runes(bytes, &accented)
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_rune_writer() {
+        let bytes: Vec<u8> = (0..1000).map(|_| rand::random::<u8>()).collect();
+        let mut writer = RuneWriter::new(Vec::new(), &FUTHARK);
+        for chunk in bytes.chunks(7) {
+            writer.write_all(chunk).unwrap();
+        }
+        let written = writer.finish().unwrap();
+        assert_eq!(
+            String::from_utf8(written).unwrap(),
+            generate_runes(&bytes, &FUTHARK)
+        );
+
+        let mut written = Vec::new();
+        {
+            let mut writer = RuneWriter::new(&mut written, &ALPHA_NUM);
+            writer.write_all(b"\x05Hello*").unwrap();
+        }
+        assert_eq!(String::from_utf8(written).unwrap(), "FASKGWRNPTKA");
+
+        // Flushing partway writes the partial rune, so the bytes after it start new runes.
+        let mut writer = RuneWriter::new(Vec::new(), &ALPHA_NUM);
+        writer.write_all(b"\x05He").unwrap();
+        writer.flush().unwrap();
+        assert_eq!(
+            writer.inner.as_ref().unwrap().as_slice(),
+            generate_runes(b"\x05He", &ALPHA_NUM).as_bytes()
+        );
+        writer.write_all(b"llo*").unwrap();
+        assert_eq!(
+            String::from_utf8(writer.finish().unwrap()).unwrap(),
+            generate_runes(b"\x05He", &ALPHA_NUM) + &generate_runes(b"llo*", &ALPHA_NUM)
+        );
+
+        // A writer which takes a few bytes of a write and then fails doesn't lose or repeat any runes.
+        struct Flaky {
+            written: Vec<u8>,
+            fail_after: Option<usize>,
+        }
+        impl Write for Flaky {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                match self.fail_after.take() {
+                    Some(0) => Err(std::io::Error::other("flaky")),
+                    Some(n) => {
+                        self.fail_after = Some(0);
+                        self.written.write(&buf[..n.min(buf.len())])
+                    }
+                    None => self.written.write(buf),
+                }
+            }
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+        let mut writer = RuneWriter::new(
+            Flaky {
+                written: Vec::new(),
+                fail_after: None,
+            },
+            &FUTHARK,
+        );
+        writer.write_all(b"\x05He").unwrap();
+        writer.inner.as_mut().unwrap().fail_after = Some(4);
+        writer.write_all(b"llo*").unwrap();
+        writer.inner.as_mut().unwrap().fail_after = Some(0);
+        assert!(writer.write(b"!").is_err());
+        writer.write_all(b"!").unwrap();
+        assert_eq!(
+            String::from_utf8(writer.finish().unwrap().written).unwrap(),
+            generate_runes(b"\x05Hello*!", &FUTHARK)
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_create_runes_from_reader() {
+        for length in [0, 1, 4, 5, 6, 20_001] {
+            let bytes: Vec<u8> = (0..length).map(|_| rand::random::<u8>()).collect();
+            for alphabet in [&FUTHARK, &ALPHA_NUM] {
+                assert_eq!(
+                    create_runes_from_reader(&mut bytes.as_slice(), alphabet).unwrap(),
+                    generate_runes(&bytes, alphabet)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_runes_prefix() {
+        let bytes: Vec<u8> = (0..50).map(|_| rand::random()).collect();
+        for alphabet in [&FUTHARK, &ALPHA_NUM] {
+            let runes = generate_runes(&bytes, alphabet);
+            let all = bytes_from_runes(&runes, alphabet);
+            for max_bytes in [0, 1, 2, 5, 13, 50, 100] {
+                assert_eq!(
+                    parse_runes_prefix(&runes, alphabet, max_bytes),
+                    all[..max_bytes.min(all.len())]
+                );
+            }
+        }
+        // A tag at the front can be read without the rest of the value.
+        let runes = create_runes(&(3u8, "The rest of a long value"), &FUTHARK);
+        assert_eq!(parse_runes_prefix(&runes, &FUTHARK, 1), vec![3]);
+    }
+
+    #[test]
+    fn test_fill_template() {
+        let runes = create_runes(&("Hidden", 7u32), &FUTHARK);
+        let template = "~~ Here lies {runes}, carved in stone ~~";
+        let filled = fill_template(template, "{runes}", &runes).unwrap();
+        assert_eq!(
+            extract_from_template(&filled, template, "{runes}"),
+            Some(runes.as_str())
+        );
+        assert_eq!(
+            read_from_runes::<(String, u32)>(&filled, &FUTHARK),
+            Some(("Hidden".to_string(), 7))
+        );
+        // Prose full of the alphabet's letters can only be read back through the template.
+        let runes = create_runes(&("Hidden", 7u32), &ALPHA_NUM);
+        let filled = fill_template(template, "{runes}", &runes).unwrap();
+        let extracted = extract_from_template(&filled, template, "{runes}").unwrap();
+        assert_eq!(
+            read_from_runes::<(String, u32)>(extracted, &ALPHA_NUM),
+            Some(("Hidden".to_string(), 7))
+        );
+        assert_eq!(fill_template("No token", "{runes}", &runes), None);
+        assert_eq!(
+            extract_from_template("Other text", template, "{runes}"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_runes_with_options() {
+        let recorded = EncodeOptions {
+            pad: PadMode::Recorded,
+            ..Default::default()
+        };
+        assert_eq!(
+            generate_runes_with_options(b"Zero", &FUTHARK, &EncodeOptions::default()),
+            generate_runes(b"Zero", &FUTHARK)
+        );
+        for length in 0..12 {
+            let bytes: Vec<u8> = (0..length).map(|_| rand::random()).collect();
+            let runes = generate_runes_with_options(&bytes, &FUTHARK, &recorded);
+            assert_eq!(
+                runes.graphemes(true).count(),
+                generate_runes(&bytes, &FUTHARK).graphemes(true).count() + 1
+            );
+            assert_eq!(
+                parse_runes_with_options(&runes, &FUTHARK, &recorded),
+                Some(bytes)
+            );
+        }
+        // 3 bytes take 5 runes with 1 bit of padding, so dropping or adding a data rune is noticed.
+        let runes = generate_runes_with_options(b"abc", &ALPHA_NUM, &recorded);
+        assert_eq!(&runes[5..], "B");
+        assert_eq!(
+            parse_runes_with_options(&runes[1..], &ALPHA_NUM, &recorded),
+            None
+        );
+        assert_eq!(
+            parse_runes_with_options(&format!("A{}", runes), &ALPHA_NUM, &recorded),
+            None
+        );
+        assert_eq!(parse_runes_with_options("", &ALPHA_NUM, &recorded), None);
+    }
+
+    #[test]
+    fn test_try_read_from_runes() {
+        let value = ("Hello, runes".to_string(), 42u32);
+        let runes = create_runes(&value, &FUTHARK);
+        assert_eq!(try_read_from_runes(&runes, &FUTHARK), Ok(value));
+        let cut: String = runes.chars().take(runes.chars().count() / 2).collect();
+        let err = try_read_from_runes::<(String, u32)>(&cut, &FUTHARK).unwrap_err();
+        assert!(err.is_truncated());
+        assert_eq!(
+            err.postcard_error(),
+            &postcard::Error::DeserializeUnexpectedEnd
+        );
+        // Every byte is there, but 2 isn't a bool.
+        let err = try_read_from_runes::<bool>(&create_runes(&2u8, &FUTHARK), &FUTHARK).unwrap_err();
+        assert_eq!(
+            err,
+            DecodeError::Mismatch(postcard::Error::DeserializeBadBool)
+        );
+    }
+
+    #[test]
+    fn test_permuted_alphabet() {
+        let permuted = permuted_alphabet(&FUTHARK, 1234);
+        assert_eq!(permuted, permuted_alphabet(&FUTHARK, 1234));
+        assert_ne!(permuted, FUTHARK);
+        assert_ne!(permuted, permuted_alphabet(&FUTHARK, 1235));
+        assert_eq!(Alphabet::new(permuted.as_str()), Ok(permuted.clone()));
+        let mut sorted: Vec<&str> = permuted.graphemes().collect();
+        let mut base: Vec<&str> = FUTHARK.graphemes().collect();
+        sorted.sort();
+        base.sort();
+        assert_eq!(sorted, base);
+
+        let value = ("Hidden".to_string(), 7u32);
+        let runes = create_runes(&value, &permuted);
+        assert_ne!(runes, create_runes(&value, &FUTHARK));
+        assert_eq!(read_from_runes(&runes, &permuted), Some(value.clone()));
+        assert_ne!(read_from_runes(&runes, &FUTHARK), Some(value));
+    }
+
+    #[test]
+    fn test_runes_rows() {
+        let value = ("A rectangular block of runes".to_string(), 1234u32);
+        let bytes = postcard::to_allocvec(&value).unwrap();
+        let options = EncodeOptions::default().rows_of(8);
+        let block = generate_runes_with_options(&bytes, &FUTHARK, &options);
+        let rows: Vec<&str> = block.split('\n').collect();
+        let (last, full) = rows.split_last().unwrap();
+        assert!(full.iter().all(|row| row.graphemes(true).count() == 8));
+        assert!((1..=8).contains(&last.graphemes(true).count()));
+        assert_eq!(block.replace('\n', ""), generate_runes(&bytes, &FUTHARK));
+        assert_eq!(
+            parse_runes_with_options(&block, &FUTHARK, &options),
+            Some(bytes.clone())
+        );
+        assert_eq!(read_from_runes(&block, &FUTHARK), Some(value));
+
+        let options = EncodeOptions {
+            pad: PadMode::Recorded,
+            sentinel: Some("\u{00B7}"),
+            ..Default::default()
+        }
+        .rows_of(5);
+        let block = generate_runes_with_options(&bytes, &ALPHA_NUM, &options);
+        assert!(block.ends_with('\u{00B7}'));
+        assert_eq!(
+            parse_runes_with_options(&block, &ALPHA_NUM, &options),
+            Some(bytes)
+        );
+    }
+
+    #[test]
+    fn test_runes_sentinel() {
+        let options = EncodeOptions {
+            sentinel: Some("\u{00B7}"),
+            ..Default::default()
+        };
+        let recorded = EncodeOptions {
+            pad: PadMode::Recorded,
+            ..options
+        };
+        let codes: Vec<Vec<u8>> = vec![b"First".to_vec(), vec![], b"Third code".to_vec()];
+        for options in [options, recorded] {
+            let stream: String = codes
+                .iter()
+                .map(|code| generate_runes_with_options(code, &FUTHARK, &options))
+                .collect();
+            assert_eq!(
+                parse_runes_with_options(&stream, &FUTHARK, &options),
+                Some(codes[0].clone())
+            );
+            assert_eq!(
+                parse_runes_stream(&stream, &FUTHARK, &options),
+                Some(codes.clone())
+            );
+            // The last code is missing its sentinel, so it may have been cut short.
+            let cut = stream.trim_end_matches('\u{00B7}');
+            assert_eq!(parse_runes_stream(cut, &FUTHARK, &options), None);
+            assert_eq!(
+                parse_runes_with_options(&generate_runes(b"First", &FUTHARK), &FUTHARK, &options),
+                None
+            );
+        }
+        assert_eq!(
+            parse_runes_stream("", &FUTHARK, &EncodeOptions::default()),
+            None
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_runes_sentinel_in_alphabet() {
+        generate_runes_with_options(
+            b"Bad",
+            &ALPHA_NUM,
+            &EncodeOptions {
+                sentinel: Some("Z"),
+                ..Default::default()
+            },
+        );
+    }
+
+    #[test]
+    fn test_runes_framed() {
+        let test = TestStruct {
+            comments: "Hello".to_string(),
+            code: 42,
+        };
+        let runes = create_runes_framed(&test, &FUTHARK);
+        assert_eq!(runes, "ᚹᚲᚠᛖᛁᚷᛞᛒᛈᛊᛗᛁᚠ");
+        assert_eq!(read_from_runes_framed(&runes, &FUTHARK), Some(test));
+        assert_eq!(generate_runes_framed(&[], &ALPHA_NUM), "A");
+        assert_eq!(parse_runes_framed("A", &ALPHA_NUM), Some(vec![]));
+        assert_eq!(parse_runes_framed("", &ALPHA_NUM), None);
+    }
+
+    #[test]
+    fn test_runes_many() {
+        let items = vec![
+            TestStruct {
+                comments: "Hello".to_string(),
+                code: 42,
+            },
+            TestStruct {
+                comments: String::new(),
+                code: 0,
+            },
+            TestStruct {
+                comments: "x".repeat(200),
+                code: 7,
+            },
+        ];
+        let runes = create_runes_many(&items, &FUTHARK);
+        assert_eq!(read_runes_many(&runes, &FUTHARK), Ok(items));
+        assert_eq!(
+            read_runes_many::<TestStruct>(
+                &create_runes_many::<TestStruct>(&[], &FUTHARK),
+                &FUTHARK
+            ),
+            Ok(vec![])
+        );
+        // Dropping the last few runes cuts off the end of the last item.
+        let truncated: String = runes.chars().take(runes.chars().count() - 4).collect();
+        assert_eq!(
+            read_runes_many::<TestStruct>(&truncated, &FUTHARK),
+            Err(RuneError::Truncated)
+        );
+    }
+
+    #[test]
+    fn test_runes_checked() {
+        assert_eq!(crc16(b"123456789"), 0x29b1);
+        let test = TestStruct {
+            comments: "Hello".to_string(),
+            code: 42,
+        };
+        let runes = create_runes_checked(&test, &FUTHARK);
+        assert_eq!(read_from_runes_checked(&runes, &FUTHARK), Ok(test));
+        // Substitute a single rune in the payload.
+        let mut corrupted = runes.graphemes(true).collect::<Vec<_>>();
+        corrupted[3] = if corrupted[3] == "ᚠ" { "ᚢ" } else { "ᚠ" };
+        let corrupted = corrupted.concat();
+        assert!(matches!(
+            read_from_runes_checked::<TestStruct>(&corrupted, &FUTHARK),
+            Err(IntegrityError::ChecksumMismatch { .. })
+        ));
+        // Drop the end of the checksum.
+        let truncated = runes.graphemes(true).take(12).collect::<String>();
+        assert_eq!(
+            read_from_runes_checked::<TestStruct>(&truncated, &FUTHARK),
+            Err(IntegrityError::Malformed)
+        );
+        let runes = create_runes_checked(&"Hello", &FUTHARK);
+        assert!(matches!(
+            read_from_runes_checked::<TestStruct>(&runes, &FUTHARK),
+            Err(IntegrityError::Deserialize(_))
+        ));
+    }
+
+    #[test]
+    fn framed_stress_test() {
+        for length in 0..=256 {
+            let bytes: Vec<u8> = (0..length).map(|_| rand::random::<u8>()).collect();
+            let runes = generate_runes_framed(&bytes, &FUTHARK);
+            assert_eq!(parse_runes_framed(&runes, &FUTHARK), Some(bytes));
+        }
+    }
+}
@@ -1,3 +1,13 @@
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+extern crate alloc;
+
+mod format;
+pub use format::*;
+
+mod encoding;
+pub use encoding::*;
+
 #[cfg(feature = "futhark")]
 mod futhark;
 #[cfg(feature = "futhark")]
@@ -13,7 +23,22 @@ mod cursed;
 #[cfg(feature = "cursed")]
 pub use cursed::*;
 
-#[cfg(feature = "clipboard")]
+#[cfg(all(feature = "clipboard", not(target_arch = "wasm32")))]
 mod clipboard;
-#[cfg(feature = "clipboard")]
-pub use clipboard::*;
\ No newline at end of file
+#[cfg(all(feature = "clipboard", not(target_arch = "wasm32")))]
+pub use clipboard::*;
+
+#[cfg(feature = "compression")]
+mod compression;
+#[cfg(feature = "compression")]
+pub use compression::*;
+
+#[cfg(feature = "ecc")]
+mod ecc;
+#[cfg(feature = "ecc")]
+pub use ecc::*;
+
+#[cfg(feature = "wasm")]
+mod wasm;
+#[cfg(feature = "wasm")]
+pub use wasm::*;
@@ -0,0 +1,116 @@
+use wasm_bindgen::prelude::*;
+
+use crate::{CurseError, CursedConfig, DecodeError, RuneError, FUTHARK};
+
+/**
+An error for JavaScript callers, with a stable kind to match on and a message to show.
+*/
+#[wasm_bindgen(getter_with_clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EncodingFailure {
+    pub kind: String,
+    pub message: String,
+}
+
+impl From<RuneError> for EncodingFailure {
+    fn from(err: RuneError) -> Self {
+        let kind = match err {
+            RuneError::UnknownGrapheme { .. } => "UnknownGrapheme",
+            RuneError::Truncated => "Truncated",
+            RuneError::Deserialize(_) => "Deserialize",
+            RuneError::AlphabetTooSmall { .. } => "AlphabetTooSmall",
+        };
+        EncodingFailure {
+            kind: kind.to_string(),
+            message: err.to_string(),
+        }
+    }
+}
+
+impl From<CurseError> for EncodingFailure {
+    fn from(err: CurseError) -> Self {
+        let kind = match err {
+            CurseError::InvalidPointCount { .. } => "InvalidPointCount",
+            CurseError::PointsOutOfRange => "PointsOutOfRange",
+            CurseError::TooManyDiacritics { .. } => "TooManyDiacritics",
+            CurseError::InsufficientCarrierText { .. } => "InsufficientCarrierText",
+            CurseError::BreakRequired => "BreakRequired",
+            CurseError::Decode(DecodeError::Truncated(_)) => "Truncated",
+            CurseError::Decode(DecodeError::Mismatch(_)) => "Mismatch",
+            CurseError::BreakContainsDiacritic { .. } => "BreakContainsDiacritic",
+            CurseError::MissingChecksum => "MissingChecksum",
+            CurseError::ChecksumMismatch { .. } => "ChecksumMismatch",
+            CurseError::UnknownDiacriticSet => "UnknownDiacriticSet",
+            CurseError::MissingHeader => "MissingHeader",
+        };
+        EncodingFailure {
+            kind: kind.to_string(),
+            message: err.to_string(),
+        }
+    }
+}
+
+/**
+Writes bytes as futhark runes.
+*/
+#[wasm_bindgen]
+pub fn encode_runes(bytes: &[u8]) -> String {
+    crate::generate_runes(bytes, &FUTHARK)
+}
+
+/**
+Reads bytes back from futhark runes, skipping anything which isn't a rune.
+*/
+#[wasm_bindgen]
+pub fn decode_runes(runes: &str) -> Vec<u8> {
+    crate::bytes_from_runes(runes, &FUTHARK)
+}
+
+/**
+Reads bytes back from futhark runes, failing on anything which isn't a rune.
+*/
+#[wasm_bindgen]
+pub fn decode_runes_checked(runes: &str) -> Result<Vec<u8>, EncodingFailure> {
+    Ok(crate::parse_runes_checked(runes, &FUTHARK)?)
+}
+
+/**
+Spreads bytes over the cover text as diactrics, with the default config.
+*/
+#[wasm_bindgen]
+pub fn encode_cursed(bytes: &[u8], text: &str) -> Result<String, EncodingFailure> {
+    Ok(CursedConfig::new().generate_curse(text, bytes)?)
+}
+
+/**
+Reads bytes back from cursed text.
+*/
+#[wasm_bindgen]
+pub fn decode_cursed(text: &str) -> Result<Vec<u8>, EncodingFailure> {
+    Ok(crate::bytes_from_curse_with_set(
+        text,
+        &crate::DiacriticSet::basic(),
+    )?)
+}
+
+#[cfg(test)]
+mod wasm_tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trips() {
+        let bytes = b"Hello from the browser";
+        assert_eq!(decode_runes(&encode_runes(bytes)), bytes);
+        assert_eq!(decode_runes_checked(&encode_runes(bytes)).unwrap(), bytes);
+        let curse = encode_cursed(bytes, "Nothing to see here, move along").unwrap();
+        assert_eq!(decode_cursed(&curse).unwrap(), bytes);
+    }
+
+    #[test]
+    fn test_structured_errors() {
+        let err = decode_runes_checked("not runes").unwrap_err();
+        assert_eq!(err.kind, "UnknownGrapheme");
+        let err = decode_cursed("a\u{0300}").unwrap_err();
+        assert_eq!(err.kind, "InvalidPointCount");
+    }
+}